@@ -7,7 +7,7 @@ pub trait Address:
 }
 
 pub trait AccountMeta {
-    fn balance(&self) -> u64;
+    fn balance(&self) -> u128;
 }
 
 pub trait Blockchain {
@@ -53,7 +53,7 @@ pub trait Block {
         caller: Self::Address,
         callee: Self::Address,
         payer: Self::Address,
-        value: u64,
+        value: u128,
         input: &[u8],
         gas: u64,
         gas_price: u64,
@@ -88,21 +88,46 @@ pub trait PendingTransaction {
     fn sender(&self) -> &Self::Address;
 
     /// Returns the value sent to the current transaction.
-    fn value(&self) -> u64;
+    fn value(&self) -> u128;
 
     /// Returns the input provided by the calling context.
     fn input(&self) -> &[u8];
 
+    /// Returns the additional authenticated data that accompanied the input
+    /// of a confidential transaction, or an empty slice.
+    fn aad(&self) -> &[u8];
+
     /// Executes a balance-transferring RPC to `callee` with provided input and value.
     /// The new transaction will inherit the gas parameters and gas payer of the top level
     /// transaction. The current account will be set as the sender.
     fn transact(
         &mut self,
         callee: Self::Address,
-        value: u64,
+        value: u128,
         input: &[u8],
     ) -> Box<dyn Receipt<Address = Self::Address>>;
 
+    /// Replaces the code of the account at `addr` with `code` and runs the migration of the
+    /// new code with `migration_input`. The current account must be the account at `addr`
+    /// or its admin. The migration inherits the gas parameters of the top level transaction.
+    fn upgrade(
+        &mut self,
+        addr: Self::Address,
+        code: &[u8],
+        migration_input: &[u8],
+    ) -> Box<dyn Receipt<Address = Self::Address>>;
+
+    /// Creates a new account having `code` and transfers `value` tokens to it, then runs the
+    /// constructor of `code` with `ctor_input`. The current account will be set as the sender.
+    /// The callee of the returned receipt is the address of the new account, which exists
+    /// only if the constructor succeeded.
+    fn create(
+        &mut self,
+        value: u128,
+        code: &[u8],
+        ctor_input: &[u8],
+    ) -> Box<dyn Receipt<Address = Self::Address>>;
+
     /// Returns data to the calling transaction.
     fn ret(&mut self, data: &[u8]);
 
@@ -194,6 +219,7 @@ pub enum TransactionOutcome {
     NoAccount,
     Aborted, // recoverable error
     Fatal,
+    PermissionDenied,
 }
 
 impl TransactionOutcome {
@@ -1,8 +1,11 @@
 #[macro_use]
 extern crate serde;
 
-use map_vec::{map::Entry, Map, Set};
-use oasis_std::{Address, Context, Event};
+use map_vec::{map::Entry, Map};
+use oasis_std::{
+    access::{AccessControl, AccessControlled, AccessError},
+    Address, Context, Event,
+};
 
 pub type Result<T> = std::result::Result<T, Error>;
 
@@ -25,13 +28,24 @@ pub enum Error {
         amount, allowance
     )]
     RequestExceedsAllowance { amount: u64, allowance: u64 },
+
+    #[fail(display = "The token is paused.")]
+    Paused,
+}
+
+impl From<AccessError> for Error {
+    fn from(err: AccessError) -> Self {
+        match err {
+            AccessError::NotPermitted => Error::AdminPrivilegesRequired,
+            AccessError::Paused => Error::Paused,
+        }
+    }
 }
 
 #[derive(oasis_std::Service, Default)]
 pub struct ERC20Token {
     total_supply: u64,
-    owner: Address,
-    admins: Set<Address>,
+    access: AccessControl,
     accounts: Map<Address, u64>,
     allowed: Map<Address, Map<Address, u64>>,
 }
@@ -58,19 +72,24 @@ pub struct Approval {
     pub amount: u64,
 }
 
+impl AccessControlled for ERC20Token {
+    fn access_control(&self) -> &AccessControl {
+        &self.access
+    }
+}
+
 impl ERC20Token {
     /// Constructs a new `ERC20Token`
     pub fn new(ctx: &Context, total_supply: u64) -> Result<Self> {
         let owner = ctx.sender();
-        let mut admins = Set::new();
-        admins.insert(owner);
+        let mut access = AccessControl::new(owner);
+        access.grant_role("admin", owner);
         let mut accounts = Map::new();
         accounts.insert(owner, total_supply);
 
         Ok(Self {
             total_supply,
-            owner,
-            admins,
+            access,
             accounts,
             ..Default::default()
         })
@@ -91,11 +110,23 @@ impl ERC20Token {
     }
 
     /// Add admin
-    pub fn add_admin(&mut self, ctx: &Context, admin: Address) -> Result<()> {
-        if !self.admins.contains(&ctx.sender()) {
-            return Err(Error::AdminPrivilegesRequired);
-        }
-        self.admins.insert(admin);
+    #[oasis_std::only(role = "admin")]
+    pub fn add_admin(&mut self, _ctx: &Context, admin: Address) -> Result<()> {
+        self.access.grant_role("admin", admin);
+        Ok(())
+    }
+
+    /// Pause transfers, minting, and burning
+    #[oasis_std::only(owner)]
+    pub fn pause(&mut self, _ctx: &Context) -> Result<()> {
+        self.access.pause();
+        Ok(())
+    }
+
+    /// Unpause transfers, minting, and burning
+    #[oasis_std::only(owner)]
+    pub fn unpause(&mut self, _ctx: &Context) -> Result<()> {
+        self.access.unpause();
         Ok(())
     }
 }
@@ -121,6 +152,7 @@ fn do_transfer(accounts: &mut Map<Address, u64>, from: Address, to: Address, amo
 
 impl ERC20Token {
     /// transfer
+    #[oasis_std::when_not_paused]
     pub fn transfer(&mut self, ctx: &Context, to: Address, amount: u64) -> Result<Transfer> {
         let from = ctx.sender();
         if from == to || amount == 0u64 {
@@ -166,6 +198,7 @@ impl ERC20Token {
     }
 
     /// transfer from a given account up to the given allowance
+    #[oasis_std::when_not_paused]
     pub fn transfer_from(
         &mut self,
         _ctx: &Context,
@@ -198,19 +231,17 @@ impl ERC20Token {
 
 impl ERC20Token {
     /// mint new tokens
-    pub fn mint(&mut self, ctx: &Context, amount: u64) -> Result<()> {
-        if !self.admins.contains(&ctx.sender()) {
-            return Err(Error::AdminPrivilegesRequired);
-        }
+    #[oasis_std::only(role = "admin")]
+    #[oasis_std::when_not_paused]
+    pub fn mint(&mut self, _ctx: &Context, amount: u64) -> Result<()> {
         self.total_supply += amount;
         Ok(())
     }
 
     /// burn tokens from a given account
-    pub fn burn(&mut self, ctx: &Context, from: Address, amount: u64) -> Result<()> {
-        if !self.admins.contains(&ctx.sender()) {
-            return Err(Error::AdminPrivilegesRequired);
-        }
+    #[oasis_std::only(role = "admin")]
+    #[oasis_std::when_not_paused]
+    pub fn burn(&mut self, _ctx: &Context, from: Address, amount: u64) -> Result<()> {
         let balance = self.accounts.get(&from).copied().unwrap_or_default();
         self.accounts
             .insert(from, std::cmp::max(0, balance - amount));
@@ -236,6 +267,21 @@ mod tests {
         (addr, ctx)
     }
 
+    /// Checks the access-control attributes of an RPC against the sender of `ctx`,
+    /// as the generated dispatcher does before calling the RPC.
+    fn check_access(
+        erc20: &ERC20Token,
+        ctx: &Context,
+        owner: bool,
+        roles: &[&str],
+        when_not_paused: bool,
+    ) -> Result<()> {
+        erc20
+            .access_control()
+            .check(&ctx.sender(), owner, roles, when_not_paused)
+            .map_err(Error::from)
+    }
+
     #[test]
     fn happy_paths() {
         let (_getafix, gctx) = create_account();
@@ -264,4 +310,45 @@ mod tests {
         balance = erc20.balance_of(&bctx).unwrap();
         assert_eq!(balance, 400u64);
     }
+    #[test]
+    fn denied_paths() {
+        let (_getafix, gctx) = create_account();
+        let (caesar, cctx) = create_account();
+
+        let mut erc20 = ERC20Token::new(&gctx, 1000).unwrap();
+
+        // Caesar is not an admin, so may neither mint nor add admins
+        assert_eq!(
+            check_access(&erc20, &cctx, false, &["admin"], true),
+            Err(Error::AdminPrivilegesRequired)
+        );
+        assert_eq!(
+            check_access(&erc20, &cctx, false, &["admin"], false),
+            Err(Error::AdminPrivilegesRequired)
+        );
+        assert_eq!(
+            check_access(&erc20, &cctx, true, &[], false),
+            Err(Error::AdminPrivilegesRequired)
+        );
+
+        // Getafix adds Caesar as an admin, who may then mint
+        check_access(&erc20, &gctx, false, &["admin"], false).unwrap();
+        erc20.add_admin(&gctx, caesar).unwrap();
+        check_access(&erc20, &cctx, false, &["admin"], true).unwrap();
+
+        // Getafix, the owner, pauses the token, so nobody may mint or transfer
+        check_access(&erc20, &gctx, true, &[], false).unwrap();
+        erc20.pause(&gctx).unwrap();
+        assert_eq!(
+            check_access(&erc20, &cctx, false, &["admin"], true),
+            Err(Error::Paused)
+        );
+        assert_eq!(
+            check_access(&erc20, &gctx, false, &[], true),
+            Err(Error::Paused)
+        );
+
+        erc20.unpause(&gctx).unwrap();
+        check_access(&erc20, &gctx, false, &[], true).unwrap();
+    }
 }
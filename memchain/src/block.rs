@@ -1,24 +1,150 @@
 use blockchain_traits::TransactionOutcome;
 use oasis_types::{AccountMeta, Address};
 
-use crate::{output::Receipt, pending_transaction::PendingTransaction, State};
+use crate::{
+    confidential, output::Receipt, pending_transaction::PendingTransaction, AccountMain, Codes,
+    State,
+};
 
 #[derive(Debug)]
 pub struct Block<'bc> {
     pub base_gas: u64,
+    pub gas_limit: u64,
+    pub gas_used: u64,
     pub height: u64,
     pub state: State<'bc>,
+    pub codes: Codes,
     pub completed_transactions: Vec<Receipt>,
 }
 
+/// The replacement code of an account and the means to migrate its state.
+pub struct Upgrade<'a> {
+    pub code: Vec<u8>,
+    pub main: Option<AccountMain>,
+
+    /// An entrypoint that is called once, with `migration_input`, after the code
+    /// has been replaced. The upgrade is reverted if the migration fails.
+    pub migrate: Option<AccountMain>,
+    pub migration_input: &'a [u8],
+}
+
 impl<'bc> Block<'bc> {
-    pub fn new(height: u64, state: State<'bc>, base_gas: u64) -> Self {
+    pub fn new(
+        height: u64,
+        state: State<'bc>,
+        base_gas: u64,
+        gas_limit: u64,
+        codes: Codes,
+    ) -> Self {
         Self {
             height,
             state,
+            codes,
             completed_transactions: Vec::new(),
             base_gas,
+            gas_limit,
+            gas_used: 0,
+        }
+    }
+
+    /// Replaces the code of the account at `addr`. The upgrade must be performed
+    /// by the account itself or by its admin. `gas` bounds the computation
+    /// performed by the migration, and the `payer` is charged `gas * gas_price` for it.
+    #[allow(clippy::too_many_arguments)]
+    pub fn upgrade(
+        &mut self,
+        caller: Address,
+        addr: Address,
+        payer: Address,
+        upgrade: Upgrade,
+        gas: u64,
+        gas_price: u64,
+    ) -> Box<dyn blockchain_traits::Receipt<Address = Address>> {
+        let mut receipt = Receipt {
+            caller,
+            callee: addr,
+            value: 0,
+            gas_used: gas,
+            output: Vec::new(),
+            events: Vec::new(),
+            outcome: TransactionOutcome::Success,
+        };
+
+        macro_rules! early_return {
+            ($outcome:ident) => {{
+                receipt.outcome = TransactionOutcome::$outcome;
+                self.completed_transactions.push(receipt.clone());
+                return box receipt;
+            }};
+        }
+
+        let admin = match self.state.get(&addr) {
+            Some(acct) => acct.admin,
+            None => early_return!(NoAccount),
+        };
+
+        if caller != addr && admin != Some(caller) {
+            early_return!(PermissionDenied);
+        }
+
+        if gas < self.base_gas || gas > self.gas_limit - self.gas_used {
+            early_return!(InsufficientGas);
+        }
+
+        if let Err(outcome) = self.charge_gas(&payer, gas, gas_price) {
+            receipt.outcome = outcome;
+            self.completed_transactions.push(receipt.clone());
+            return box receipt;
         }
+
+        let mut pending_transaction = PendingTransaction {
+            caller,
+            callee: addr,
+            value: 0,
+            input: upgrade.migration_input.to_vec(),
+            aad: Vec::new(),
+            outcome: TransactionOutcome::Success,
+            state: self.state.clone(),
+            codes: self.codes.clone(),
+            events: Vec::new(),
+            output: Vec::new(),
+            base_gas: self.base_gas,
+            gas_left: gas - self.base_gas,
+        };
+        pending_transaction.replace_code(upgrade.code, upgrade.main, upgrade.migrate);
+
+        receipt.outcome = pending_transaction.outcome;
+        receipt.output = pending_transaction.output;
+        if blockchain_traits::Receipt::reverted(&receipt) {
+            receipt.events.clear();
+        } else {
+            self.state = pending_transaction.state;
+            receipt.events = pending_transaction.events;
+        }
+        self.completed_transactions.push(receipt.clone());
+        box receipt
+    }
+
+    /// Charges the `payer` for `gas` at `gas_price` and adds `gas` to the gas used
+    /// by this block. A payer who cannot afford the gas loses their entire balance.
+    fn charge_gas(
+        &mut self,
+        payer: &Address,
+        gas: u64,
+        gas_price: u64,
+    ) -> Result<(), TransactionOutcome> {
+        let payer_acct = match self.state.get_mut(payer) {
+            Some(payer_acct) => payer_acct.to_mut(),
+            None => return Err(TransactionOutcome::NoAccount),
+        };
+        let gas_cost = u128::from(gas) * u128::from(gas_price);
+        if payer_acct.balance < gas_cost {
+            payer_acct.balance = 0;
+            return Err(TransactionOutcome::InsufficientFunds);
+        }
+        payer_acct.balance -= gas_cost;
+        self.gas_used += gas;
+        Ok(())
     }
 }
 
@@ -35,7 +161,7 @@ impl<'bc> blockchain_traits::Block for Block<'bc> {
         caller: Self::Address,
         callee: Self::Address,
         payer: Self::Address,
-        value: u64,
+        value: u128,
         input: &[u8],
         gas: u64,
         gas_price: u64,
@@ -62,21 +188,25 @@ impl<'bc> blockchain_traits::Block for Block<'bc> {
             early_return!(NoAccount);
         }
 
-        if gas < self.base_gas {
+        if gas < self.base_gas || gas > self.gas_limit - self.gas_used {
             early_return!(InsufficientGas);
         }
 
-        match self.state.get_mut(&payer) {
-            Some(payer_acct) => {
-                let payer_acct = payer_acct.to_mut();
-                let gas_cost = gas * gas_price;
-                if payer_acct.balance < gas_cost {
-                    payer_acct.balance = 0;
-                    early_return!(InsufficientFunds);
+        if let Err(outcome) = self.charge_gas(&payer, gas, gas_price) {
+            receipt.outcome = outcome;
+            self.completed_transactions.push(receipt.clone());
+            return box receipt;
+        }
+
+        let (input, aad, session) = match self.state.get(&callee).unwrap().confidential_key {
+            Some(account_key) => {
+                let session_key = confidential::session_key(&account_key, &caller);
+                match confidential::open_input(&session_key, input) {
+                    Some((input, aad, nonce)) => (input, aad, Some((session_key, nonce))),
+                    None => early_return!(InvalidInput),
                 }
-                payer_acct.balance -= gas_cost;
             }
-            None => early_return!(NoAccount),
+            None => (input.to_vec(), Vec::new(), None),
         };
 
         let mut ptx_state = self.state.clone();
@@ -98,9 +228,11 @@ impl<'bc> blockchain_traits::Block for Block<'bc> {
             caller,
             callee,
             value,
-            input: input.to_vec(),
+            input,
+            aad,
             outcome: TransactionOutcome::Success,
             state: ptx_state,
+            codes: self.codes.clone(),
             events: Vec::new(),
             output: Vec::new(),
             base_gas: self.base_gas,
@@ -108,23 +240,19 @@ impl<'bc> blockchain_traits::Block for Block<'bc> {
         };
 
         if let Some(main) = self.state.get(&callee).unwrap().main {
-            let ptx: &mut dyn blockchain_traits::PendingTransaction<
-                Address = Address,
-                AccountMeta = AccountMeta,
-            > = &mut pending_transaction;
-            let errno = main(unsafe {
-                // Extend the lifetime, as required by the FFI type.
-                // This is only unsafe if the `main` fn stores the pointer,
-                // but this is disallowed by the precondition on `main`.
-                &(std::mem::transmute::<&mut _, &'static mut _>(ptx) as *mut _) as *const _
-            });
-            if errno != 0 {
-                pending_transaction.outcome = TransactionOutcome::Aborted;
-            }
+            pending_transaction.call_main(main);
         }
 
         receipt.outcome = pending_transaction.outcome;
-        receipt.output = pending_transaction.output;
+        receipt.output = match session {
+            Some((session_key, nonce)) => confidential::seal(
+                &session_key,
+                &confidential::output_nonce(&nonce),
+                &pending_transaction.output,
+                &pending_transaction.aad,
+            ),
+            None => pending_transaction.output,
+        };
         if blockchain_traits::Receipt::reverted(&receipt) {
             receipt.events.clear();
         } else {
@@ -0,0 +1,136 @@
+//! Simulated confidential execution.
+//!
+//! The AEAD in this module is constructed from Keccak so that memchain can model the
+//! semantics of sealed inputs, outputs, and storage without a dedicated cipher.
+//! It is only suitable for testing and must not be used to protect real data.
+
+use oasis_types::Address;
+use tiny_keccak::keccak256;
+
+pub const KEY_SIZE: usize = 32;
+pub const NONCE_SIZE: usize = 16;
+pub const TAG_SIZE: usize = 16;
+
+pub type Key = [u8; KEY_SIZE];
+pub type Nonce = [u8; NONCE_SIZE];
+
+/// Returns the key shared by `caller` and the confidential account having `account_key`.
+/// The caller uses this key to seal inputs to, and open outputs from, the account.
+pub fn session_key(account_key: &Key, caller: &Address) -> Key {
+    hash(&[b"session", account_key, caller.as_ref()])
+}
+
+/// Encrypts and authenticates `plaintext` and authenticates `aad`.
+/// Returns `nonce || ciphertext || tag`.
+pub fn seal(key: &Key, nonce: &Nonce, plaintext: &[u8], aad: &[u8]) -> Vec<u8> {
+    let mut sealed = Vec::with_capacity(NONCE_SIZE + plaintext.len() + TAG_SIZE);
+    sealed.extend_from_slice(nonce);
+    sealed.extend(
+        plaintext
+            .iter()
+            .zip(keystream(key, nonce))
+            .map(|(p, k)| p ^ k),
+    );
+    let tag = tag(key, nonce, aad, &sealed[NONCE_SIZE..]);
+    sealed.extend_from_slice(&tag);
+    sealed
+}
+
+/// Opens a message created by `seal`. Returns `None` if the message was not sealed
+/// using `key` and `aad` or if it has been modified.
+pub fn open(key: &Key, sealed: &[u8], aad: &[u8]) -> Option<Vec<u8>> {
+    if sealed.len() < NONCE_SIZE + TAG_SIZE {
+        return None;
+    }
+    let (nonce_bytes, rest) = sealed.split_at(NONCE_SIZE);
+    let (ciphertext, tag_bytes) = rest.split_at(rest.len() - TAG_SIZE);
+    let mut nonce = Nonce::default();
+    nonce.copy_from_slice(nonce_bytes);
+    if tag(key, &nonce, aad, ciphertext) != tag_bytes {
+        return None;
+    }
+    Some(
+        ciphertext
+            .iter()
+            .zip(keystream(key, &nonce))
+            .map(|(c, k)| c ^ k)
+            .collect(),
+    )
+}
+
+/// Creates the input of a transaction to a confidential account.
+/// The AAD is sent in the clear, along with the sealed input, and is made available
+/// to the callee. The output of the transaction is sealed to the same key and AAD
+/// and can be opened using `open`.
+pub fn seal_input(session_key: &Key, nonce: &Nonce, input: &[u8], aad: &[u8]) -> Vec<u8> {
+    let mut envelope = Vec::with_capacity(4 + aad.len() + NONCE_SIZE + input.len() + TAG_SIZE);
+    envelope.extend_from_slice(&(aad.len() as u32).to_le_bytes());
+    envelope.extend_from_slice(aad);
+    envelope.append(&mut seal(session_key, nonce, input, aad));
+    envelope
+}
+
+/// Opens a transaction input created by `seal_input`. Returns the plaintext input,
+/// the AAD, and the nonce with which the input was sealed.
+pub(crate) fn open_input(session_key: &Key, envelope: &[u8]) -> Option<(Vec<u8>, Vec<u8>, Nonce)> {
+    if envelope.len() < 4 {
+        return None;
+    }
+    let (aad_len_bytes, rest) = envelope.split_at(4);
+    let mut aad_len = [0u8; 4];
+    aad_len.copy_from_slice(aad_len_bytes);
+    let aad_len = u32::from_le_bytes(aad_len) as usize;
+    if rest.len() < aad_len + NONCE_SIZE {
+        return None;
+    }
+    let (aad, sealed) = rest.split_at(aad_len);
+    let input = open(session_key, sealed, aad)?;
+    let mut nonce = Nonce::default();
+    nonce.copy_from_slice(&sealed[..NONCE_SIZE]);
+    Some((input, aad.to_vec(), nonce))
+}
+
+/// Returns the nonce used to seal the output of a transaction whose input was sealed
+/// using `input_nonce`.
+pub(crate) fn output_nonce(input_nonce: &Nonce) -> Nonce {
+    truncate(hash(&[b"output", input_nonce]))
+}
+
+/// Seals an account storage value. The storage key is used as the AAD so that
+/// values cannot be swapped between keys.
+pub(crate) fn seal_value(account_key: &Key, key: &[u8], value: &[u8]) -> Vec<u8> {
+    let nonce = truncate(hash(&[b"storage", account_key, key, value]));
+    seal(account_key, &nonce, value, key)
+}
+
+pub(crate) fn open_value(account_key: &Key, key: &[u8], sealed_value: &[u8]) -> Option<Vec<u8>> {
+    open(account_key, sealed_value, key)
+}
+
+fn keystream<'a>(key: &'a Key, nonce: &'a Nonce) -> impl Iterator<Item = u8> + 'a {
+    (0u64..).flat_map(move |counter| {
+        hash(&[b"stream", key, nonce, &counter.to_le_bytes()])
+            .to_vec()
+            .into_iter()
+    })
+}
+
+fn tag(key: &Key, nonce: &Nonce, aad: &[u8], ciphertext: &[u8]) -> [u8; TAG_SIZE] {
+    truncate(hash(&[b"tag", key, nonce, aad, ciphertext]))
+}
+
+/// Hashes the length-prefixed concatenation of `parts`.
+fn hash(parts: &[&[u8]]) -> [u8; 32] {
+    let mut buf = Vec::with_capacity(parts.iter().map(|p| 8 + p.len()).sum());
+    for part in parts {
+        buf.extend_from_slice(&(part.len() as u64).to_le_bytes());
+        buf.extend_from_slice(part);
+    }
+    keccak256(&buf)
+}
+
+fn truncate(hash: [u8; 32]) -> [u8; 16] {
+    let mut truncated = [0u8; 16];
+    truncated.copy_from_slice(&hash[..16]);
+    truncated
+}
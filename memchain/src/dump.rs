@@ -0,0 +1,135 @@
+//! A portable snapshot of the accounts in a chain, which can be used to share
+//! the services deployed to a devnet with the tools that build against them.
+//!
+//! A dump is JSON of the form
+//! `{ "accounts": [{ "address": "<hex>", "balance": 0, "code": "<hex>", ... }] }`.
+//! Account entrypoints are native function pointers and are not dumped, so a chain
+//! restored from a dump holds the code and state of its accounts but cannot execute them.
+
+use std::{borrow::Cow, collections::BTreeMap, time::Duration};
+
+use blockchain_traits::Address as _;
+use oasis_types::Address;
+
+use crate::{Account, State};
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct StateDump {
+    pub accounts: Vec<AccountDump>,
+}
+
+/// An account, whose byte strings are hex-encoded.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct AccountDump {
+    pub address: String,
+    pub balance: u128,
+    #[serde(default)]
+    pub code: String,
+    /// The raw account storage. Values are sealed if the account is confidential.
+    #[serde(default)]
+    pub storage: BTreeMap<String, String>,
+    /// The expiry of the account, in seconds.
+    #[serde(default)]
+    pub expiry: Option<u64>,
+    #[serde(default)]
+    pub confidential_key: Option<String>,
+    #[serde(default)]
+    pub admin: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum DumpError {
+    /// The dump is not valid JSON of the expected form.
+    Malformed(String),
+
+    /// A field of the account having the address is not valid hex of the expected length.
+    InvalidAccount(String /* address */, &'static str /* field */),
+}
+
+impl std::fmt::Display for DumpError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            DumpError::Malformed(err) => write!(f, "Malformed state dump: {}", err),
+            DumpError::InvalidAccount(addr, field) => {
+                write!(f, "Account `{}` has invalid `{}`", addr, field)
+            }
+        }
+    }
+}
+
+impl StateDump {
+    pub(crate) fn new(state: &State) -> Self {
+        let mut accounts: Vec<_> = state
+            .iter()
+            .map(|(addr, acct)| AccountDump {
+                address: addr.path_repr(),
+                balance: acct.balance,
+                code: hex::encode(&acct.code),
+                storage: acct
+                    .storage
+                    .iter()
+                    .map(|(k, v)| (hex::encode(k), hex::encode(v)))
+                    .collect(),
+                expiry: acct.expiry.map(|expiry| expiry.as_secs()),
+                confidential_key: acct.confidential_key.map(hex::encode),
+                admin: acct.admin.map(|admin| admin.path_repr()),
+            })
+            .collect();
+        accounts.sort_by(|a, b| a.address.cmp(&b.address));
+        Self { accounts }
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, DumpError> {
+        serde_json::from_str(json).map_err(|err| DumpError::Malformed(err.to_string()))
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    pub(crate) fn to_state<'bc>(&self) -> Result<State<'bc>, DumpError> {
+        self.accounts
+            .iter()
+            .map(|acct| {
+                let invalid = |field| DumpError::InvalidAccount(acct.address.clone(), field);
+                let address = parse_address(&acct.address).ok_or_else(|| invalid("address"))?;
+                let admin = match &acct.admin {
+                    Some(admin) => Some(parse_address(admin).ok_or_else(|| invalid("admin"))?),
+                    None => None,
+                };
+                let confidential_key = match &acct.confidential_key {
+                    Some(key) => {
+                        let key = hex::decode(key).map_err(|_| invalid("confidential_key"))?;
+                        if key.len() != crate::confidential::KEY_SIZE {
+                            return Err(invalid("confidential_key"));
+                        }
+                        let mut account_key = crate::confidential::Key::default();
+                        account_key.copy_from_slice(&key);
+                        Some(account_key)
+                    }
+                    None => None,
+                };
+                let storage = acct
+                    .storage
+                    .iter()
+                    .map(|(k, v)| Ok((hex::decode(k)?, hex::decode(v)?)))
+                    .collect::<Result<_, hex::FromHexError>>()
+                    .map_err(|_| invalid("storage"))?;
+                let account = Account {
+                    balance: acct.balance,
+                    code: hex::decode(&acct.code).map_err(|_| invalid("code"))?,
+                    storage,
+                    expiry: acct.expiry.map(Duration::from_secs),
+                    confidential_key,
+                    admin,
+                    main: None,
+                };
+                Ok((address, Cow::Owned(account)))
+            })
+            .collect()
+    }
+}
+
+fn parse_address(hex_addr: &str) -> Option<Address> {
+    hex_addr.trim_start_matches("0x").parse().ok()
+}
@@ -1,19 +1,34 @@
 //! An in-memory blockchain with Ethereum-like semantics.
 #![feature(box_syntax)]
 
+#[macro_use]
+extern crate serde;
+
 mod block;
+pub mod confidential;
+mod dump;
+mod mempool;
 mod output;
 mod pending_transaction;
 
-use std::{borrow::Cow, collections::HashMap, convert::TryInto};
+use std::{borrow::Cow, collections::HashMap, convert::TryInto, rc::Rc, time::Duration};
 
 use blockchain_traits::Blockchain;
 use oasis_types::{AccountMeta, Address};
 
 use block::Block;
 
+pub use block::Upgrade;
+
+pub use dump::{AccountDump, DumpError, StateDump};
+
+pub use mempool::{Mempool, MempoolError, Transaction};
+
 type State<'bc> = HashMap<Address, Cow<'bc, Account>>;
 
+/// The entrypoints of the code registered with `Memchain::register_code`.
+type Codes = Rc<HashMap<Vec<u8>, Entrypoints>>;
+
 pub type PtxPtr = *const *mut dyn blockchain_traits::PendingTransaction<
     Address = Address,
     AccountMeta = AccountMeta,
@@ -25,6 +40,11 @@ pub struct Memchain<'bc> {
     name: String,
     blocks: Vec<Block<'bc>>,
     base_gas: u64,
+    block_gas_limit: u64,
+    block_interval: Option<Duration>,
+    time_since_last_block: Duration,
+    mempool: Mempool,
+    codes: Codes,
 }
 
 impl<'bc> Memchain<'bc> {
@@ -33,11 +53,47 @@ impl<'bc> Memchain<'bc> {
             name: name.as_ref().to_string(),
             blocks: Vec::new(),
             base_gas,
+            block_gas_limit: u64::max_value(),
+            block_interval: None,
+            time_since_last_block: Duration::default(),
+            mempool: Mempool::default(),
+            codes: Codes::default(),
         };
         bc.create_block_with_state(genesis_state);
         bc
     }
 
+    /// Creates a chain whose genesis state is the state that was dumped. The accounts
+    /// in the dump have no entrypoints, so transactions to them do nothing.
+    pub fn from_dump<S: AsRef<str>>(
+        name: S,
+        dump: &StateDump,
+        base_gas: u64,
+    ) -> Result<Self, DumpError> {
+        Ok(Self::new(name, dump.to_state()?, base_gas))
+    }
+
+    /// Returns a snapshot of the accounts in the current block.
+    pub fn dump_state(&self) -> StateDump {
+        StateDump::new(&self.blocks.last().unwrap().state)
+    }
+
+    /// Sets the maximum total gas of the transactions in a block.
+    /// Blocks are unlimited by default.
+    pub fn with_block_gas_limit(mut self, gas_limit: u64) -> Self {
+        self.block_gas_limit = gas_limit;
+        self.blocks.last_mut().unwrap().gas_limit = gas_limit;
+        self
+    }
+
+    /// Produces a block every `interval` of time passed to `advance_time`.
+    /// By default, blocks are only produced on demand.
+    pub fn with_block_interval(mut self, interval: Duration) -> Self {
+        assert!(interval > Duration::default(), "Block interval must be nonzero");
+        self.block_interval = Some(interval);
+        self
+    }
+
     pub fn create_block(&mut self) -> &mut Block<'bc> {
         self.create_block_with_state(self.blocks.last().unwrap().state.clone())
     }
@@ -47,9 +103,88 @@ impl<'bc> Memchain<'bc> {
             self.blocks.len().try_into().unwrap(),
             state,
             self.base_gas,
+            self.block_gas_limit,
+            self.codes.clone(),
         ));
         self.blocks.last_mut().unwrap()
     }
+
+    /// Adds a transaction to the mempool. It will be executed by a subsequent call
+    /// to `produce_block`.
+    pub fn submit(&mut self, tx: Transaction) -> Result<(), MempoolError> {
+        if tx.gas > self.block_gas_limit {
+            return Err(MempoolError::ExceedsBlockGasLimit);
+        }
+        self.mempool.insert(tx)
+    }
+
+    /// Returns the transactions that have been submitted but not yet included in a block.
+    pub fn mempool(&self) -> &Mempool {
+        &self.mempool
+    }
+
+    /// Upgrades the code of the account at `addr` in the current block.
+    /// See `Block::upgrade`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn upgrade(
+        &mut self,
+        caller: Address,
+        addr: Address,
+        payer: Address,
+        upgrade: Upgrade,
+        gas: u64,
+        gas_price: u64,
+    ) -> Box<dyn blockchain_traits::Receipt<Address = Address>> {
+        self.blocks
+            .last_mut()
+            .unwrap()
+            .upgrade(caller, addr, payer, upgrade, gas, gas_price)
+    }
+
+    /// Sets the entrypoints of the account that a service upgrades to `code`.
+    /// Accounts upgraded to code that was not registered have no entrypoints.
+    pub fn register_code(&mut self, code: Vec<u8>, entrypoints: Entrypoints) {
+        Rc::make_mut(&mut self.codes).insert(code, entrypoints);
+        self.blocks.last_mut().unwrap().codes = self.codes.clone();
+    }
+
+    /// Creates a new block and executes as many pending transactions as fit
+    /// within the block gas limit, in order of gas price and nonce.
+    pub fn produce_block(&mut self) -> &mut Block<'bc> {
+        let txs = self.mempool.take(self.block_gas_limit);
+        let block = self.create_block();
+        for tx in txs {
+            blockchain_traits::Block::transact(
+                block,
+                tx.caller,
+                tx.callee,
+                tx.payer,
+                tx.value,
+                &tx.input,
+                tx.gas,
+                tx.gas_price,
+            );
+        }
+        block
+    }
+
+    /// Advances the simulated clock by `elapsed` and produces a block for every
+    /// block interval that has passed. Returns the number of blocks produced.
+    /// Has no effect if no block interval was set.
+    pub fn advance_time(&mut self, elapsed: Duration) -> usize {
+        let interval = match self.block_interval {
+            Some(interval) => interval,
+            None => return 0,
+        };
+        self.time_since_last_block += elapsed;
+        let mut num_produced = 0;
+        while self.time_since_last_block >= interval {
+            self.time_since_last_block -= interval;
+            self.produce_block();
+            num_produced += 1;
+        }
+        num_produced
+    }
 }
 
 impl<'bc> Blockchain for Memchain<'bc> {
@@ -91,11 +226,21 @@ impl<'bc> Blockchain for Memchain<'bc> {
 
 #[derive(Clone, Default, Debug)]
 pub struct Account {
-    pub balance: u64,
+    pub balance: u128,
     pub code: Vec<u8>,
+
+    /// The raw account storage. Values are sealed if the account is confidential.
     pub storage: HashMap<Vec<u8>, Vec<u8>>,
     pub expiry: Option<std::time::Duration>,
 
+    /// The key used to seal storage values. Setting a key makes this account confidential:
+    /// transactions to the account must have their input sealed using
+    /// `confidential::seal_input`, and their output will be sealed for the caller.
+    pub confidential_key: Option<confidential::Key>,
+
+    /// An account that, in addition to this account, may upgrade this account's code.
+    pub admin: Option<Address>,
+
     /// Callable account entrypoint. `main` takes an pointer to a
     /// `Blockchain` trait object which can be used via FFI bindings
     /// to interact with the memchain. Returns nonzero to revert transaction.
@@ -103,19 +248,40 @@ pub struct Account {
     pub main: Option<AccountMain>,
 }
 
+/// The entrypoints of an account, which memchain calls in place of executing its code.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Entrypoints {
+    pub main: Option<AccountMain>,
+
+    /// Called once, with the migration input, after an account is upgraded to the code.
+    pub migrate: Option<AccountMain>,
+
+    /// Called once, with the constructor input, after an account is created with the code.
+    pub deploy: Option<AccountMain>,
+}
+
 impl blockchain_traits::KVStore for Account {
     fn contains(&self, key: &[u8]) -> bool {
         self.storage.contains_key(key)
     }
 
     fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
-        self.storage.get(key).map(Vec::to_owned)
+        let value = self.storage.get(key)?;
+        Some(match &self.confidential_key {
+            Some(account_key) => confidential::open_value(account_key, key, value)
+                .expect("Confidential account storage was modified"),
+            None => value.to_owned(),
+        })
     }
 }
 
 impl blockchain_traits::KVStoreMut for Account {
     fn set(&mut self, key: &[u8], value: &[u8]) {
-        self.storage.insert(key.to_vec(), value.to_vec());
+        let value = match &self.confidential_key {
+            Some(account_key) => confidential::seal_value(account_key, key, value),
+            None => value.to_vec(),
+        };
+        self.storage.insert(key.to_vec(), value);
     }
 
     fn remove(&mut self, key: &[u8]) {
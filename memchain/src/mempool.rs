@@ -0,0 +1,107 @@
+use std::collections::{BTreeMap, HashMap};
+
+use oasis_types::Address;
+
+/// A transaction waiting to be included in a block.
+#[derive(Clone, Debug)]
+pub struct Transaction {
+    pub caller: Address,
+    pub callee: Address,
+    pub payer: Address,
+    pub value: u128,
+    pub input: Vec<u8>,
+    pub gas: u64,
+    pub gas_price: u64,
+
+    /// Orders transactions sent by the same `caller`.
+    /// A transaction is never included before one with a lower nonce from the same caller.
+    pub nonce: u64,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum MempoolError {
+    /// The transaction requests more gas than can fit into a single block.
+    ExceedsBlockGasLimit,
+
+    /// A transaction with the same caller and nonce is already pending
+    /// at an equal or greater gas price.
+    Underpriced,
+}
+
+/// Holds submitted transactions until they are included in a block.
+#[derive(Debug, Default)]
+pub struct Mempool {
+    pending: HashMap<Address, BTreeMap<u64, Transaction>>, // caller -> nonce -> tx
+}
+
+impl Mempool {
+    /// Adds a transaction to the pool. A pending transaction with the same caller
+    /// and nonce is replaced only if the new transaction has a higher gas price.
+    pub fn insert(&mut self, tx: Transaction) -> Result<(), MempoolError> {
+        let caller_txs = self.pending.entry(tx.caller).or_default();
+        match caller_txs.get(&tx.nonce) {
+            Some(pending_tx) if pending_tx.gas_price >= tx.gas_price => {
+                Err(MempoolError::Underpriced)
+            }
+            _ => {
+                caller_txs.insert(tx.nonce, tx);
+                Ok(())
+            }
+        }
+    }
+
+    /// Returns the number of pending transactions.
+    pub fn len(&self) -> usize {
+        self.pending.values().map(BTreeMap::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes and returns the transactions that should be included in the next block.
+    /// Transactions are ordered by descending gas price, subject to each caller's
+    /// transactions being included in nonce order. The total gas of the returned
+    /// transactions does not exceed `gas_limit`.
+    pub fn take(&mut self, gas_limit: u64) -> Vec<Transaction> {
+        let mut txs = Vec::new();
+        let mut gas_left = gas_limit;
+        let mut blocked_callers = Vec::new();
+        loop {
+            // The next candidate is the highest priced of each caller's lowest-nonce transaction.
+            // Ties are broken by caller address so that block production is deterministic.
+            let next_caller = self
+                .pending
+                .iter()
+                .filter(|(caller, _)| !blocked_callers.contains(*caller))
+                .filter_map(|(caller, caller_txs)| {
+                    caller_txs.values().next().map(|tx| (tx.gas_price, *caller))
+                })
+                .max_by(|(price_a, caller_a), (price_b, caller_b)| {
+                    price_a.cmp(price_b).then_with(|| caller_b.cmp(caller_a))
+                })
+                .map(|(_, caller)| caller);
+
+            let caller = match next_caller {
+                Some(caller) => caller,
+                None => break,
+            };
+
+            let caller_txs = self.pending.get_mut(&caller).unwrap();
+            let nonce = *caller_txs.keys().next().unwrap();
+            if caller_txs[&nonce].gas > gas_left {
+                // Later transactions from this caller can't skip ahead of this one.
+                blocked_callers.push(caller);
+                continue;
+            }
+
+            let tx = caller_txs.remove(&nonce).unwrap();
+            if caller_txs.is_empty() {
+                self.pending.remove(&caller);
+            }
+            gas_left -= tx.gas;
+            txs.push(tx);
+        }
+        txs
+    }
+}
@@ -2,17 +2,20 @@ use blockchain_traits::TransactionOutcome;
 use oasis_types::{AccountMeta, Address};
 
 use crate::{
+    confidential,
     output::{Event, Receipt},
-    State,
+    Account, AccountMain, Codes, State,
 };
 
 #[derive(Debug)]
 pub struct PendingTransaction<'bc> {
     pub caller: Address,
     pub callee: Address,
-    pub value: u64,
+    pub value: u128,
     pub state: State<'bc>,
+    pub codes: Codes,
     pub input: Vec<u8>,
+    pub aad: Vec<u8>,
     pub outcome: TransactionOutcome,
     pub output: Vec<u8>,
     pub events: Vec<Event>,
@@ -20,6 +23,55 @@ pub struct PendingTransaction<'bc> {
     pub base_gas: u64,
 }
 
+impl<'bc> PendingTransaction<'bc> {
+    /// Calls `main` with this transaction and aborts the transaction if `main` fails.
+    pub fn call_main(&mut self, main: AccountMain) {
+        let ptx: &mut dyn blockchain_traits::PendingTransaction<
+            Address = Address,
+            AccountMeta = AccountMeta,
+        > = self;
+        let errno = main(unsafe {
+            // Extend the lifetime, as required by the FFI type.
+            // This is only unsafe if the `main` fn stores the pointer,
+            // but this is disallowed by the precondition on `main`.
+            &(std::mem::transmute::<&mut _, &'static mut _>(ptx) as *mut _) as *const _
+        });
+        if errno != 0 {
+            self.outcome = TransactionOutcome::Aborted;
+        }
+    }
+
+    /// Replaces the code and `main` of the callee, then calls `migrate`, if any,
+    /// with this transaction.
+    pub fn replace_code(
+        &mut self,
+        code: Vec<u8>,
+        main: Option<AccountMain>,
+        migrate: Option<AccountMain>,
+    ) {
+        let acct = self.state.get_mut(&self.callee).unwrap().to_mut();
+        acct.code = code;
+        acct.main = main;
+        if let Some(migrate) = migrate {
+            self.call_main(migrate);
+        }
+    }
+
+    /// Returns an unused address for an account created by the callee.
+    fn new_address(&self) -> Address {
+        let mut nonce = self.state.len() as u64;
+        loop {
+            let hash = tiny_keccak::keccak256(&[self.callee.as_ref(), &nonce.to_le_bytes()].concat());
+            let mut addr = Address::default();
+            addr.0.copy_from_slice(&hash[12..]);
+            if !self.state.contains_key(&addr) {
+                return addr;
+            }
+            nonce += 1;
+        }
+    }
+}
+
 impl<'bc> blockchain_traits::PendingTransaction for PendingTransaction<'bc> {
     type Address = Address;
     type AccountMeta = AccountMeta;
@@ -32,7 +84,7 @@ impl<'bc> blockchain_traits::PendingTransaction for PendingTransaction<'bc> {
         &self.caller
     }
 
-    fn value(&self) -> u64 {
+    fn value(&self) -> u128 {
         self.value
     }
 
@@ -40,10 +92,14 @@ impl<'bc> blockchain_traits::PendingTransaction for PendingTransaction<'bc> {
         self.input.as_slice()
     }
 
+    fn aad(&self) -> &[u8] {
+        self.aad.as_slice()
+    }
+
     fn transact(
         &mut self,
         callee: Self::Address,
-        value: u64,
+        value: u128,
         input: &[u8],
     ) -> Box<dyn blockchain_traits::Receipt<Address = Self::Address>> {
         let caller = self.callee;
@@ -67,6 +123,21 @@ impl<'bc> blockchain_traits::PendingTransaction for PendingTransaction<'bc> {
             return box receipt;
         }
 
+        // The input to a confidential account is sealed to the calling account.
+        let (input, aad, session) = match self.state.get(&callee).unwrap().confidential_key {
+            Some(account_key) => {
+                let session_key = confidential::session_key(&account_key, &caller);
+                match confidential::open_input(&session_key, input) {
+                    Some((input, aad, nonce)) => (input, aad, Some((session_key, nonce))),
+                    None => {
+                        receipt.outcome = TransactionOutcome::InvalidInput;
+                        return box receipt;
+                    }
+                }
+            }
+            None => (input.to_vec(), Vec::new(), None),
+        };
+
         let mut ptx_state = self.state.clone();
 
         let caller_acct = ptx_state.get_mut(&caller).unwrap().to_mut();
@@ -84,9 +155,11 @@ impl<'bc> blockchain_traits::PendingTransaction for PendingTransaction<'bc> {
             caller: self.callee,
             callee,
             value,
-            input: input.to_vec(),
+            input,
+            aad,
             outcome: TransactionOutcome::Success,
             state: ptx_state,
+            codes: self.codes.clone(),
             events: Vec::new(),
             output: Vec::new(),
             base_gas: self.base_gas,
@@ -96,23 +169,19 @@ impl<'bc> blockchain_traits::PendingTransaction for PendingTransaction<'bc> {
         let main_fn = self.state.get(&callee).unwrap().main;
 
         if let Some(main) = main_fn {
-            let ptx: &mut dyn blockchain_traits::PendingTransaction<
-                Address = Address,
-                AccountMeta = AccountMeta,
-            > = &mut pending_transaction;
-            let errno = main(unsafe {
-                // Extend the lifetime, as required by the FFI type.
-                // This is only unsafe if the `main` fn stores the pointer,
-                // but this is disallowed by the precondition on `main`.
-                &(std::mem::transmute::<&mut _, &'static mut _>(ptx) as *mut _) as *const _
-            });
-            if errno != 0 {
-                pending_transaction.outcome = TransactionOutcome::Aborted;
-            }
+            pending_transaction.call_main(main);
         }
 
         receipt.outcome = pending_transaction.outcome;
-        receipt.output = pending_transaction.output;
+        receipt.output = match session {
+            Some((session_key, nonce)) => confidential::seal(
+                &session_key,
+                &confidential::output_nonce(&nonce),
+                &pending_transaction.output,
+                &pending_transaction.aad,
+            ),
+            None => pending_transaction.output,
+        };
         if blockchain_traits::Receipt::reverted(&receipt) {
             receipt.events.clear();
         } else {
@@ -125,6 +194,144 @@ impl<'bc> blockchain_traits::PendingTransaction for PendingTransaction<'bc> {
         box receipt
     }
 
+    fn upgrade(
+        &mut self,
+        addr: Self::Address,
+        code: &[u8],
+        migration_input: &[u8],
+    ) -> Box<dyn blockchain_traits::Receipt<Address = Self::Address>> {
+        let caller = self.callee;
+        let mut receipt = Receipt {
+            caller,
+            callee: addr,
+            value: 0,
+            gas_used: 0, // TODO(#116)
+            output: Vec::new(),
+            events: Vec::new(),
+            outcome: TransactionOutcome::Success,
+        };
+
+        let admin = match self.state.get(&addr) {
+            Some(acct) => acct.admin,
+            None => {
+                receipt.outcome = TransactionOutcome::NoAccount;
+                return box receipt;
+            }
+        };
+
+        if caller != addr && admin != Some(caller) {
+            receipt.outcome = TransactionOutcome::PermissionDenied;
+            return box receipt;
+        }
+
+        if self.gas_left < self.base_gas {
+            receipt.outcome = TransactionOutcome::InsufficientGas;
+            return box receipt;
+        }
+
+        let entrypoints = self.codes.get(code).copied().unwrap_or_default();
+
+        let mut pending_transaction = PendingTransaction {
+            caller,
+            callee: addr,
+            value: 0,
+            input: migration_input.to_vec(),
+            aad: Vec::new(),
+            outcome: TransactionOutcome::Success,
+            state: self.state.clone(),
+            codes: self.codes.clone(),
+            events: Vec::new(),
+            output: Vec::new(),
+            base_gas: self.base_gas,
+            gas_left: self.gas_left - self.base_gas,
+        };
+        pending_transaction.replace_code(code.to_vec(), entrypoints.main, entrypoints.migrate);
+
+        receipt.outcome = pending_transaction.outcome;
+        receipt.output = pending_transaction.output;
+        if !blockchain_traits::Receipt::reverted(&receipt) {
+            self.state = pending_transaction.state;
+            receipt
+                .events
+                .append(&mut pending_transaction.events.clone());
+            self.events.append(&mut pending_transaction.events);
+        }
+        box receipt
+    }
+
+    fn create(
+        &mut self,
+        value: u128,
+        code: &[u8],
+        ctor_input: &[u8],
+    ) -> Box<dyn blockchain_traits::Receipt<Address = Self::Address>> {
+        let caller = self.callee;
+        let addr = self.new_address();
+        let mut receipt = Receipt {
+            caller,
+            callee: addr,
+            value,
+            gas_used: 0, // TODO(#116)
+            output: Vec::new(),
+            events: Vec::new(),
+            outcome: TransactionOutcome::Success,
+        };
+
+        if self.gas_left < self.base_gas {
+            receipt.outcome = TransactionOutcome::InsufficientGas;
+            return box receipt;
+        }
+
+        let mut ptx_state = self.state.clone();
+
+        let caller_acct = ptx_state.get_mut(&caller).unwrap().to_mut();
+        if caller_acct.balance < value {
+            receipt.outcome = TransactionOutcome::InsufficientFunds;
+            return box receipt;
+        }
+        caller_acct.balance -= value;
+
+        let entrypoints = self.codes.get(code).copied().unwrap_or_default();
+        ptx_state.insert(
+            addr,
+            std::borrow::Cow::Owned(Account {
+                balance: value,
+                code: code.to_vec(),
+                main: entrypoints.main,
+                ..Default::default()
+            }),
+        );
+
+        let mut pending_transaction = PendingTransaction {
+            caller,
+            callee: addr,
+            value,
+            input: ctor_input.to_vec(),
+            aad: Vec::new(),
+            outcome: TransactionOutcome::Success,
+            state: ptx_state,
+            codes: self.codes.clone(),
+            events: Vec::new(),
+            output: Vec::new(),
+            base_gas: self.base_gas,
+            gas_left: self.gas_left - self.base_gas,
+        };
+        if let Some(deploy) = entrypoints.deploy {
+            pending_transaction.call_main(deploy);
+        }
+
+        receipt.outcome = pending_transaction.outcome;
+        receipt.output = pending_transaction.output;
+        if !blockchain_traits::Receipt::reverted(&receipt) {
+            self.state = pending_transaction.state;
+            receipt
+                .events
+                .append(&mut pending_transaction.events.clone());
+            self.events.append(&mut pending_transaction.events);
+        }
+        box receipt
+    }
+
     fn ret(&mut self, data: &[u8]) {
         assert!(self.output.is_empty());
         self.output = data.to_vec()
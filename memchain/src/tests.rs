@@ -6,10 +6,12 @@ use crate::*;
 
 const ADDR_1: Address = Address([1u8; 20]);
 const ADDR_2: Address = Address([2u8; 20]);
+const ADDR_3: Address = Address([3u8; 20]);
+const ADDR_4: Address = Address([4u8; 20]);
 
 const BASE_GAS: u64 = 2100;
 
-fn giga(num: u64) -> u64 {
+fn giga(num: u128) -> u128 {
     num * 1_000_000_000
 }
 
@@ -79,7 +81,7 @@ fn create_bc<'bc>(
             (
                 Address([i as u8; 20]),
                 Cow::Owned(Account {
-                    balance: giga(i as u64),
+                    balance: giga(i as u128),
                     code: format!("\0asm not wasm {}", i).into_bytes(),
                     storage: {
                         let mut storage = HashMap::new();
@@ -94,6 +96,8 @@ fn create_bc<'bc>(
                         storage
                     },
                     expiry: None,
+                    confidential_key: None,
+                    admin: None,
                     main,
                 }),
             )
@@ -119,7 +123,29 @@ fn transfer() {
         .transact(ADDR_1, ADDR_2, ADDR_1, value, &Vec::new(), BASE_GAS, 1);
     assert_eq!(
         bc.last_block().account_meta_at(&ADDR_1).unwrap().balance,
-        giga(1) - BASE_GAS - value,
+        giga(1) - u128::from(BASE_GAS) - value,
+    );
+    assert_eq!(
+        bc.last_block().account_meta_at(&ADDR_2).unwrap().balance,
+        giga(2) + value,
+    );
+}
+
+#[test]
+fn transfer_exceeding_u64() {
+    let mut bc = create_bc(vec![None, Some(nop_main)]);
+    let value = u128::from(u64::max_value()) + 1;
+    bc.create_block()
+        .state
+        .get_mut(&ADDR_1)
+        .unwrap()
+        .to_mut()
+        .balance += value;
+    bc.last_block_mut()
+        .transact(ADDR_1, ADDR_2, ADDR_1, value, &Vec::new(), BASE_GAS, 1);
+    assert_eq!(
+        bc.last_block().account_meta_at(&ADDR_1).unwrap().balance,
+        giga(1) - u128::from(BASE_GAS),
     );
     assert_eq!(
         bc.last_block().account_meta_at(&ADDR_2).unwrap().balance,
@@ -190,7 +216,7 @@ fn revert_tx() {
         .transact(ADDR_1, ADDR_2, ADDR_2, 10_000, &Vec::new(), BASE_GAS, 1);
     assert_eq!(
         bc.last_block().account_meta_at(&ADDR_2).unwrap().balance,
-        giga(2) - BASE_GAS,
+        giga(2) - u128::from(BASE_GAS),
     );
     assert_eq!(
         bc.last_block().account_meta_at(&ADDR_1).unwrap().balance,
@@ -247,3 +273,618 @@ fn subtx_revert() {
         Some(b"common_value".to_vec())
     );
 }
+
+fn mempool_tx(caller: Address, nonce: u64, gas: u64, gas_price: u64) -> Transaction {
+    Transaction {
+        caller,
+        callee: ADDR_2,
+        payer: caller,
+        value: 0,
+        input: Vec::new(),
+        gas,
+        gas_price,
+        nonce,
+    }
+}
+
+#[test]
+fn mempool_gas_price_order() {
+    let mut bc = create_bc(vec![None, Some(nop_main), None]);
+    bc.submit(mempool_tx(ADDR_1, 0, BASE_GAS, 1)).unwrap();
+    bc.submit(mempool_tx(ADDR_3, 0, BASE_GAS, 5)).unwrap();
+    assert_eq!(bc.mempool().len(), 2);
+
+    bc.produce_block();
+
+    assert!(bc.mempool().is_empty());
+    let callers: Vec<Address> = bc
+        .last_block()
+        .receipts()
+        .iter()
+        .map(|r| *r.caller())
+        .collect();
+    assert_eq!(callers, vec![ADDR_3, ADDR_1]);
+    assert_eq!(
+        bc.last_block().account_meta_at(&ADDR_3).unwrap().balance,
+        giga(3) - u128::from(BASE_GAS) * 5,
+    );
+}
+
+#[test]
+fn mempool_nonce_order() {
+    let mut bc = create_bc(vec![None, Some(nop_main), None]);
+    bc.submit(mempool_tx(ADDR_1, 1, BASE_GAS, 10)).unwrap();
+    bc.submit(mempool_tx(ADDR_1, 0, BASE_GAS, 1)).unwrap();
+    bc.submit(mempool_tx(ADDR_3, 0, BASE_GAS, 5)).unwrap();
+
+    bc.produce_block();
+
+    let receipts = bc.last_block().receipts();
+    let callers: Vec<Address> = receipts.iter().map(|r| *r.caller()).collect();
+    assert_eq!(callers, vec![ADDR_3, ADDR_1, ADDR_1]);
+    assert_eq!(
+        bc.last_block().account_meta_at(&ADDR_1).unwrap().balance,
+        giga(1) - u128::from(BASE_GAS) * 11,
+    );
+}
+
+#[test]
+fn mempool_replacement() {
+    let mut bc = create_bc(vec![None, Some(nop_main)]);
+    bc.submit(mempool_tx(ADDR_1, 0, BASE_GAS, 2)).unwrap();
+    assert_eq!(
+        bc.submit(mempool_tx(ADDR_1, 0, BASE_GAS, 2)),
+        Err(MempoolError::Underpriced)
+    );
+    bc.submit(mempool_tx(ADDR_1, 0, BASE_GAS, 3)).unwrap();
+    assert_eq!(bc.mempool().len(), 1);
+
+    bc.produce_block();
+    assert_eq!(
+        bc.last_block().account_meta_at(&ADDR_1).unwrap().balance,
+        giga(1) - u128::from(BASE_GAS) * 3,
+    );
+}
+
+#[test]
+fn block_gas_limit() {
+    let mut bc = create_bc(vec![None, Some(nop_main), None]).with_block_gas_limit(BASE_GAS * 2);
+    assert_eq!(
+        bc.submit(mempool_tx(ADDR_1, 0, BASE_GAS * 3, 1)),
+        Err(MempoolError::ExceedsBlockGasLimit)
+    );
+
+    bc.submit(mempool_tx(ADDR_1, 0, BASE_GAS, 1)).unwrap();
+    bc.submit(mempool_tx(ADDR_1, 1, BASE_GAS, 1)).unwrap();
+    bc.submit(mempool_tx(ADDR_3, 0, BASE_GAS * 2, 2)).unwrap();
+
+    bc.produce_block();
+    assert_eq!(bc.last_block().receipts().len(), 1);
+    assert_eq!(bc.mempool().len(), 2);
+
+    bc.produce_block();
+    assert_eq!(bc.last_block().receipts().len(), 2);
+    assert!(bc.mempool().is_empty());
+
+    let receipt = bc
+        .last_block_mut()
+        .transact(ADDR_1, ADDR_2, ADDR_1, 0, &[], BASE_GAS, 1);
+    assert_eq!(
+        receipt.outcome(),
+        blockchain_traits::TransactionOutcome::InsufficientGas
+    );
+}
+
+#[test]
+fn timed_block_production() {
+    let mut bc = create_bc(vec![None, Some(nop_main)])
+        .with_block_interval(std::time::Duration::from_secs(5));
+    bc.submit(mempool_tx(ADDR_1, 0, BASE_GAS, 1)).unwrap();
+
+    assert_eq!(bc.advance_time(std::time::Duration::from_secs(3)), 0);
+    assert_eq!(bc.last_block().height(), 0);
+
+    assert_eq!(bc.advance_time(std::time::Duration::from_secs(8)), 2);
+    assert_eq!(bc.last_block().height(), 2);
+    assert_eq!(bc.block(1).unwrap().receipts().len(), 1);
+    assert!(bc.last_block().receipts().is_empty());
+}
+
+extern "C" fn confidential_main(
+    ptx: *const *mut dyn PendingTransaction<Address = Address, AccountMeta = AccountMeta>,
+) -> u16 {
+    let ptx = unsafe { &mut **ptx };
+    let input = ptx.input().to_vec();
+    ptx.state_mut().set(b"secret", &input);
+    let mut rv = ptx.aad().to_vec();
+    rv.append(&mut ptx.state().get(b"secret").unwrap());
+    ptx.ret(&rv);
+    0
+}
+
+#[test]
+fn confidential_tx() {
+    let account_key = [7u8; confidential::KEY_SIZE];
+    let mut genesis_state = HashMap::new();
+    genesis_state.insert(
+        ADDR_1,
+        Cow::Owned(Account {
+            balance: giga(1),
+            ..Default::default()
+        }),
+    );
+    genesis_state.insert(
+        ADDR_2,
+        Cow::Owned(Account {
+            confidential_key: Some(account_key),
+            main: Some(confidential_main),
+            ..Default::default()
+        }),
+    );
+    let mut bc = Memchain::new("memchain", genesis_state, BASE_GAS);
+
+    let session_key = confidential::session_key(&account_key, &ADDR_1);
+    let nonce = [1u8; confidential::NONCE_SIZE];
+    let input = confidential::seal_input(&session_key, &nonce, b"plaintext", b"aad");
+
+    let receipt = bc
+        .last_block_mut()
+        .transact(ADDR_1, ADDR_2, ADDR_1, 0, &input, BASE_GAS, 0);
+    assert!(!receipt.reverted());
+    assert_eq!(
+        confidential::open(&session_key, receipt.output(), b"aad").unwrap(),
+        b"aadplaintext"
+    );
+    assert!(confidential::open(&session_key, receipt.output(), b"bad").is_none());
+
+    assert_eq!(
+        bc.last_block().state_at(&ADDR_2).unwrap().get(b"secret"),
+        Some(b"plaintext".to_vec())
+    );
+    let sealed_value = &bc.blocks.last().unwrap().state[&ADDR_2].storage[b"secret".as_ref()];
+    assert!(!sealed_value.windows(9).any(|w| w == b"plaintext"));
+
+    let wrong_key = confidential::session_key(&account_key, &ADDR_2);
+    let bad_input = confidential::seal_input(&wrong_key, &nonce, b"plaintext", b"aad");
+    let receipt = bc
+        .last_block_mut()
+        .transact(ADDR_1, ADDR_2, ADDR_1, 0, &bad_input, BASE_GAS, 0);
+    assert_eq!(
+        receipt.outcome(),
+        blockchain_traits::TransactionOutcome::InvalidInput
+    );
+}
+
+const CALLEE_KEY: confidential::Key = [8u8; confidential::KEY_SIZE];
+
+extern "C" fn confidential_caller_main(
+    ptx: *const *mut dyn PendingTransaction<Address = Address, AccountMeta = AccountMeta>,
+) -> u16 {
+    let ptx = unsafe { &mut **ptx };
+
+    // A non-confidential callee receives the plaintext input and no AAD.
+    let receipt = ptx.transact(ADDR_4, 0, b"plaintext");
+    if receipt.reverted() || receipt.output() != b"plaintext" {
+        return 1;
+    }
+
+    // A confidential callee requires input sealed to the calling account.
+    let receipt = ptx.transact(ADDR_3, 0, b"plaintext");
+    if receipt.outcome() != blockchain_traits::TransactionOutcome::InvalidInput {
+        return 2;
+    }
+
+    let session_key = confidential::session_key(&CALLEE_KEY, ptx.address());
+    let nonce = [2u8; confidential::NONCE_SIZE];
+    let input = confidential::seal_input(&session_key, &nonce, b"secret", b"inner");
+    let receipt = ptx.transact(ADDR_3, 0, &input);
+    if receipt.reverted() {
+        return 3;
+    }
+    match confidential::open(&session_key, receipt.output(), b"inner") {
+        Some(output) => ptx.ret(&output),
+        None => return 4,
+    }
+    0
+}
+
+#[test]
+fn confidential_subtx() {
+    let caller_key = [7u8; confidential::KEY_SIZE];
+    let mut genesis_state = HashMap::new();
+    genesis_state.insert(
+        ADDR_1,
+        Cow::Owned(Account {
+            balance: giga(1),
+            ..Default::default()
+        }),
+    );
+    genesis_state.insert(
+        ADDR_2,
+        Cow::Owned(Account {
+            confidential_key: Some(caller_key),
+            main: Some(confidential_caller_main),
+            ..Default::default()
+        }),
+    );
+    genesis_state.insert(
+        ADDR_3,
+        Cow::Owned(Account {
+            confidential_key: Some(CALLEE_KEY),
+            main: Some(confidential_main),
+            ..Default::default()
+        }),
+    );
+    genesis_state.insert(
+        ADDR_4,
+        Cow::Owned(Account {
+            main: Some(confidential_main),
+            ..Default::default()
+        }),
+    );
+    let mut bc = Memchain::new("memchain", genesis_state, BASE_GAS);
+
+    let session_key = confidential::session_key(&caller_key, &ADDR_1);
+    let nonce = [1u8; confidential::NONCE_SIZE];
+    let input = confidential::seal_input(&session_key, &nonce, b"", b"outer");
+
+    let receipt = bc
+        .last_block_mut()
+        .transact(ADDR_1, ADDR_2, ADDR_1, 0, &input, BASE_GAS * 4, 0);
+    assert!(!receipt.reverted());
+    assert_eq!(
+        confidential::open(&session_key, receipt.output(), b"outer").unwrap(),
+        b"innersecret"
+    );
+    assert_eq!(
+        bc.last_block().state_at(&ADDR_3).unwrap().get(b"secret"),
+        Some(b"secret".to_vec())
+    );
+    assert_eq!(
+        bc.last_block().state_at(&ADDR_4).unwrap().get(b"secret"),
+        Some(b"plaintext".to_vec())
+    );
+}
+
+extern "C" fn migrate_main(
+    ptx: *const *mut dyn PendingTransaction<Address = Address, AccountMeta = AccountMeta>,
+) -> u16 {
+    let ptx = unsafe { &mut **ptx };
+    let input = ptx.input().to_vec();
+    ptx.state_mut().set(b"common_key", &input);
+    0
+}
+
+fn upgrade(migrate: Option<AccountMain>) -> Upgrade<'static> {
+    Upgrade {
+        code: b"\0asm upgraded".to_vec(),
+        main: Some(simple_main),
+        migrate,
+        migration_input: b"migrated",
+    }
+}
+
+#[test]
+fn upgrade_permissions() {
+    let mut bc = create_bc(vec![None, Some(nop_main), None]);
+    bc.blocks
+        .last_mut()
+        .unwrap()
+        .state
+        .get_mut(&ADDR_2)
+        .unwrap()
+        .to_mut()
+        .admin = Some(ADDR_3);
+
+    let receipt = bc.upgrade(ADDR_1, ADDR_2, ADDR_1, upgrade(None), BASE_GAS, 1);
+    assert_eq!(
+        receipt.outcome(),
+        blockchain_traits::TransactionOutcome::PermissionDenied
+    );
+    assert_eq!(
+        bc.last_block().code_at(&ADDR_2).unwrap(),
+        b"\0asm not wasm 2"
+    );
+
+    let receipt = bc.upgrade(ADDR_3, ADDR_2, ADDR_3, upgrade(None), BASE_GAS, 1);
+    assert!(!receipt.reverted());
+    assert_eq!(bc.last_block().code_at(&ADDR_2).unwrap(), b"\0asm upgraded");
+
+    // The payer is charged for the gas, like in a transaction.
+    assert_eq!(
+        bc.last_block().account_meta_at(&ADDR_3).unwrap().balance,
+        giga(3) - u128::from(BASE_GAS)
+    );
+    assert_eq!(bc.blocks.last().unwrap().gas_used, BASE_GAS);
+
+    // The service can upgrade itself.
+    let receipt = bc.upgrade(ADDR_2, ADDR_2, ADDR_2, upgrade(None), BASE_GAS, 1);
+    assert!(!receipt.reverted());
+}
+
+#[test]
+fn upgrade_migration() {
+    let mut bc = create_bc(vec![None, Some(nop_main)]);
+
+    let receipt = bc.upgrade(
+        ADDR_2,
+        ADDR_2,
+        ADDR_2,
+        upgrade(Some(fail_main)),
+        BASE_GAS,
+        0,
+    );
+    assert!(receipt.reverted());
+    assert_eq!(
+        bc.last_block().code_at(&ADDR_2).unwrap(),
+        b"\0asm not wasm 2"
+    );
+
+    let receipt = bc.upgrade(
+        ADDR_2,
+        ADDR_2,
+        ADDR_2,
+        upgrade(Some(migrate_main)),
+        BASE_GAS,
+        0,
+    );
+    assert!(!receipt.reverted());
+    assert_eq!(
+        bc.last_block()
+            .state_at(&ADDR_2)
+            .unwrap()
+            .get(b"common_key"),
+        Some(b"migrated".to_vec())
+    );
+
+    // The new `main` handles subsequent transactions.
+    bc.last_block_mut()
+        .transact(ADDR_2, ADDR_2, ADDR_2, 0, &[1], BASE_GAS, 0);
+    assert_eq!(bc.last_block().receipts().last().unwrap().output(), &[1, 4]);
+}
+
+extern "C" fn upgrade_main(
+    ptx: *const *mut dyn PendingTransaction<Address = Address, AccountMeta = AccountMeta>,
+) -> u16 {
+    let ptx = unsafe { &mut **ptx };
+    let mut addr = Address::default();
+    addr.0.copy_from_slice(ptx.input());
+    let receipt = ptx.upgrade(addr, b"\0asm upgraded", b"migrated");
+    if receipt.reverted() {
+        return 1;
+    }
+    0
+}
+
+#[test]
+fn upgrade_from_service() {
+    let mut bc = create_bc(vec![Some(upgrade_main), Some(upgrade_main), None]);
+    bc.register_code(
+        b"\0asm upgraded".to_vec(),
+        Entrypoints {
+            main: Some(simple_main),
+            migrate: Some(migrate_main),
+            deploy: None,
+        },
+    );
+    bc.blocks
+        .last_mut()
+        .unwrap()
+        .state
+        .get_mut(&ADDR_3)
+        .unwrap()
+        .to_mut()
+        .admin = Some(ADDR_1);
+
+    // A service may not upgrade a service that it does not administer.
+    let receipt = bc
+        .last_block_mut()
+        .transact(ADDR_1, ADDR_2, ADDR_1, 0, &ADDR_1.0, BASE_GAS, 0);
+    assert!(receipt.reverted());
+    assert_eq!(
+        bc.last_block().code_at(&ADDR_1).unwrap(),
+        b"\0asm not wasm 1"
+    );
+
+    // A service can upgrade itself, and the migration of the new code is run.
+    let receipt =
+        bc.last_block_mut()
+            .transact(ADDR_1, ADDR_2, ADDR_1, 0, &ADDR_2.0, BASE_GAS * 2, 0);
+    assert!(!receipt.reverted());
+    assert_eq!(bc.last_block().code_at(&ADDR_2).unwrap(), b"\0asm upgraded");
+    assert_eq!(
+        bc.last_block()
+            .state_at(&ADDR_2)
+            .unwrap()
+            .get(b"common_key"),
+        Some(b"migrated".to_vec())
+    );
+    let receipt = bc
+        .last_block_mut()
+        .transact(ADDR_2, ADDR_2, ADDR_2, 0, &[1], BASE_GAS, 0);
+    assert_eq!(receipt.output(), &[1, 4]);
+
+    // An admin can upgrade the services that it administers.
+    let receipt =
+        bc.last_block_mut()
+            .transact(ADDR_2, ADDR_1, ADDR_2, 0, &ADDR_3.0, BASE_GAS * 2, 0);
+    assert!(!receipt.reverted());
+    assert_eq!(bc.last_block().code_at(&ADDR_3).unwrap(), b"\0asm upgraded");
+
+    // The migration needs gas.
+    let receipt = bc
+        .last_block_mut()
+        .transact(ADDR_1, ADDR_1, ADDR_1, 0, &ADDR_1.0, BASE_GAS, 0);
+    assert_eq!(
+        receipt.outcome(),
+        blockchain_traits::TransactionOutcome::Aborted
+    );
+}
+
+#[test]
+fn state_dump() {
+    let mut bc = create_bc(vec![None, Some(confidential_main)]);
+    {
+        let state = &mut bc.blocks.last_mut().unwrap().state;
+        let acct_2 = state.get_mut(&ADDR_2).unwrap().to_mut();
+        acct_2.confidential_key = Some([7u8; confidential::KEY_SIZE]);
+        acct_2.admin = Some(ADDR_1);
+        acct_2.expiry = Some(std::time::Duration::from_secs(42));
+    }
+
+    let dump = bc.dump_state();
+    assert_eq!(dump.accounts.len(), 2);
+    assert_eq!(dump.accounts[0].address, hex::encode([1u8; 20]));
+    assert_eq!(dump.accounts[0].balance, giga(1));
+    assert_eq!(dump.accounts[0].code, hex::encode(b"\0asm not wasm 1"));
+    assert_eq!(StateDump::from_json(&dump.to_json()).unwrap(), dump);
+
+    let restored = Memchain::from_dump("restored", &dump, BASE_GAS).unwrap();
+    assert_eq!(restored.dump_state(), dump);
+    assert_eq!(
+        restored.last_block().code_at(&ADDR_2),
+        Some(b"\0asm not wasm 2".as_ref())
+    );
+    assert_eq!(
+        restored
+            .last_block()
+            .state_at(&ADDR_1)
+            .unwrap()
+            .get(b"key_1"),
+        Some(b"value_1".to_vec())
+    );
+    assert!(restored.blocks[0].state[&ADDR_2].main.is_none());
+
+    let mut bad_dump = dump;
+    bad_dump.accounts[1].admin = Some("0x1234".to_string());
+    assert_eq!(
+        Memchain::from_dump("bad", &bad_dump, BASE_GAS).unwrap_err(),
+        DumpError::InvalidAccount(hex::encode([2u8; 20]), "admin")
+    );
+    assert!(StateDump::from_json("{").is_err());
+}
+
+extern "C" fn deploy_main(
+    ptx: *const *mut dyn PendingTransaction<Address = Address, AccountMeta = AccountMeta>,
+) -> u16 {
+    let ptx = unsafe { &mut **ptx };
+    let input = ptx.input().to_vec();
+    if input.is_empty() {
+        ptx.err(&oasis_std::reexports::serde_cbor::to_vec(&"empty input").unwrap());
+        return 1;
+    }
+    ptx.state_mut().set(b"ctor_input", &input);
+    0
+}
+
+extern "C" fn create_main(
+    ptx: *const *mut dyn PendingTransaction<Address = Address, AccountMeta = AccountMeta>,
+) -> u16 {
+    let ptx = unsafe { &mut **ptx };
+    let input = ptx.input().to_vec();
+    let receipt = ptx.create(5, b"\0asm created", &input);
+    if receipt.reverted() {
+        ptx.err(receipt.output());
+        return 1;
+    }
+    ptx.ret(receipt.callee().as_ref());
+    0
+}
+
+#[test]
+fn create_from_service() {
+    let mut bc = create_bc(vec![None, Some(create_main)]);
+    bc.register_code(
+        b"\0asm created".to_vec(),
+        Entrypoints {
+            main: Some(nop_main),
+            deploy: Some(deploy_main),
+            ..Default::default()
+        },
+    );
+
+    let receipt =
+        bc.last_block_mut()
+            .transact(ADDR_1, ADDR_2, ADDR_1, 0, b"ctor input", BASE_GAS * 2, 0);
+    assert!(!receipt.reverted());
+    let mut addr = Address::default();
+    addr.0.copy_from_slice(receipt.output());
+    assert_eq!(
+        bc.last_block().code_at(&addr),
+        Some(b"\0asm created".as_ref())
+    );
+    assert_eq!(bc.last_block().account_meta_at(&addr).unwrap().balance, 5);
+    assert_eq!(
+        bc.last_block().account_meta_at(&ADDR_2).unwrap().balance,
+        giga(2) - 5
+    );
+    assert_eq!(
+        bc.last_block().state_at(&addr).unwrap().get(b"ctor_input"),
+        Some(b"ctor input".to_vec())
+    );
+    let receipt = bc
+        .last_block_mut()
+        .transact(ADDR_1, addr, ADDR_1, 0, &[], BASE_GAS, 0);
+    assert!(!receipt.reverted());
+
+    // The error of a failed constructor is returned to the creator, and no account is created.
+    let num_accounts = bc.dump_state().accounts.len();
+    let receipt = bc
+        .last_block_mut()
+        .transact(ADDR_1, ADDR_2, ADDR_1, 0, &[], BASE_GAS * 2, 0);
+    assert!(receipt.reverted());
+    let err = oasis_std::RpcError::<String>::from(oasis_std::backend::Error::Execution {
+        code: 1,
+        payload: receipt.output().to_vec(),
+    });
+    match err {
+        oasis_std::RpcError::Exec(ctor_err) => assert_eq!(ctor_err, "empty input"),
+        err => panic!("unexpected error: {:?}", err),
+    }
+    assert_eq!(bc.dump_state().accounts.len(), num_accounts);
+}
+
+/// Rejects calls that send value as the dispatcher of a service whose RPCs are not payable does.
+extern "C" fn not_payable_main(
+    ptx: *const *mut dyn PendingTransaction<Address = Address, AccountMeta = AccountMeta>,
+) -> u16 {
+    let ptx = unsafe { &mut **ptx };
+    if ptx.value() != 0 {
+        ptx.err(oasis_std::exe::NOT_PAYABLE_ERROR);
+        return 1;
+    }
+    ptx.ret(b"paid nothing");
+    0
+}
+
+#[test]
+fn not_payable() {
+    let mut bc = create_bc(vec![None, Some(not_payable_main)]);
+
+    let receipt = bc
+        .last_block_mut()
+        .transact(ADDR_1, ADDR_2, ADDR_1, 50, &[], BASE_GAS, 0);
+    assert!(receipt.reverted());
+    let err = oasis_std::RpcError::<String>::from(oasis_std::backend::Error::Execution {
+        code: 1,
+        payload: receipt.output().to_vec(),
+    });
+    match err {
+        oasis_std::RpcError::NotPayable => (),
+        err => panic!("unexpected error: {:?}", err),
+    }
+    assert_eq!(
+        bc.last_block().account_meta_at(&ADDR_1).unwrap().balance,
+        giga(1)
+    );
+    assert_eq!(
+        bc.last_block().account_meta_at(&ADDR_2).unwrap().balance,
+        giga(2)
+    );
+
+    let receipt = bc
+        .last_block_mut()
+        .transact(ADDR_1, ADDR_2, ADDR_1, 0, &[], BASE_GAS, 0);
+    assert!(!receipt.reverted());
+    assert_eq!(receipt.output(), b"paid nothing");
+}
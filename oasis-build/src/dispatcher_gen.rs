@@ -1,5 +1,6 @@
 use std::{io::Write, path::Path};
 
+use rustc::util::nodemap::FxHashMap;
 use syntax::{
     ast::{Arg, Block, Crate, Item, ItemKind, MethodSig, StmtKind},
     print::pprust,
@@ -9,7 +10,7 @@ use syntax_pos::symbol::Symbol;
 
 use crate::{
     parse,
-    visitor::syntax::{ParsedRpc, ParsedRpcKind},
+    visitor::syntax::{ParsedRpc, ParsedRpcKind, RpcAccess},
 };
 
 pub fn generate_and_insert(
@@ -18,7 +19,9 @@ pub fn generate_and_insert(
     crate_name: &str,
     service_name: Symbol,
     ctor: &MethodSig,
+    migration: Option<&ParsedRpc>,
     rpcs: Vec<ParsedRpc>,
+    rpc_selectors: &FxHashMap<String, u32>,
 ) {
     let default_fn = rpcs.iter().find(|rpc| match rpc.kind {
         ParsedRpcKind::Default(_) => true,
@@ -26,7 +29,8 @@ pub fn generate_and_insert(
     });
 
     if !rpcs.is_empty() {
-        let rpcs_dispatcher = generate_rpc_dispatcher(service_name, &rpcs, default_fn);
+        let rpcs_dispatcher =
+            generate_rpc_dispatcher(service_name, &rpcs, default_fn, rpc_selectors);
         let rpcs_include_file = out_dir.join(format!("{}_dispatcher.rs", crate_name));
         let mut buf = Vec::new();
         for stmt in rpcs_dispatcher.stmts.iter() {
@@ -42,6 +46,16 @@ pub fn generate_and_insert(
     krate.module.items.push(
         parse!(format!("include!(\"{}\");", ctor_include_file.display()) => parse_item).unwrap(),
     );
+
+    if let Some(migration) = migration {
+        let migrate_fn = generate_migrate_fn(service_name, migration);
+        let migrate_include_file = out_dir.join(format!("{}_migrate.rs", crate_name));
+        std::fs::write(&migrate_include_file, pprust::item_to_string(&migrate_fn)).unwrap();
+        krate.module.items.push(
+            parse!(format!("include!(\"{}\");", migrate_include_file.display()) => parse_item)
+                .unwrap(),
+        );
+    }
     krate.module.items.insert(0,
         parse!(r#"
             #[cfg(all(
@@ -60,21 +74,35 @@ fn generate_rpc_dispatcher(
     service_name: Symbol,
     rpcs: &[ParsedRpc],
     default_fn: Option<&ParsedRpc>,
+    rpc_selectors: &FxHashMap<String, u32>,
 ) -> P<Block> {
     let mut any_rpc_returns_result = false;
     let mut rpc_payload_variants = Vec::with_capacity(rpcs.len());
+    let mut selector_match_arms = Vec::with_capacity(rpcs.len());
     let rpc_match_arms = rpcs
         .iter()
         .map(|rpc| {
             let (arg_names, arg_tys) = split_args(&rpc.sig.decl.inputs[2..]);
 
             rpc_payload_variants.push(format!("{}({})", rpc.name, tuplize(&arg_tys)));
+            if let Some(selector) = rpc_selectors.get(rpc.name.as_str().get()) {
+                selector_match_arms.push(format!(
+                    "{selector:#010x} => RpcPayload::{name}({args}),",
+                    selector = selector,
+                    name = rpc.name,
+                    args = if arg_tys.is_empty() {
+                        ""
+                    } else {
+                        "oasis_std::reexports::serde_cbor::value::from_value(args).unwrap()"
+                    },
+                ));
+            }
 
             if crate::utils::unpack_syntax_ret(&rpc.sig.decl.output).is_result {
                 any_rpc_returns_result = true;
-                gen_result_dispatch(rpc.name, arg_names)
+                gen_result_dispatch(rpc, arg_names)
             } else {
-                gen_dispatch(rpc.name, arg_names)
+                gen_dispatch(rpc, arg_names)
             }
         })
         .collect::<String>();
@@ -82,9 +110,9 @@ fn generate_rpc_dispatcher(
     let default_fn_arm = if let Some(rpc) = default_fn {
         if crate::utils::unpack_syntax_ret(&rpc.sig.decl.output).is_result {
             any_rpc_returns_result = true;
-            gen_result_dispatch(rpc.name, Vec::new())
+            gen_result_dispatch(rpc, Vec::new())
         } else {
-            gen_dispatch(rpc.name, Vec::new())
+            gen_dispatch(rpc, Vec::new())
         }
     } else {
         String::new()
@@ -102,10 +130,18 @@ fn generate_rpc_dispatcher(
         r#"unreachable!("No RPC function returns Err")"#
     };
 
+    // The error of a batch is the index of the call that failed and that call's error.
+    let batch_err = if any_rpc_returns_result {
+        "oasis_std::reexports::serde_cbor::to_vec(&(i, Value::Bytes(err))).unwrap()"
+    } else {
+        "err"
+    };
+
     parse!(format!(r#"{{
         #[allow(warnings)]
         {{
             use oasis_std::reexports::serde::{{Serialize, Deserialize}};
+            use oasis_std::reexports::serde_cbor::Value;
             use oasis_std::Service as _;
 
             #[derive(Serialize, Deserialize)]
@@ -114,13 +150,67 @@ fn generate_rpc_dispatcher(
                 {rpc_payload_variants}
             }}
 
+            fn decode_selected(selector: u32, args: Value) -> RpcPayload {{
+                match selector {{
+                    {selector_match_arms}
+                    _ => panic!("Unknown RPC selector `{{:#010x}}`", selector),
+                }}
+            }}
+
+            // A call that names its RPC is a CBOR map, and one that uses a selector is an array.
+            fn is_selected(input: &[u8]) -> bool {{
+                input.first().map(|b| b >> 5) == Some(4)
+            }}
+
             let ctx = oasis_std::Context::default(); // TODO(#33)
             let mut service = <{service_ident}>::coalesce();
-            let payload: RpcPayload =
-                oasis_std::reexports::serde_cbor::from_slice(&oasis_std::backend::input()).unwrap();
-            let output: std::result::Result<Vec<u8>, {output_err_ty}> = match payload {{
-                {call_tree}
-                {default_fn_arm}
+            let mut dispatch = |payload: RpcPayload| -> std::result::Result<Vec<u8>, {output_err_ty}> {{
+                match payload {{
+                    {call_tree}
+                    {default_fn_arm}
+                }}
+            }};
+            let input = oasis_std::backend::input();
+            let output = if is_selected(&input) {{
+                let (selector, args): (u32, Value) =
+                    oasis_std::reexports::serde_cbor::from_slice(&input).unwrap();
+                if selector == {batch_selector} {{
+                    // A batch runs its calls in order and fails, reverting them all, if any fails.
+                    // Its calls share one value, so none of them may be sent any.
+                    if ctx.value() != 0 {{
+                        oasis_std::backend::err(oasis_std::exe::NOT_PAYABLE_ERROR);
+                    }}
+                    let calls: Vec<Value> =
+                        oasis_std::reexports::serde_cbor::value::from_value(args).unwrap();
+                    let mut outputs = Vec::with_capacity(calls.len());
+                    let mut batch_output = Ok(());
+                    for (i, call) in calls.into_iter().enumerate() {{
+                        let call = match call {{
+                            Value::Bytes(call) => call,
+                            _ => panic!("Batched calls must be encoded as bytes"),
+                        }};
+                        let payload = if is_selected(&call) {{
+                            let (selector, args) =
+                                oasis_std::reexports::serde_cbor::from_slice(&call).unwrap();
+                            decode_selected(selector, args)
+                        }} else {{
+                            oasis_std::reexports::serde_cbor::from_slice(&call).unwrap()
+                        }};
+                        match dispatch(payload) {{
+                            Ok(output) => outputs.push(Value::Bytes(output)),
+                            Err(err) => {{
+                                batch_output = Err({batch_err});
+                                break;
+                            }}
+                        }}
+                    }}
+                    batch_output
+                        .map(|_| oasis_std::reexports::serde_cbor::to_vec(&outputs).unwrap())
+                }} else {{
+                    dispatch(decode_selected(selector, args))
+                }}
+            }} else {{
+                dispatch(oasis_std::reexports::serde_cbor::from_slice(&input).unwrap())
             }};
             <{service_ident}>::sunder(service);
             match output {{
@@ -130,37 +220,113 @@ fn generate_rpc_dispatcher(
         }}
         }}"#,
         rpc_payload_variants = rpc_payload_variants.join(", "),
+        selector_match_arms = selector_match_arms.join("\n"),
         service_ident = service_name.as_str().get(),
         call_tree = rpc_match_arms,
         default_fn_arm = default_fn_arm,
         output_err_ty = output_err_ty,
+        batch_selector = oasis_rpc::BATCH_SELECTOR,
+        batch_err = batch_err,
         err_returner = err_returner
     ) => parse_block)
 }
 
-fn gen_result_dispatch(name: Symbol, arg_names: Vec<String>) -> String {
+fn gen_result_dispatch(rpc: &ParsedRpc, arg_names: Vec<String>) -> String {
+    let call = format!(
+        "service.{name}(&ctx, {arg_names})",
+        name = rpc.name,
+        arg_names = arg_names.join(",")
+    );
+    // A denied call fails with the RPC's own error type, which must be `From<AccessError>`.
+    let call = match gen_access_check(rpc) {
+        Some(access_check) => format!(
+            r#"match {access_check} {{
+                Ok(()) => {call},
+                Err(err) => Err(From::from(err)),
+            }}"#,
+            access_check = access_check,
+            call = call,
+        ),
+        None => call,
+    };
     format!(
-        r#"RpcPayload::{name}({tup_arg_names}) => match service.{name}(&ctx, {arg_names}) {{
-            Ok(output) => Ok(oasis_std::reexports::serde_cbor::to_vec(&output).unwrap()),
-            Err(err) => Err(oasis_std::reexports::serde_cbor::to_vec(&err).unwrap()),
+        r#"RpcPayload::{name}({tup_arg_names}) => {{
+            {value_check}
+            match {call} {{
+                Ok(output) => Ok(oasis_std::reexports::serde_cbor::to_vec(&output).unwrap()),
+                Err(err) => Err(oasis_std::reexports::serde_cbor::to_vec(&err).unwrap()),
+            }}
         }}"#,
-        name = name,
+        name = rpc.name,
         tup_arg_names = tuplize(&arg_names),
-        arg_names = arg_names.join(","),
+        call = call,
+        value_check = gen_value_check(rpc),
     )
 }
 
-fn gen_dispatch(name: Symbol, arg_names: Vec<String>) -> String {
+fn gen_dispatch(rpc: &ParsedRpc, arg_names: Vec<String>) -> String {
+    let access_check = match gen_access_check(rpc) {
+        Some(access_check) => format!(
+            r#"if let Err(err) = {access_check} {{
+                panic!("RPC `{name}` was denied: {{}}", err);
+            }}"#,
+            access_check = access_check,
+            name = rpc.name,
+        ),
+        None => String::new(),
+    };
     format!(
         r#"RpcPayload::{name}({tup_arg_names}) => {{
+            {value_check}
+            {access_check}
             Ok(oasis_std::reexports::serde_cbor::to_vec(&service.{name}(&ctx, {arg_names})).unwrap())
         }}"#,
-        name = name,
+        name = rpc.name,
         tup_arg_names = tuplize(&arg_names),
         arg_names = arg_names.join(","),
+        value_check = gen_value_check(rpc),
+        access_check = access_check,
     )
 }
 
+/// Returns the expression that checks the sender against the access-control attributes
+/// of `rpc`, if it has any, using the `AccessControl` of the service.
+fn gen_access_check(rpc: &ParsedRpc) -> Option<String> {
+    let RpcAccess {
+        only,
+        when_not_paused,
+    } = &rpc.access;
+    if only.is_empty() && !when_not_paused {
+        return None;
+    }
+    let roles = only
+        .iter()
+        .filter_map(|principal| match principal {
+            oasis_rpc::Principal::Role(role) => Some(format!("{:?}", role)),
+            oasis_rpc::Principal::Owner => None,
+        })
+        .collect::<Vec<_>>();
+    Some(format!(
+        "oasis_std::access::AccessControlled::access_control(&service).check(\
+            &ctx.sender(), {owner}, &[{roles}], {when_not_paused})",
+        owner = only.contains(&oasis_rpc::Principal::Owner),
+        roles = roles.join(", "),
+        when_not_paused = when_not_paused,
+    ))
+}
+
+/// Returns the statement that fails a call that sends value to an RPC that is not `#[payable]`
+/// with the error that clients decode as `RpcError::NotPayable`.
+fn gen_value_check(rpc: &ParsedRpc) -> String {
+    if rpc.payable {
+        return String::new();
+    }
+    r#"if ctx.value() != 0 {
+        oasis_std::backend::err(oasis_std::exe::NOT_PAYABLE_ERROR);
+    }"#
+    .to_string()
+}
+
 fn generate_ctor_fn(service_name: Symbol, ctor: &MethodSig) -> P<Item> {
     let (arg_names, arg_tys) = split_args(&ctor.decl.inputs[1..]);
 
@@ -180,7 +346,7 @@ fn generate_ctor_fn(service_name: Symbol, ctor: &MethodSig) -> P<Item> {
             match <{service_ident}>::new(&ctx, {arg_names}) {{
                 Ok(service) => service,
                 Err(err) => {{
-                    oasis_std::backend::err(&format!("{{:#?}}", err).into_bytes());
+                    oasis_std::backend::err(&oasis_std::reexports::serde_cbor::to_vec(&err).unwrap());
                     return 1;
                 }}
             }}
@@ -222,6 +388,71 @@ fn generate_ctor_fn(service_name: Symbol, ctor: &MethodSig) -> P<Item> {
     .unwrap()
 }
 
+/// Generates `_oasis_migrate`, which is called once after the service code is upgraded.
+/// Like the constructor, the migration function returns the new service state.
+fn generate_migrate_fn(service_name: Symbol, migration: &ParsedRpc) -> P<Item> {
+    let (arg_names, arg_tys) = split_args(&migration.sig.decl.inputs[1..]);
+
+    let migration_payload_unpack = if migration.sig.decl.inputs.len() > 1 {
+        format!(
+            "let MigrationPayload({}) =
+                    oasis_std::reexports::serde_cbor::from_slice(&oasis_std::backend::input()).unwrap();",
+            tuplize(&arg_names)
+        )
+    } else {
+        String::new()
+    };
+
+    let migrate_stmt = if crate::utils::unpack_syntax_ret(&migration.sig.decl.output).is_result {
+        format!(
+            r#"
+            match <{service_ident}>::{name}(&ctx, {arg_names}) {{
+                Ok(service) => service,
+                Err(err) => {{
+                    oasis_std::backend::err(&oasis_std::reexports::serde_cbor::to_vec(&err).unwrap());
+                    return 1;
+                }}
+            }}
+            "#,
+            service_ident = service_name.as_str().get(),
+            name = migration.name,
+            arg_names = arg_names.join(","),
+        )
+    } else {
+        format!(
+            "<{service_ident}>::{name}(&ctx, {arg_names})",
+            service_ident = service_name.as_str().get(),
+            name = migration.name,
+            arg_names = arg_names.join(",")
+        )
+    };
+
+    parse!(format!(r#"
+            #[allow(warnings)]
+            #[no_mangle]
+            extern "C" fn _oasis_migrate() -> u8 {{
+                use oasis_std::Service as _;
+                use oasis_std::reexports::serde::{{Serialize, Deserialize}};
+
+                #[derive(Serialize, Deserialize)]
+                #[allow(non_camel_case_types)]
+                struct MigrationPayload({migration_payload_types});
+
+                let ctx = oasis_std::Context::default(); // TODO(#33)
+                {migration_payload_unpack}
+                let mut service = {migrate_stmt};
+                <{service_ident}>::sunder(service);
+                return 0;
+            }}
+        "#,
+        migrate_stmt = migrate_stmt,
+        migration_payload_unpack = migration_payload_unpack,
+        migration_payload_types = tuplize(&arg_tys),
+        service_ident = service_name.as_str().get(),
+    ) => parse_item)
+    .unwrap()
+}
+
 fn insert_rpc_dispatcher_stub(krate: &mut Crate, include_file: &Path) {
     for item in krate.module.items.iter_mut() {
         if item.ident.name != Symbol::intern("main") {
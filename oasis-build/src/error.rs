@@ -4,7 +4,7 @@ use syntax_pos::{MultiSpan, Span};
 
 pub enum UnsupportedTypeError {
     NotReprC(String /* type name string */, MultiSpan),
-    ComplexEnum(MultiSpan),
+    Int128(&'static str /* `u128` or `i128` */, MultiSpan),
 }
 
 impl std::fmt::Display for UnsupportedTypeError {
@@ -12,7 +12,12 @@ impl std::fmt::Display for UnsupportedTypeError {
         use UnsupportedTypeError::*;
         match self {
             NotReprC(ty_str, ..) => write!(f, "`{}` cannot be converted to an RPC type", ty_str),
-            ComplexEnum(..) => write!(f, "Tagged unions cannot (yet) be converted to an RPC type"),
+            Int128(ty_str, ..) => write!(
+                f,
+                "`{}` cannot be converted to an RPC type. Maybe try `oasis_std::{}`?",
+                ty_str,
+                ty_str.to_uppercase()
+            ),
         }
     }
 }
@@ -21,24 +26,28 @@ impl UnsupportedTypeError {
     pub fn span(&self) -> MultiSpan {
         use UnsupportedTypeError::*;
         match &self {
-            NotReprC(_, span) | ComplexEnum(span) => span.clone(),
+            NotReprC(_, span) | Int128(_, span) => span.clone(),
         }
     }
 }
 
 pub enum RpcError {
+    AccessNotRpc(Span),
     BadArgPat(Span),
     BadArgTy {
         span: Span,
         suggestion: String,
     },
     BadStruct(Span),
+    BadOnly(Span),
     BadCtorReturn {
         self_ty: syntax::ast::Ty,
         span: Span,
     },
     CtorIsDefault(Span),
+    CtorIsMigration(Span),
     DefaultFnHasArg(Span),
+    DefaultIsMigration(Span),
     HasAbi(Span),
     HasAsync(Span),
     HasGenerics(Span),
@@ -46,7 +55,15 @@ pub enum RpcError {
         from_ctor: bool,
         span: Span,
     },
+    MigrationHasSelf(Span),
+    MigrationMissingContext(Span),
+    MigrationNotPub(Span),
+    BadMigrationReturn {
+        self_ty: syntax::ast::Ty,
+        span: Span,
+    },
     MissingSelf(Span),
+    PayableNotRpc(Span),
     Unsafe(Span),
 }
 
@@ -54,6 +71,7 @@ impl std::fmt::Display for RpcError {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         use RpcError::*;
         match self {
+            AccessNotRpc(..) => write!(f, "Only RPC methods can have access-control attributes."),
             BadArgPat(..) => write!(f, "Argument name must be a valid identifier."),
             BadArgTy { suggestion, .. } => write!(
                 f,
@@ -61,6 +79,10 @@ impl std::fmt::Display for RpcError {
                 suggestion
             ),
             BadStruct(..) => write!(f, "Service state definition must have named fields."),
+            BadOnly(..) => write!(
+                f,
+                "Expected `#[only(owner)]`, `#[only(role = \"name\")]`, or a list of them."
+            ),
             BadCtorReturn { self_ty, .. } => {
                 let self_ty_str = format!("{:?}", self_ty);
                 write!(
@@ -70,9 +92,15 @@ impl std::fmt::Display for RpcError {
                 )
             }
             CtorIsDefault(..) => write!(f, "Service constructor cannot be the default function."),
+            CtorIsMigration(..) => {
+                write!(f, "Service constructor cannot be the migration function.")
+            }
             DefaultFnHasArg(..) => {
                 write!(f, "Default function cannot take arguments after `Context`.")
             }
+            DefaultIsMigration(..) => {
+                write!(f, "Default function cannot be the migration function.")
+            }
             HasAbi(..) => write!(f, "RPC method cannot declare an ABI."),
             HasAsync(..) => write!(f, "RPC method cannot be async."),
             HasGenerics(..) => write!(f, "RPC definition cannot have generic parameters."),
@@ -86,10 +114,31 @@ impl std::fmt::Display for RpcError {
                     write!(f, "RPC method must take `&Context` as its second argument.")
                 }
             }
+            MigrationHasSelf(..) => write!(
+                f,
+                "Migration function must not take `self`. It should return the migrated `Self`."
+            ),
+            MigrationMissingContext(..) => write!(
+                f,
+                "Migration function must take `&Context` as its first argument."
+            ),
+            MigrationNotPub(..) => write!(f, "Migration function must be `pub`."),
+            BadMigrationReturn { self_ty, .. } => {
+                let self_ty_str = format!("{:?}", self_ty);
+                write!(
+                    f,
+                    "Migration function must return `Self` (aka `{}`)",
+                    &self_ty_str["type(".len()..(self_ty_str.len() - 1)]
+                )
+            }
             MissingSelf(..) => write!(
                 f,
                 "RPC method must take `&self` or `&mut self` as its first argument."
             ),
+            PayableNotRpc(..) => write!(
+                f,
+                "Only RPC methods can be `#[payable]`. The constructor always accepts value."
+            ),
             Unsafe(..) => write!(f, "RPC method cannot be unsafe."),
         }
     }
@@ -99,17 +148,26 @@ impl RpcError {
     pub fn span(&self) -> Span {
         use RpcError::*;
         match self {
-            BadArgPat(span)
+            AccessNotRpc(span)
+            | BadArgPat(span)
             | BadArgTy { span, .. }
             | BadStruct(span)
+            | BadOnly(span)
             | BadCtorReturn { span, .. }
             | CtorIsDefault(span)
+            | CtorIsMigration(span)
             | DefaultFnHasArg(span)
+            | DefaultIsMigration(span)
             | HasAbi(span)
             | HasAsync(span)
             | HasGenerics(span)
             | MissingContext { span, .. }
+            | MigrationHasSelf(span)
+            | MigrationMissingContext(span)
+            | MigrationNotPub(span)
+            | BadMigrationReturn { span, .. }
             | MissingSelf(span)
+            | PayableNotRpc(span)
             | Unsafe(span) => *span,
         }
     }
@@ -27,6 +27,8 @@ fn sanitize_ident(ident: &str) -> String {
 pub struct Import {
     pub name: String,
     pub version: String,
+    /// The registry from which the service was imported, if any.
+    pub registry: Option<String>,
     pub lib_path: std::path::PathBuf,
 }
 
@@ -38,19 +40,46 @@ pub fn build(
 ) -> Result<Vec<Import>, failure::Error> {
     let out_dir = out_dir.as_ref();
 
-    let services = oasis_rpc::import::Resolver::new(
-        top_level_deps.into_iter().collect(),
-        std::path::PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap()),
-    )
-    .resolve()?;
+    let crate_dir = std::path::PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap());
+    let lock_path = crate_dir.join(oasis_rpc::import::LOCKFILE_NAME);
+    let lockfile = oasis_rpc::import::Lockfile::load(&lock_path)?;
+    let top_level_deps: Vec<_> = top_level_deps.into_iter().collect();
+    let has_deps = !top_level_deps.is_empty();
+
+    let mut resolver = oasis_rpc::import::Resolver::new(top_level_deps, crate_dir);
+    if let Some(lockfile) = lockfile.clone() {
+        resolver = resolver.with_lockfile(lockfile);
+    }
+    let (services, new_lockfile) = resolver.resolve_locked()?;
+    // Services that are no longer imported are dropped from the lockfile, but a crate
+    // that imports nothing does not need one.
+    if lockfile.as_ref() != Some(&new_lockfile) && (has_deps || lockfile.is_some()) {
+        new_lockfile.save(&lock_path)?;
+    }
 
     let mut imports = Vec::with_capacity(services.len());
 
     rustc_args.push("--crate-name".to_string());
 
+    // The resolver orders services after the services they import, so those have been built
+    // by the time that they're needed to resolve the `Type::Defined { namespace }`s of a service.
     for service in services {
+        let dep_externs: Vec<String> = service
+            .interface
+            .imports
+            .iter()
+            .filter_map(|imp| {
+                let dep_name = sanitize_ident(&imp.name).to_snake_case();
+                imports
+                    .iter()
+                    .find(|built| built.name == dep_name)
+                    .map(|built| format!("{}={}", built.name, built.lib_path.display()))
+            })
+            .collect();
+
         let mut hasher = DefaultHasher::new();
         service.interface.hash(&mut hasher);
+        dep_externs.hash(&mut hasher); // rebuild when an imported service changes
         let interface_hash = hasher.finish();
 
         let mod_name = sanitize_ident(&service.interface.namespace).to_snake_case();
@@ -60,6 +89,7 @@ pub fn build(
         imports.push(Import {
             name: mod_name.clone(),
             version: service.interface.version.clone(),
+            registry: service.registry.clone(),
             lib_path: lib_path.clone(),
         });
 
@@ -98,14 +128,17 @@ pub fn build(
             version = service.interface.version,
             path = mod_path.display()
         );
+        let num_common_args = rustc_args.len();
         rustc_args.push(mod_name.clone());
         rustc_args.push(mod_path.display().to_string());
         rustc_args.push(format!("-Cextra-filename=-{:016x}", interface_hash));
+        for dep_extern in dep_externs {
+            rustc_args.push("--extern".to_string());
+            rustc_args.push(dep_extern);
+        }
         rustc_driver::run_compiler(&rustc_args, &mut rustc_driver::DefaultCallbacks, None, None)
             .map_err(|_| failure::format_err!("Could not build `{}`", mod_name))?;
-        rustc_args.pop();
-        rustc_args.pop();
-        rustc_args.pop();
+        rustc_args.truncate(num_common_args);
     }
     Ok(imports)
 }
@@ -113,6 +146,7 @@ pub fn build(
 fn gen_def_tys<'a>(defs: &'a [oasis_rpc::TypeDef]) -> impl Iterator<Item = TokenStream> + 'a {
     defs.iter().map(|def| {
         let name = format_ident!("{}", def.name());
+        let docs = quote_docs(def.docs());
         let derives = quote!(Serialize, Deserialize, Debug, Clone, PartialEq, Hash);
         match def {
             oasis_rpc::TypeDef::Struct { fields, .. } => {
@@ -121,24 +155,43 @@ fn gen_def_tys<'a>(defs: &'a [oasis_rpc::TypeDef]) -> impl Iterator<Item = Token
                     .enumerate()
                     .all(|(i, f)| usize::from_str(&f.name) == Ok(i));
                 let tys = fields.iter().map(|f| quote_ty(&f.ty));
+                let field_docs = fields.iter().map(|f| quote_docs(f.docs.as_ref()));
                 if is_newtype {
                     quote! {
+                        #docs
                         #[derive(#derives)]
-                        pub struct #name(#(pub #tys),*);
+                        pub struct #name(#(#field_docs pub #tys),*);
                     }
                 } else {
                     let field_names = fields.iter().map(|f| format_ident!("{}", f.name));
                     quote! {
+                        #docs
                         #[derive(#derives)]
                         pub struct #name {
-                            #(pub #field_names: #tys),*
+                            #(#field_docs pub #field_names: #tys),*
                         }
                     }
                 }
             }
             oasis_rpc::TypeDef::Enum { variants, .. } => {
-                let variants = variants.iter().map(|v| format_ident!("{}", v));
+                let variants = variants.iter().map(|v| {
+                    let var_name = format_ident!("{}", v.name());
+                    match v {
+                        oasis_rpc::Variant::Unit(_) => quote!(#var_name),
+                        oasis_rpc::Variant::Tuple { items, .. } => {
+                            let tys = items.iter().map(quote_ty);
+                            quote!(#var_name(#(#tys),*))
+                        }
+                        oasis_rpc::Variant::Struct { fields, .. } => {
+                            let field_names = fields.iter().map(|f| format_ident!("{}", f.name));
+                            let tys = fields.iter().map(|f| quote_ty(&f.ty));
+                            let field_docs = fields.iter().map(|f| quote_docs(f.docs.as_ref()));
+                            quote!(#var_name { #(#field_docs #field_names: #tys),* })
+                        }
+                    }
+                });
                 quote! {
+                    #docs
                     #[derive(#derives)]
                     pub enum #name {
                         #(#variants),*
@@ -151,6 +204,7 @@ fn gen_def_tys<'a>(defs: &'a [oasis_rpc::TypeDef]) -> impl Iterator<Item = Token
             } => {
                 let field_names = indexed_fields.iter().map(|f| format_ident!("{}", f.name));
                 let tys = indexed_fields.iter().map(|f| quote_ty(&f.ty));
+                let field_docs = indexed_fields.iter().map(|f| quote_docs(f.docs.as_ref()));
                 let indexeds = indexed_fields.iter().map(|f| {
                     if f.indexed {
                         quote!(#[indexed])
@@ -159,9 +213,10 @@ fn gen_def_tys<'a>(defs: &'a [oasis_rpc::TypeDef]) -> impl Iterator<Item = Token
                     }
                 });
                 quote! {
+                    #docs
                     #[derive(#derives, Event)]
                     pub struct #name {
-                        #(#indexeds #field_names: #tys),*
+                        #(#field_docs #indexeds #field_names: #tys),*
                     }
                 }
             }
@@ -173,14 +228,31 @@ fn gen_client(service: &ImportedService) -> TokenStream {
     let ImportedService {
         bytecode,
         interface,
+        address,
+        ..
     } = service;
 
     let client_ident = format_ident!("{}Client", sanitize_ident(&interface.name).to_camel_case());
 
     let rpcs = gen_rpcs(&interface.functions);
+    let batch = gen_batch(&client_ident, &interface.functions);
     let ctor_fns = gen_ctors(&interface.constructor, &bytecode);
+    let deployed_fn = match address {
+        Some(address) => {
+            let address_bytes = address.iter();
+            quote! {
+                /// Returns a client of the service at the address from which it was imported.
+                pub fn deployed() -> Self {
+                    Self::at(oasis_std::Address([#(#address_bytes),*]))
+                }
+            }
+        }
+        None => quote!(),
+    };
+    let docs = quote_docs(interface.docs.as_ref());
 
     quote! {
+        #docs
         pub struct #client_ident {
             pub address: oasis_std::Address,
         }
@@ -188,56 +260,43 @@ fn gen_client(service: &ImportedService) -> TokenStream {
         impl #client_ident {
             #ctor_fns
 
+            #deployed_fn
+
             #(#rpcs)*
         }
+
+        #batch
     }
 }
 
 fn gen_rpcs<'a>(functions: &'a [oasis_rpc::Function]) -> impl Iterator<Item = TokenStream> + 'a {
-    functions.iter().enumerate().map(|(fn_idx, func)| {
+    functions.iter().map(|func| {
         let fn_name = format_ident!("{}", func.name);
+        let docs = quote_fn_docs(func.docs.as_ref(), &func.inputs);
 
         let self_ref = match func.mutability {
             oasis_rpc::StateMutability::Immutable => quote! { &self },
             oasis_rpc::StateMutability::Mutable => quote! { &mut self },
         };
 
-        let num_args = func.inputs.len();
-        let (arg_names, arg_tys): (Vec<Ident>, Vec<TokenStream>) = func
-            .inputs
-            .iter()
-            .map(|field| (format_ident!("{}", field.name), quote_borrow(&field.ty)))
-            .unzip();
-
-        let (output_ty, err_ty) = match &func.output {
-            Some(oasis_rpc::Type::Result(ok_ty, err_ty)) => (quote_ty(ok_ty), quote_ty(err_ty)),
-            Some(ty) => (quote_ty(ty), quote!(())),
-            None => (quote!(()), quote!(())),
-        };
+        let (arg_names, arg_tys) = split_inputs(&func.inputs);
+        let (output_ty, err_ty) = quote_output(&func.output);
+        let encode_payload = quote_payload(func);
 
         quote! {
+            #docs
             pub fn #fn_name(
                 #self_ref,
                 ctx: &oasis_std::Context,
                 #(#arg_names: #arg_tys),*
            ) -> Result<#output_ty, oasis_std::RpcError<#err_ty>> {
-                use serde::ser::{Serializer as _, SerializeTupleVariant as _};
-                let mut serializer = oasis_std::reexports::serde_cbor::Serializer::new(Vec::new());
-                let mut state = serializer.serialize_tuple_variant(
-                    "" /* unused enum name */,
-                    #fn_idx as u32 /* unused */,
-                    stringify!(#fn_name),
-                    #num_args
-                ) .unwrap();
-                #(state.serialize_field(#arg_names.borrow()).unwrap();)*
-                state.end().unwrap();
-                let payload = serializer.into_inner();
+                #encode_payload
 
                 #[cfg(target_os = "wasi")] {
                     let output = oasis_std::backend::transact(
                         &self.address,
                         ctx.value.unwrap_or(0),
-                        &oasis_std::reexports::serde_cbor::to_vec(&payload).unwrap()
+                        &payload
                     )?;
                     Ok(oasis_std::reexports::serde_cbor::from_slice(&output)
                        .map_err(|_| oasis_std::RpcError::InvalidOutput(output))?)
@@ -250,7 +309,128 @@ fn gen_rpcs<'a>(functions: &'a [oasis_rpc::Function]) -> impl Iterator<Item = To
     })
 }
 
-fn gen_ctors(ctor: &oasis_rpc::Constructor, _bytecode: &[u8]) -> TokenStream {
+/// Generates the builder of batches of calls to the service, which are sent in one transaction.
+/// An interface having a function without a selector was exported by a dispatcher that
+/// predates batches, so no builder is generated for it.
+fn gen_batch(client_ident: &Ident, functions: &[oasis_rpc::Function]) -> TokenStream {
+    if functions.iter().any(|func| func.selector.is_none()) {
+        return quote!();
+    }
+
+    let batch_ident = format_ident!(
+        "{}Batch",
+        client_ident.to_string().trim_end_matches("Client")
+    );
+
+    let batch_rpcs = functions.iter().map(|func| {
+        let fn_name = format_ident!("{}", func.name);
+        let docs = quote_fn_docs(func.docs.as_ref(), &func.inputs);
+        let (arg_names, arg_tys) = split_inputs(&func.inputs);
+        let (output_ty, err_ty) = quote_output(&func.output);
+        let encode_payload = quote_payload(func);
+        quote! {
+            #docs
+            pub fn #fn_name(
+                &mut self,
+                #(#arg_names: #arg_tys),*
+            ) -> oasis_std::BatchCall<#output_ty, #err_ty> {
+                #encode_payload
+                self.calls.push(oasis_std::reexports::serde_cbor::Value::Bytes(payload));
+                oasis_std::BatchCall::new(self.calls.len() - 1)
+            }
+        }
+    });
+
+    let batch_selector = oasis_rpc::BATCH_SELECTOR;
+
+    quote! {
+        /// Collects calls to the service that are sent in one transaction. The calls take
+        /// effect in order and together or, if any of them fails, not at all.
+        pub struct #batch_ident<'a> {
+            client: &'a mut #client_ident,
+            calls: Vec<oasis_std::reexports::serde_cbor::Value>,
+        }
+
+        impl #client_ident {
+            /// Returns a builder of a batch of calls to this service.
+            pub fn batch(&mut self) -> #batch_ident<'_> {
+                #batch_ident {
+                    client: self,
+                    calls: Vec::new(),
+                }
+            }
+        }
+
+        impl<'a> #batch_ident<'a> {
+            #(#batch_rpcs)*
+
+            /// Sends the batched calls and returns their outputs.
+            pub fn send(
+                self,
+                ctx: &oasis_std::Context,
+            ) -> Result<oasis_std::BatchOutputs, oasis_std::RpcError<oasis_std::BatchError>> {
+                let payload = oasis_std::reexports::serde_cbor::to_vec(
+                    &(#batch_selector, self.calls)
+                ).unwrap();
+
+                #[cfg(target_os = "wasi")] {
+                    let output = oasis_std::backend::transact(
+                        &self.client.address,
+                        0, // batches are not payable
+                        &payload
+                    )?;
+                    oasis_std::BatchOutputs::from_output(output)
+                }
+                #[cfg(not(target_os = "wasi"))] {
+                    unimplemented!("Native client not yet implemented.")
+                }
+            }
+        }
+    }
+}
+
+/// Generates the statements that encode a call to `func` into `payload`.
+fn quote_payload(func: &oasis_rpc::Function) -> TokenStream {
+    let (arg_names, _) = split_inputs(&func.inputs);
+    match func.selector {
+        Some(selector) => quote! {
+            let payload = oasis_std::reexports::serde_cbor::to_vec(
+                &(#selector, (#(#arg_names.borrow(),)*))
+            ).unwrap();
+        },
+        None => {
+            let fn_name = &func.name;
+            // The payload of an RPC without arguments is an empty array rather than `()`.
+            let args = if arg_names.is_empty() {
+                quote!([(); 0])
+            } else {
+                quote!((#(#arg_names.borrow(),)*))
+            };
+            quote! {
+                let payload = oasis_std::exe::encode_named_call(#fn_name, &#args);
+            }
+        }
+    }
+}
+
+/// Returns the names of the client method parameters of `inputs` and their types.
+fn split_inputs(inputs: &[oasis_rpc::Field]) -> (Vec<Ident>, Vec<TokenStream>) {
+    inputs
+        .iter()
+        .map(|field| (format_ident!("{}", field.name), quote_borrow(&field.ty)))
+        .unzip()
+}
+
+/// Returns the type of the successful output of an RPC and the type of its error.
+fn quote_output(output: &Option<oasis_rpc::Type>) -> (TokenStream, TokenStream) {
+    match output {
+        Some(oasis_rpc::Type::Result(ok_ty, err_ty)) => (quote_ty(ok_ty), quote_ty(err_ty)),
+        Some(ty) => (quote_ty(ty), quote!(())),
+        None => (quote!(()), quote!(())),
+    }
+}
+
+fn gen_ctors(ctor: &oasis_rpc::Constructor, bytecode: &[u8]) -> TokenStream {
     let (arg_names, arg_tys): (Vec<Ident>, Vec<TokenStream>) = ctor
         .inputs
         .iter()
@@ -263,12 +443,29 @@ fn gen_ctors(ctor: &oasis_rpc::Constructor, _bytecode: &[u8]) -> TokenStream {
         quote!(())
     };
 
+    let docs = quote_fn_docs(ctor.docs.as_ref(), &ctor.inputs);
+    let bytecode = Literal::byte_string(bytecode);
+
     quote! {
+        #docs
         pub fn new(
             ctx: &oasis_std::Context,
-            #(#arg_names: #arg_tys)*
+            #(#arg_names: #arg_tys),*
         ) -> Result<Self, oasis_std::RpcError<#error_ty>> {
-            unimplemented!()
+            // The service's `_oasis_deploy` decodes its arguments as a CBOR tuple.
+            let ctor_input = oasis_std::reexports::serde_cbor::to_vec(&(#(#arg_names,)*)).unwrap();
+
+            #[cfg(target_os = "wasi")] {
+                let address = oasis_std::backend::create(
+                    ctx.value.unwrap_or(0),
+                    #bytecode,
+                    &ctor_input,
+                )?;
+                Ok(Self::at(address))
+            }
+            #[cfg(not(target_os = "wasi"))] {
+                unimplemented!("Native client not yet implemented.")
+            }
         }
 
         pub fn at(address: oasis_std::Address) -> Self {
@@ -279,6 +476,32 @@ fn gen_ctors(ctor: &oasis_rpc::Constructor, _bytecode: &[u8]) -> TokenStream {
     }
 }
 
+fn quote_docs<S: AsRef<str>>(docs: Option<S>) -> TokenStream {
+    match docs {
+        Some(docs) => {
+            let docs = docs.as_ref();
+            quote!(#[doc = #docs])
+        }
+        None => quote!(),
+    }
+}
+
+/// Quotes the docs of a function, to which the docs of its inputs are added
+/// as an `# Arguments` section.
+fn quote_fn_docs(docs: Option<&String>, inputs: &[oasis_rpc::Field]) -> TokenStream {
+    let arg_docs: Vec<_> = inputs
+        .iter()
+        .filter_map(|inp| Some(format!("* `{}` - {}", inp.name, inp.docs.as_ref()?)))
+        .collect();
+    if arg_docs.is_empty() {
+        return quote_docs(docs);
+    }
+    let mut fn_docs = docs.map(|docs| format!("{}\n\n", docs)).unwrap_or_default();
+    fn_docs.push_str("# Arguments\n\n");
+    fn_docs.push_str(&arg_docs.join("\n"));
+    quote_docs(Some(fn_docs))
+}
+
 fn quote_ty(ty: &oasis_rpc::Type) -> TokenStream {
     use oasis_rpc::Type;
     match ty {
@@ -291,6 +514,9 @@ fn quote_ty(ty: &oasis_rpc::Type) -> TokenStream {
         Type::I32 => quote!(i32),
         Type::U64 => quote!(u64),
         Type::I64 => quote!(i64),
+        Type::U128 => quote!(oasis_std::U128),
+        Type::I128 => quote!(oasis_std::I128),
+        Type::U256 => quote!(oasis_std::U256),
         Type::F32 => quote!(f32),
         Type::F64 => quote!(f64),
         Type::Bytes => quote!(Vec<u8>),
@@ -352,6 +578,8 @@ fn quote_borrow(ty: &oasis_rpc::Type) -> TokenStream {
         | Type::I32
         | Type::U64
         | Type::I64
+        | Type::U128
+        | Type::I128
         | Type::F32
         | Type::F64 => {
             return quote_ty(ty);
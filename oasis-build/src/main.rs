@@ -6,10 +6,7 @@
 extern crate rustc;
 extern crate rustc_driver;
 
-use std::{
-    collections::BTreeMap,
-    path::{Path, PathBuf},
-};
+use std::path::{Path, PathBuf};
 
 use colored::*;
 use rustc::util::common::ErrorReported;
@@ -54,9 +51,7 @@ fn main() {
             let gen_dir = out_dir.parent().unwrap().join("build/oasis_imports");
             std::fs::create_dir_all(&gen_dir).unwrap();
 
-            let mut manifest_path = PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap());
-            manifest_path.push("Cargo.toml");
-            match load_deps(&manifest_path).and_then(|services| {
+            match oasis_rpc::import::manifest_deps(&manifest_path()).and_then(|services| {
                 oasis_build::build_imports(
                     services,
                     gen_dir,
@@ -81,8 +76,37 @@ fn main() {
             Vec::new()
         };
 
-        let mut idl8r =
-            oasis_build::BuildPlugin::new(imports.into_iter().map(|imp| (imp.name, imp.version)));
+        let imports: Vec<_> = imports
+            .into_iter()
+            .map(|imp| (imp.name, imp.version, imp.registry))
+            .collect();
+
+        // The selectors that the dispatcher accepts are computed from the RPCs' analyzed types,
+        // but the dispatcher is generated before analysis, so the service is analyzed first.
+        // Its lints are reported only once, by the compilation that follows.
+        let rpc_selectors = if is_service {
+            let mut analysis_args = args.clone();
+            if get_arg("--cap-lints", &args).is_none() {
+                analysis_args.push("--cap-lints".to_string());
+                analysis_args.push("allow".to_string());
+            }
+            let mut analyzer = oasis_build::BuildPlugin::new(imports.clone()).analysis_only();
+            rustc_driver::run_compiler(&analysis_args, &mut analyzer, None, None)?;
+            analyzer
+                .try_get()
+                .map(|iface| {
+                    iface
+                        .functions
+                        .iter()
+                        .filter_map(|func| Some((func.name.clone(), func.selector?)))
+                        .collect()
+                })
+                .unwrap_or_default()
+        } else {
+            Vec::new()
+        };
+
+        let mut idl8r = oasis_build::BuildPlugin::new(imports).with_rpc_selectors(rpc_selectors);
         let mut default_cbs = rustc_driver::DefaultCallbacks;
         let callbacks: &mut (dyn rustc_driver::Callbacks + Send) = if is_service || is_testing {
             &mut idl8r
@@ -109,6 +133,19 @@ fn main() {
             }
         };
 
+        if let Err(err) = rpc_iface.validate() {
+            eprintln!("    {} {}", "error:".red(), err);
+            return Err(ErrorReported);
+        }
+
+        if let Err(err) = load_upgrade_source(&manifest_path()).and_then(|source| match source {
+            Some(source) => check_upgrade(&source, &rpc_iface),
+            None => Ok(()),
+        }) {
+            eprintln!("    {} {}", "error:".red(), err);
+            return Err(ErrorReported);
+        }
+
         let out_dir = out_dir.as_ref().unwrap();
         let wasm_path = out_dir.join(format!("{}.wasm", service_name));
         if wasm_path.is_file() {
@@ -168,17 +205,53 @@ fn collect_import_rustc_args(args: &[String]) -> Vec<String> {
     import_args
 }
 
-fn load_deps(manifest_path: &Path) -> Result<BTreeMap<String, String>, failure::Error> {
+fn manifest_path() -> PathBuf {
+    let mut manifest_path = PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap());
+    manifest_path.push("Cargo.toml");
+    manifest_path
+}
+
+/// Returns the URL of the service that the current service upgrades, if any.
+/// Specified in the manifest as `[package.metadata.oasis-upgrade] from = "<url>"`.
+fn load_upgrade_source(manifest_path: &Path) -> Result<Option<String>, failure::Error> {
     let cargo_toml: toml::Value = toml::from_slice(&std::fs::read(manifest_path).unwrap()).unwrap();
     Ok(cargo_toml
         .as_table()
         .and_then(|c_t| c_t.get("package").and_then(toml::Value::as_table))
         .and_then(|p| p.get("metadata").and_then(toml::Value::as_table))
-        .and_then(|m| m.get("oasis-dependencies"))
-        .cloned()
-        .map(|d| d.try_into::<BTreeMap<String, String>>())
-        .unwrap_or_else(|| Ok(BTreeMap::new()))
-        .map_err(|err| failure::format_err!("Could not parse Oasis dependencies: {}", err))?)
+        .and_then(|m| m.get("oasis-upgrade").and_then(toml::Value::as_table))
+        .and_then(|u| u.get("from"))
+        .map(|from| {
+            from.as_str().map(str::to_string).ok_or_else(|| {
+                failure::format_err!("`package.metadata.oasis-upgrade.from` must be a URL")
+            })
+        })
+        .transpose()?)
+}
+
+/// Checks that the service built from this crate can replace the service at `source_url`.
+fn check_upgrade(source_url: &str, iface: &oasis_rpc::Interface) -> Result<(), failure::Error> {
+    let base_dir = manifest_path().parent().unwrap().to_path_buf();
+    let old_iface = oasis_rpc::import::Importer::for_url(source_url, base_dir)?
+        .import(&iface.name)?
+        .interface;
+    // The constructor of an upgraded service is never called, so it may change freely.
+    let incompatibilities: Vec<_> = oasis_rpc::compat::diff(&old_iface, iface)
+        .into_iter()
+        .filter(|change| {
+            change.is_breaking() && *change != oasis_rpc::compat::Change::ConstructorChanged
+        })
+        .map(|change| change.to_string())
+        .collect();
+    if incompatibilities.is_empty() {
+        return Ok(());
+    }
+    Err(failure::format_err!(
+        "`{}` cannot be upgraded from `{}`:\n        {}",
+        iface.name,
+        source_url,
+        incompatibilities.join("\n        ")
+    ))
 }
 
 fn pack_iface_into_wasm(
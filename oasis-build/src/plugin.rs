@@ -1,32 +1,65 @@
 use std::collections::BTreeSet; // BTree for reproducability
 
-use rustc::{hir::intravisit::Visitor, util::nodemap::FxHashMap};
+use rustc::{
+    hir::intravisit::Visitor,
+    util::nodemap::{FxHashMap, FxHashSet},
+};
 use rustc_data_structures::sync::Once;
 use rustc_driver::Compilation;
 use syntax_pos::symbol::Symbol;
 
 use crate::visitor::{
     hir::{AnalyzedRpcCollector, DefinedTypeCollector, EventCollector},
-    syntax::{ParsedRpcCollector, ParsedRpcKind, ServiceDefFinder},
+    syntax::{ParsedRpcCollector, ParsedRpcKind, RpcAccess, ServiceDefFinder},
 };
 
 pub struct BuildPlugin {
-    imports: FxHashMap<String, String>, // crate_name -> version
+    imports: FxHashMap<String, (String, Option<String>)>, // crate_name -> (version, registry)
     service_name: Once<Symbol>,
+    migration_fn: Option<Symbol>,
+    rpc_selectors: FxHashMap<String, u32>, // rpc_name -> selector accepted by the dispatcher
+    analysis_only: bool,
+    payable_rpcs: FxHashSet<Symbol>,
+    rpc_access: FxHashMap<Symbol, RpcAccess>,
     event_indexed_fields: FxHashMap<Symbol, Vec<Symbol>>, // event_name -> field_name
     iface: Once<oasis_rpc::Interface>,
 }
 
 impl BuildPlugin {
-    pub fn new(imports: impl IntoIterator<Item = (String, String)>) -> Self {
+    pub fn new(imports: impl IntoIterator<Item = (String, String, Option<String>)>) -> Self {
         Self {
-            imports: imports.into_iter().collect(),
+            imports: imports
+                .into_iter()
+                .map(|(name, version, registry)| (name, (version, registry)))
+                .collect(),
             service_name: Once::new(),
+            migration_fn: None,
+            rpc_selectors: Default::default(),
+            analysis_only: false,
+            payable_rpcs: Default::default(),
+            rpc_access: Default::default(),
             event_indexed_fields: Default::default(),
             iface: Once::new(),
         }
     }
 
+    /// Stops compilation once the interface has been generated. The selectors of the interface
+    /// are those that the dispatcher of a subsequent compilation should accept.
+    pub fn analysis_only(mut self) -> Self {
+        self.analysis_only = true;
+        self
+    }
+
+    /// Makes the dispatcher accept calls that identify RPCs by these selectors,
+    /// which are those of the interface generated by an analysis-only compilation.
+    pub fn with_rpc_selectors(
+        mut self,
+        rpc_selectors: impl IntoIterator<Item = (String, u32)>,
+    ) -> Self {
+        self.rpc_selectors = rpc_selectors.into_iter().collect();
+        self
+    }
+
     /// Returns the generated interface.
     /// Only valid after rustc callback has been executed. Panics if called before.
     pub fn try_get(&self) -> Option<&oasis_rpc::Interface> {
@@ -101,8 +134,11 @@ impl rustc_driver::Callbacks for BuildPlugin {
 
         let (rpcs_result, warnings) = parsed_rpc_collector.into_rpcs();
 
-        for warning in warnings {
-            sess.span_warn(warning.span(), &warning.to_string());
+        // The warnings are reported by the compilation that follows an analysis-only one.
+        if !self.analysis_only {
+            for warning in warnings {
+                sess.span_warn(warning.span(), &warning.to_string());
+            }
         }
 
         let rpcs = match rpcs_result {
@@ -130,6 +166,30 @@ impl rustc_driver::Callbacks for BuildPlugin {
             _ => ret_err!(), // Multiply defined `new` function. Let the compiler catch this.
         };
 
+        let (migrations, rpcs): (Vec<_>, Vec<_>) =
+            rpcs.into_iter().partition(|rpc| match rpc.kind {
+                ParsedRpcKind::Migrate(_) => true,
+                _ => false,
+            });
+        let migration = match migrations.as_slice() {
+            [] => None,
+            [migration] => Some(migration),
+            _ => {
+                sess.span_err(
+                    migrations
+                        .iter()
+                        .flat_map(|rpc| match rpc.kind {
+                            ParsedRpcKind::Migrate(migrate_span) => vec![migrate_span, rpc.span],
+                            _ => unreachable!(),
+                        })
+                        .collect::<Vec<_>>(),
+                    "Only one method can be marked with `#[migrate]`",
+                );
+                ret_err!();
+            }
+        };
+        self.migration_fn = migration.map(|rpc| rpc.name);
+
         let default_fn_spans = rpcs
             .iter()
             .filter_map(|rpc| {
@@ -151,13 +211,25 @@ impl rustc_driver::Callbacks for BuildPlugin {
             ret_err!();
         }
 
+        self.payable_rpcs = rpcs
+            .iter()
+            .filter(|rpc| rpc.payable)
+            .map(|rpc| rpc.name)
+            .collect();
+        self.rpc_access = rpcs
+            .iter()
+            .map(|rpc| (rpc.name, rpc.access.clone()))
+            .collect();
+
         crate::dispatcher_gen::generate_and_insert(
             &mut parse,
             &gen_dir,
             &crate_name,
             service_name,
             &ctor_sig,
+            migration,
             rpcs,
+            &self.rpc_selectors,
         );
 
         Compilation::Continue
@@ -177,7 +249,7 @@ impl rustc_driver::Callbacks for BuildPlugin {
             let mut rpc_collector = AnalyzedRpcCollector::new(krate, tcx, *service_name);
             krate.visit_all_item_likes(&mut rpc_collector);
 
-            let defined_types = rpc_collector.rpcs().iter().flat_map(|(_, decl, _)| {
+            let defined_types = rpc_collector.rpcs().iter().flat_map(|(_, decl, ..)| {
                 let mut def_ty_collector = DefinedTypeCollector::new(tcx);
                 def_ty_collector.visit_fn_decl(decl);
                 def_ty_collector.adt_defs()
@@ -204,8 +276,8 @@ impl rustc_driver::Callbacks for BuildPlugin {
                 } else {
                     let crate_name = tcx.original_crate_name(def.did.krate);
                     match self.imports.get(crate_name.as_str().get()) {
-                        Some(version) => {
-                            imports.insert((crate_name, version.to_string()));
+                        Some((version, registry)) => {
+                            imports.insert((crate_name, version.to_string(), registry.clone()));
                         }
                         None => {
                             let err_msg = format!(
@@ -219,12 +291,14 @@ impl rustc_driver::Callbacks for BuildPlugin {
                 }
             }
 
-            let iface = match crate::rpc::convert_interface(
+            let mut iface = match crate::rpc::convert_interface(
                 tcx,
                 *service_name,
+                rpc_collector.service_attrs(),
                 imports,
                 adt_defs,
                 &self.event_indexed_fields,
+                self.migration_fn,
                 rpc_collector.rpcs(),
             ) {
                 Ok(iface) => iface,
@@ -236,9 +310,33 @@ impl rustc_driver::Callbacks for BuildPlugin {
                 }
             };
 
+            // A selector is computed from the function's canonical signature, so it does not
+            // depend on how the service's source spells the types. Clients may use it only
+            // if the dispatcher, which was generated before the types were known, accepts it.
+            for func in iface.functions.iter_mut() {
+                let name = Symbol::intern(&func.name);
+                let selector = oasis_rpc::selector::function_selector(func);
+                func.selector = if self.analysis_only
+                    || self.rpc_selectors.get(&func.name) == Some(&selector)
+                {
+                    Some(selector)
+                } else {
+                    None
+                };
+                func.payable = self.payable_rpcs.contains(&name);
+                if let Some(access) = self.rpc_access.get(&name) {
+                    func.only = access.only.clone();
+                    func.when_not_paused = access.when_not_paused;
+                }
+            }
+
             self.iface.set(iface);
         });
 
-        Compilation::Continue
+        if self.analysis_only {
+            Compilation::Stop
+        } else {
+            Compilation::Continue
+        }
     }
 }
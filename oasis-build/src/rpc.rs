@@ -1,14 +1,16 @@
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 
 use rustc::{
-    hir::{self, def_id::DefId, Body, FnDecl},
+    hir::{self, def::CtorKind, def_id::DefId, Body, FnDecl},
     ty::{self, AdtDef, TyCtxt, TyS},
     util::nodemap::FxHashMap,
 };
+use syntax::ast;
 use syntax_pos::symbol::Symbol;
 
 use oasis_rpc::{
-    Constructor, Field, Function, Import, IndexedField, Interface, StateMutability, Type, TypeDef,
+    Constructor, Field, Function, Import, IndexedField, Interface, Migration, StateMutability,
+    Type, TypeDef, Variant,
 };
 
 use crate::error::UnsupportedTypeError;
@@ -17,20 +19,22 @@ use crate::error::UnsupportedTypeError;
 pub fn convert_interface(
     tcx: TyCtxt,
     name: Symbol,
+    service_attrs: &[ast::Attribute],
     // the following use BTreeSets to ensure idl is deterministic
-    imports: BTreeSet<(Symbol, String)>, // (name, version)
+    imports: BTreeSet<(Symbol, String, Option<String>)>, // (name, version, registry)
     adt_defs: BTreeSet<(&AdtDef, bool)>, // (adt_def, is_event)
     event_indices: &FxHashMap<Symbol, Vec<Symbol>>,
-    fns: &[(Symbol, &FnDecl, &Body)],
+    migration_fn: Option<Symbol>,
+    fns: &[(Symbol, &FnDecl, &Body, &[ast::Attribute])],
 ) -> Result<Interface, Vec<UnsupportedTypeError>> {
     let mut errs = Vec::new();
 
     let imports = imports
         .into_iter()
-        .map(|(name, version)| Import {
+        .map(|(name, version, registry)| Import {
             name: name.to_string(),
             version,
-            registry: None, // TODO
+            registry,
         })
         .collect();
 
@@ -41,6 +45,7 @@ pub fn convert_interface(
                 if let TypeDef::Event {
                     name,
                     ref mut fields,
+                    ..
                 } = &mut event_def
                 {
                     if let Some(indexed_fields) = event_indices.get(&Symbol::intern(name)) {
@@ -58,15 +63,31 @@ pub fn convert_interface(
     }
 
     let mut ctor = None;
+    let mut migration = None;
     let mut functions = Vec::with_capacity(fns.len());
-    for (name, decl, body) in fns.iter() {
+    for (name, decl, body, attrs) in fns.iter() {
         if name.as_str() == "new" {
-            match convert_state_ctor(tcx, decl, body) {
+            match convert_state_ctor(tcx, decl, body, attrs) {
                 Ok(constructor) => ctor = Some(constructor),
                 Err(mut errz) => errs.append(&mut errz),
             }
+        } else if Some(*name) == migration_fn {
+            match convert_state_ctor(tcx, decl, body, attrs) {
+                Ok(Constructor {
+                    inputs,
+                    error,
+                    docs,
+                }) => {
+                    migration = Some(Migration {
+                        inputs,
+                        error,
+                        docs,
+                    })
+                }
+                Err(mut errz) => errs.append(&mut errz),
+            }
         } else {
-            match convert_function(tcx, *name, decl, body) {
+            match convert_function(tcx, *name, decl, body, attrs) {
                 Ok(rpc_fn) => functions.push(rpc_fn),
                 Err(mut errz) => errs.append(&mut errz),
             }
@@ -83,8 +104,10 @@ pub fn convert_interface(
             imports,
             type_defs,
             constructor: ctor.unwrap(),
+            migration,
             functions,
             oasis_build_version: env!("CARGO_PKG_VERSION").to_string(),
+            docs: convert_docs(service_attrs),
         })
     }
 }
@@ -93,8 +116,10 @@ fn convert_state_ctor(
     tcx: TyCtxt,
     decl: &FnDecl,
     body: &Body,
+    attrs: &[ast::Attribute],
 ) -> Result<Constructor, Vec<UnsupportedTypeError>> {
     let mut errs = Vec::new();
+    let (docs, mut arg_docs) = split_arg_docs(convert_docs(attrs));
 
     let mut inputs = Vec::with_capacity(decl.inputs.len());
     for (arg, ty) in body
@@ -103,7 +128,7 @@ fn convert_state_ctor(
         .zip(decl.inputs.iter())
         .skip(1 /* skip ctx */)
     {
-        match convert_arg(tcx, &arg.pat, ty) {
+        match convert_arg(tcx, &arg.pat, ty, &mut arg_docs) {
             Ok(field) => inputs.push(field),
             Err(err) => errs.push(err),
         }
@@ -122,7 +147,11 @@ fn convert_state_ctor(
     if !errs.is_empty() {
         Err(errs)
     } else {
-        Ok(Constructor { inputs, error })
+        Ok(Constructor {
+            inputs,
+            error,
+            docs,
+        })
     }
 }
 
@@ -131,8 +160,10 @@ fn convert_function(
     name: Symbol,
     decl: &FnDecl,
     body: &Body,
+    attrs: &[ast::Attribute],
 ) -> Result<Function, Vec<UnsupportedTypeError>> {
     let mut errs = Vec::new();
+    let (docs, mut arg_docs) = split_arg_docs(convert_docs(attrs));
 
     let mutability = match decl.implicit_self {
         hir::ImplicitSelfKind::ImmRef => StateMutability::Immutable,
@@ -147,7 +178,7 @@ fn convert_function(
         .zip(decl.inputs.iter())
         .skip(2 /* skip self and ctx */)
     {
-        match convert_arg(tcx, &arg.pat, ty) {
+        match convert_arg(tcx, &arg.pat, ty, &mut arg_docs) {
             Ok(ty) => inputs.push(ty),
             Err(err) => errs.push(err),
         }
@@ -173,22 +204,127 @@ fn convert_function(
             mutability,
             inputs,
             output,
+            docs,
+            selector: None, // set once the dispatcher has been generated
+            // the following are set from the attributes parsed before expansion
+            payable: false,
+            only: Vec::new(),
+            when_not_paused: false,
         })
     }
 }
 
-fn convert_arg(tcx: TyCtxt, pat: &hir::Pat, ty: &hir::Ty) -> Result<Field, UnsupportedTypeError> {
+fn convert_arg(
+    tcx: TyCtxt,
+    pat: &hir::Pat,
+    ty: &hir::Ty,
+    arg_docs: &mut BTreeMap<String, String>,
+) -> Result<Field, UnsupportedTypeError> {
     use hir::PatKind;
+    let name = match pat.node {
+        PatKind::Wild => "_".to_string(),
+        PatKind::Binding(_, _, ident, _) => ident.name.as_str().get().to_string(),
+        _ => unreachable!("arg pattern must be wild or ident"),
+    };
     convert_ty(tcx, ty).map(|ty| Field {
-        name: match pat.node {
-            PatKind::Wild => "_".to_string(),
-            PatKind::Binding(_, _, ident, _) => ident.name.as_str().get().to_string(),
-            _ => unreachable!("arg pattern must be wild or ident"),
-        },
+        docs: arg_docs.remove(&name),
+        name,
         ty,
     })
 }
 
+/// Returns the contents of the doc comments in `attrs`, if there are any.
+fn convert_docs(attrs: &[ast::Attribute]) -> Option<String> {
+    let lines: Vec<String> = attrs
+        .iter()
+        .filter(|attr| attr.path == Symbol::intern("doc"))
+        .filter_map(|attr| attr.with_desugared_doc(|attr| attr.value_str()))
+        .flat_map(|doc| {
+            doc.as_str()
+                .lines()
+                .map(|line| {
+                    // Doc comments are conventionally separated from their markers by a space.
+                    let line = if line.starts_with(' ') { &line[1..] } else { line };
+                    line.trim_end().to_string()
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    let docs = lines.join("\n").trim_matches('\n').to_string();
+    if docs.is_empty() {
+        None
+    } else {
+        Some(docs)
+    }
+}
+
+/// Removes the `# Arguments` section from the docs of a function and returns
+/// the remaining docs along with the docs of each argument.
+/// Arguments are documented as in the API guidelines, i.e. `` * `name` - description ``.
+fn split_arg_docs(docs: Option<String>) -> (Option<String>, BTreeMap<String, String>) {
+    let mut arg_docs = BTreeMap::new();
+    let docs = match docs {
+        Some(docs) => docs,
+        None => return (None, arg_docs),
+    };
+    let mut lines = Vec::new();
+    let mut in_args_section = false;
+    let mut in_code_block = false;
+    let mut cur_arg: Option<String> = None;
+    for line in docs.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") {
+            in_code_block = !in_code_block;
+        }
+        if trimmed.starts_with('#') && !in_code_block {
+            in_args_section = trimmed.trim_start_matches('#').trim() == "Arguments";
+            cur_arg = None;
+            if in_args_section {
+                continue;
+            }
+        }
+        if !in_args_section {
+            lines.push(line);
+            continue;
+        }
+        let item = if trimmed.starts_with("* ") || trimmed.starts_with("- ") {
+            Some(&trimmed[2..])
+        } else {
+            None
+        };
+        match item.and_then(parse_arg_item) {
+            Some((name, desc)) => {
+                arg_docs.insert(name.to_string(), desc.to_string());
+                cur_arg = Some(name.to_string());
+            }
+            None if trimmed.is_empty() => cur_arg = None,
+            None => {
+                // A continuation of the previous argument's description.
+                if let Some(desc) = cur_arg.as_ref().and_then(|arg| arg_docs.get_mut(arg)) {
+                    desc.push(' ');
+                    desc.push_str(trimmed);
+                }
+            }
+        }
+    }
+    let docs = lines.join("\n").trim_matches('\n').to_string();
+    (if docs.is_empty() { None } else { Some(docs) }, arg_docs)
+}
+
+/// Parses `` `name` - description `` or `` `name`: description ``.
+fn parse_arg_item(item: &str) -> Option<(&str, &str)> {
+    if !item.starts_with('`') {
+        return None;
+    }
+    let name_end = item[1..].find('`')? + 1;
+    let name = &item[1..name_end];
+    let desc = item[name_end + 1..]
+        .trim_start()
+        .trim_start_matches(|ch| ch == '-' || ch == ':')
+        .trim();
+    Some((name, desc))
+}
+
 // this is a macro because it's difficult to convince rustc that `T` \in {`Ty`, `TyS`}`
 macro_rules! convert_def {
     ($tcx:ident, $did:expr, $owner_did:expr, $arg_at:expr) => {{
@@ -213,6 +349,12 @@ macro_rules! convert_def {
                 Type::Set(box $arg_at(0)?)
             } else if ty_str == "Address" {
                 Type::Address
+            } else if ty_str == "U128" {
+                Type::U128
+            } else if ty_str == "I128" {
+                Type::I128
+            } else if ty_str == "U256" {
+                Type::U256
             } else {
                 // this branch includes `sync`, among other things
                 return Err(UnsupportedTypeError::NotReprC(
@@ -364,7 +506,8 @@ fn convert_int(
         IntTy::I16 => Type::I16,
         IntTy::I32 => Type::I32,
         IntTy::I64 => Type::I64,
-        IntTy::I128 | IntTy::Isize => {
+        IntTy::I128 => return Err(UnsupportedTypeError::Int128("i128", span.into())),
+        IntTy::Isize => {
             return Err(UnsupportedTypeError::NotReprC(ty.to_string(), span.into()))
         }
     })
@@ -380,7 +523,8 @@ fn convert_uint(
         UintTy::U16 => Type::U16,
         UintTy::U32 => Type::U32,
         UintTy::U64 => Type::U64,
-        UintTy::U128 | UintTy::Usize => {
+        UintTy::U128 => return Err(UnsupportedTypeError::Int128("u128", span.into())),
+        UintTy::Usize => {
             return Err(UnsupportedTypeError::NotReprC(ty.to_string(), span.into()))
         }
     })
@@ -410,16 +554,44 @@ fn convert_type_def(
         .unwrap()
         .data
         .to_string();
+    let docs = convert_docs(&tcx.get_attrs(def.did));
     if def.is_enum() {
-        if !def.is_payloadfree() {
-            // TODO: convert Rust struct enum to tagged union
-            return Err(UnsupportedTypeError::ComplexEnum(
-                tcx.def_span(def.did).into(),
-            ));
-        }
+        let variants = def
+            .variants
+            .iter()
+            .map(|v| {
+                let name = v.ident.to_string();
+                Ok(match v.ctor_kind {
+                    CtorKind::Const => Variant::Unit(name),
+                    CtorKind::Fn => Variant::Tuple {
+                        name,
+                        items: v
+                            .fields
+                            .iter()
+                            .map(|f| convert_sty(tcx, f.did, tcx.type_of(f.did)))
+                            .collect::<Result<Vec<_>, _>>()?,
+                    },
+                    CtorKind::Fictive => Variant::Struct {
+                        name,
+                        fields: v
+                            .fields
+                            .iter()
+                            .map(|f| {
+                                Ok(Field {
+                                    name: f.ident.to_string(),
+                                    ty: convert_sty(tcx, f.did, tcx.type_of(f.did))?,
+                                    docs: convert_docs(&tcx.get_attrs(f.did)),
+                                })
+                            })
+                            .collect::<Result<Vec<_>, _>>()?,
+                    },
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
         Ok(TypeDef::Enum {
             name: ty_name,
-            variants: def.variants.iter().map(|v| v.ident.to_string()).collect(),
+            variants,
+            docs,
         })
     } else if def.is_struct() {
         let fields = def
@@ -428,6 +600,7 @@ fn convert_type_def(
                 Ok((
                     f.ident.to_string(),
                     convert_sty(tcx, f.did, tcx.type_of(f.did))?,
+                    convert_docs(&tcx.get_attrs(f.did)),
                 ))
             })
             .collect::<Result<Vec<_>, _>>()?;
@@ -436,20 +609,23 @@ fn convert_type_def(
                 name: ty_name,
                 fields: fields
                     .into_iter()
-                    .map(|(name, ty)| IndexedField {
+                    .map(|(name, ty, docs)| IndexedField {
                         name,
                         ty,
                         indexed: false,
+                        docs,
                     })
                     .collect(),
+                docs,
             }
         } else {
             TypeDef::Struct {
                 name: ty_name,
                 fields: fields
                     .into_iter()
-                    .map(|(name, ty)| Field { name, ty })
+                    .map(|(name, ty, docs)| Field { name, ty, docs })
                     .collect(),
+                docs,
             }
         })
     } else if def.is_union() {
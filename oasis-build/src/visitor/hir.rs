@@ -3,16 +3,25 @@ use rustc::{
     ty::{self, AdtDef, TyCtxt, TyS},
     util::nodemap::{FxHashMap, HirIdSet},
 };
-use syntax::source_map::Span;
+use syntax::{ast, source_map::Span};
 use syntax_pos::symbol::Symbol;
 
+/// The name, signature, body, and attributes of an RPC method.
+pub type Rpc<'a, 'tcx> = (
+    Symbol,
+    &'tcx hir::FnDecl,
+    &'a hir::Body,
+    &'tcx [ast::Attribute],
+);
+
 /// Collects public functions defined in `impl #service_name`.
 pub struct AnalyzedRpcCollector<'a, 'tcx> {
     krate: &'a Crate,
     tcx: TyCtxt<'tcx>,
     service_name: Symbol,
     rpc_impls: HirIdSet,
-    rpcs: Vec<(Symbol, &'tcx hir::FnDecl, &'a hir::Body)>, // the collected RPC fns
+    service_attrs: &'tcx [ast::Attribute], // the attributes of the service struct
+    rpcs: Vec<Rpc<'a, 'tcx>>,              // the collected RPC fns
 }
 
 impl<'a, 'tcx> AnalyzedRpcCollector<'a, 'tcx> {
@@ -22,17 +31,27 @@ impl<'a, 'tcx> AnalyzedRpcCollector<'a, 'tcx> {
             tcx,
             service_name,
             rpc_impls: HirIdSet::default(),
+            service_attrs: &[],
             rpcs: Vec::new(),
         }
     }
 
-    pub fn rpcs(&self) -> &[(Symbol, &'tcx hir::FnDecl, &'a hir::Body)] {
+    pub fn service_attrs(&self) -> &'tcx [ast::Attribute] {
+        self.service_attrs
+    }
+
+    pub fn rpcs(&self) -> &[Rpc<'a, 'tcx>] {
         self.rpcs.as_slice()
     }
 }
 
 impl<'a, 'tcx> hir::itemlikevisit::ItemLikeVisitor<'tcx> for AnalyzedRpcCollector<'a, 'tcx> {
     fn visit_item(&mut self, item: &'tcx hir::Item) {
+        if let hir::ItemKind::Struct(..) = &item.node {
+            if item.ident.name == self.service_name {
+                self.service_attrs = &item.attrs;
+            }
+        }
         if let hir::ItemKind::Impl(_, _, _, _, None /* `trait_ref` */, ty, _) = &item.node {
             if let hir::TyKind::Path(hir::QPath::Resolved(_, path)) = &ty.node {
                 if path.segments.last().unwrap().ident.name == self.service_name {
@@ -50,7 +69,8 @@ impl<'a, 'tcx> hir::itemlikevisit::ItemLikeVisitor<'tcx> for AnalyzedRpcCollecto
                     .contains(&self.tcx.hir().get_parent_item(impl_item.hir_id))
             {
                 let body = self.krate.body(*body_id);
-                self.rpcs.push((impl_item.ident.name, &decl, body));
+                self.rpcs
+                    .push((impl_item.ident.name, &decl, body, &impl_item.attrs));
             }
         }
     }
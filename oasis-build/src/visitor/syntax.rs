@@ -95,12 +95,24 @@ pub struct ParsedRpc {
     pub sig: ast::MethodSig,
     pub kind: ParsedRpcKind,
     pub span: Span,
+    /// Whether the RPC is marked `#[payable]` and so accepts value.
+    pub payable: bool,
+    pub access: RpcAccess,
+}
+
+/// The access-control attributes of an RPC.
+#[derive(Clone, Default)]
+pub struct RpcAccess {
+    /// The principals permitted by `#[only(...)]`. Anyone may call the RPC if this is empty.
+    pub only: Vec<oasis_rpc::Principal>,
+    pub when_not_paused: bool,
 }
 
 #[derive(PartialEq, Eq)]
 pub enum ParsedRpcKind {
     Ctor,
     Default(Span),
+    Migrate(Span),
     Normal,
 }
 
@@ -196,10 +208,33 @@ fn check_parsed_rpc(
 
     let is_ctor = impl_item.ident.name == Symbol::intern("new");
 
+    let find_attr_span = |path: &[&str]| {
+        impl_item.attrs.iter().find_map(|attr| {
+            if crate::utils::path_ends_with(&attr.path, path) {
+                Some(attr.span)
+            } else {
+                None
+            }
+        })
+    };
+    let default_span = find_attr_span(&["oasis_std", "default"]);
+    let migrate_span = find_attr_span(&["oasis_std", "migrate"]);
+    let payable_span = find_attr_span(&["oasis_std", "payable"]);
+    let when_not_paused_span = find_attr_span(&["oasis_std", "when_not_paused"]);
+    let only_attr = impl_item
+        .attrs
+        .iter()
+        .find(|attr| crate::utils::path_ends_with(&attr.path, &["oasis_std", "only"]));
+
     match impl_item.vis.node {
         ast::VisibilityKind::Public => (),
         _ if is_ctor => (),
-        _ => return Ok(None),
+        _ => {
+            return match migrate_span {
+                Some(migrate_span) => Err(vec![RpcError::MigrationNotPub(migrate_span)]),
+                None => Ok(None),
+            };
+        }
     }
 
     let msig = match &impl_item.node {
@@ -232,17 +267,52 @@ fn check_parsed_rpc(
         }
     }
 
-    let default_span = impl_item.attrs.iter().find_map(|attr| {
-        if crate::utils::path_ends_with(&attr.path, &["oasis_std", "default"]) {
-            Some(attr.span)
-        } else {
-            None
+    if let Some(payable_span) = payable_span {
+        if is_ctor || migrate_span.is_some() {
+            errors.push(RpcError::PayableNotRpc(payable_span));
+        }
+    }
+
+    let mut access = RpcAccess {
+        only: Vec::new(),
+        when_not_paused: when_not_paused_span.is_some(),
+    };
+    if let Some(only_attr) = only_attr {
+        match parse_only(only_attr) {
+            Some(only) => access.only = only,
+            None => errors.push(RpcError::BadOnly(only_attr.span)),
         }
-    });
+    }
+    if is_ctor || migrate_span.is_some() {
+        if let Some(span) = only_attr.map(|attr| attr.span).or(when_not_paused_span) {
+            errors.push(RpcError::AccessNotRpc(span));
+        }
+    }
 
-    let mut args = msig.decl.inputs.iter();
+    let mut args = msig.decl.inputs.iter().peekable();
 
-    if !is_ctor {
+    if let Some(migrate_span) = migrate_span {
+        if is_ctor {
+            errors.push(RpcError::CtorIsMigration(migrate_span));
+        }
+        if default_span.is_some() {
+            errors.push(RpcError::DefaultIsMigration(migrate_span));
+        }
+        // Like the constructor, the migration function creates the service state.
+        if let Some(arg) = args.peek() {
+            if crate::utils::is_self_ref(&arg.ty) || arg.is_self() {
+                errors.push(RpcError::MigrationHasSelf(arg.pat.span));
+                args.next();
+            }
+        }
+        match args.next() {
+            Some(arg) if !crate::utils::is_context_ref(&arg.ty) => errors.push(
+                RpcError::MigrationMissingContext(arg.ty.span.to(arg.pat.span)),
+            ),
+            None => errors.push(RpcError::MigrationMissingContext(impl_item.ident.span)),
+            _ => (),
+        }
+    } else if !is_ctor {
         match args.next() {
             Some(arg) if !crate::utils::is_self_ref(&arg.ty) => {
                 errors.push(RpcError::MissingSelf(arg.pat.span.to(arg.pat.span)))
@@ -251,18 +321,20 @@ fn check_parsed_rpc(
             _ => (),
         }
     }
-    match args.next() {
-        Some(arg) if !crate::utils::is_context_ref(&arg.ty) => {
-            errors.push(RpcError::MissingContext {
+    if migrate_span.is_none() {
+        match args.next() {
+            Some(arg) if !crate::utils::is_context_ref(&arg.ty) => {
+                errors.push(RpcError::MissingContext {
+                    from_ctor: is_ctor,
+                    span: arg.ty.span.to(arg.pat.span),
+                })
+            }
+            None => errors.push(RpcError::MissingContext {
                 from_ctor: is_ctor,
-                span: arg.ty.span.to(arg.pat.span),
-            })
+                span: impl_item.ident.span,
+            }),
+            _ => (),
         }
-        None => errors.push(RpcError::MissingContext {
-            from_ctor: is_ctor,
-            span: impl_item.ident.span,
-        }),
-        _ => (),
     }
 
     if let Some(default_span) = default_span {
@@ -294,7 +366,7 @@ fn check_parsed_rpc(
 
     let ret_ty = crate::utils::unpack_syntax_ret(&msig.decl.output);
 
-    if is_ctor {
+    if is_ctor || migrate_span.is_some() {
         let mut ret_ty_is_self = false;
         if let crate::utils::ReturnType::Known(ast::Ty {
             node: ast::TyKind::Path(_, path),
@@ -305,9 +377,12 @@ fn check_parsed_rpc(
                 path.segments.len() == 1 && path.segments[0].ident.name == Symbol::intern("Self");
         }
         if !ret_ty_is_self {
-            errors.push(RpcError::BadCtorReturn {
-                self_ty: service_ty.clone().into_inner(),
-                span: msig.decl.output.span(),
+            let self_ty = service_ty.clone().into_inner();
+            let span = msig.decl.output.span();
+            errors.push(if is_ctor {
+                RpcError::BadCtorReturn { self_ty, span }
+            } else {
+                RpcError::BadMigrationReturn { self_ty, span }
             });
         }
     }
@@ -318,18 +393,49 @@ fn check_parsed_rpc(
             sig: msig.clone(),
             kind: if is_ctor {
                 ParsedRpcKind::Ctor
+            } else if let Some(migrate_span) = migrate_span {
+                ParsedRpcKind::Migrate(migrate_span)
             } else if let Some(default_span) = default_span {
                 ParsedRpcKind::Default(default_span)
             } else {
                 ParsedRpcKind::Normal
             },
             span: impl_item.ident.span,
+            payable: payable_span.is_some(),
+            access,
         }))
     } else {
         Err(errors)
     }
 }
 
+/// Parses `#[only(owner, role = "name", ...)]` into the principals that it permits.
+fn parse_only(attr: &ast::Attribute) -> Option<Vec<oasis_rpc::Principal>> {
+    let metas = match attr.meta()?.node {
+        ast::MetaItemKind::List(metas) => metas,
+        _ => return None,
+    };
+    let mut only = Vec::with_capacity(metas.len());
+    for nested_meta in metas.iter() {
+        let meta = nested_meta.meta_item()?;
+        let principal = if meta.path == Symbol::intern("owner") && meta.is_word() {
+            oasis_rpc::Principal::Owner
+        } else if meta.path == Symbol::intern("role") {
+            oasis_rpc::Principal::Role(meta.value_str()?.to_string())
+        } else {
+            return None;
+        };
+        if !only.contains(&principal) {
+            only.push(principal);
+        }
+    }
+    if only.is_empty() {
+        None
+    } else {
+        Some(only)
+    }
+}
+
 #[derive(Default)]
 struct PrintlnFinder {
     pub println_spans: Vec<Span>,
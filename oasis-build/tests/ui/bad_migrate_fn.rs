@@ -0,0 +1,19 @@
+use oasis_std::{Context, Service};
+
+#[derive(Service)]
+pub struct MyService;
+
+impl MyService {
+    pub fn new(_ctx: &Context) -> Self {
+        Self
+    }
+
+    #[oasis_std::migrate]
+    pub fn migrate(&mut self, _ctx: &Context) -> Self {
+        Self
+    }
+}
+
+fn main() {
+    oasis_std::service!(MyService);
+}
@@ -0,0 +1,17 @@
+use oasis_std::{Context, Service};
+
+#[derive(Service)]
+pub struct MyService;
+
+impl MyService {
+    pub fn new(_ctx: &Context) -> Self {
+        Self
+    }
+
+    #[oasis_std::only(admin)]
+    pub fn restricted(&mut self, _ctx: &Context) {}
+}
+
+fn main() {
+    oasis_std::service!(MyService);
+}
@@ -0,0 +1,18 @@
+use oasis_std::{Context, Service};
+
+#[derive(Service)]
+pub struct MyService;
+
+impl MyService {
+    #[oasis_std::payable]
+    pub fn new(_ctx: &Context) -> Self {
+        Self
+    }
+
+    #[oasis_std::payable]
+    pub fn deposit(&mut self, _ctx: &Context) {}
+}
+
+fn main() {
+    oasis_std::service!(MyService);
+}
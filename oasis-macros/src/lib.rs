@@ -13,4 +13,8 @@ use syn::spanned::Spanned as _;
 include!("utils.rs");
 include!("default_attr.rs");
 include!("event_derive.rs");
+include!("migrate_attr.rs");
+include!("only_attr.rs");
+include!("payable_attr.rs");
 include!("service_derive.rs");
+include!("when_not_paused_attr.rs");
@@ -0,0 +1,607 @@
+//! Utilities for working with Oasis interface definitions.
+//! Usage: `oasis-rpc <subcommand>`. Run `oasis-rpc help` for the list of subcommands.
+
+use std::path::Path;
+
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+use oasis_rpc::Interface;
+
+fn main() {
+    let matches = App::new("oasis-rpc")
+        .about("Utilities for working with Oasis interface definitions")
+        .version(env!("CARGO_PKG_VERSION"))
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .subcommand(
+            SubCommand::with_name("compat")
+                .about(
+                    "Reports the changes between two versions of an interface and checks \
+                     that the new version number is large enough to publish them",
+                )
+                .arg(
+                    Arg::with_name("OLD")
+                        .help("The previous interface (.json or .idl) or service (.wasm)")
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("NEW")
+                        .help("The new interface (.json or .idl) or service (.wasm)")
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("encode")
+                .about("Encodes the input of an RPC or, if no method is given, of the constructor")
+                .arg(
+                    Arg::with_name("INTERFACE")
+                        .help("The interface (.json or .idl) or service (.wasm)")
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("ARGS")
+                        .help("The JSON-encoded arguments")
+                        .multiple(true),
+                )
+                .arg(
+                    Arg::with_name("method")
+                        .short("m")
+                        .long("method")
+                        .takes_value(true)
+                        .help("The RPC to call"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("decode")
+                .about("Decodes the output of an RPC or the data of an event as JSON")
+                .arg(
+                    Arg::with_name("INTERFACE")
+                        .help("The interface (.json or .idl) or service (.wasm)")
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("DATA")
+                        .help("The hex-encoded output or event data")
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("method")
+                        .short("m")
+                        .long("method")
+                        .takes_value(true)
+                        .required_unless("event")
+                        .help("The RPC that produced the output"),
+                )
+                .arg(
+                    Arg::with_name("error")
+                        .long("error")
+                        .requires("method")
+                        .help("Decodes the output as the error of the RPC"),
+                )
+                .arg(
+                    Arg::with_name("event")
+                        .long("event")
+                        .takes_value(true)
+                        .conflicts_with("method")
+                        .help("The name of the event that produced the data"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("idl")
+                .about("Prints an interface in the textual IDL format or, with --json, as JSON")
+                .arg(
+                    Arg::with_name("INTERFACE")
+                        .help("The interface (.json or .idl) or service (.wasm)")
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("json")
+                        .long("json")
+                        .help("Prints the interface as JSON"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("inspect")
+                .about(
+                    "Summarizes the interface, build version, and size of a service \
+                     or edits its interface section",
+                )
+                .setting(AppSettings::SubcommandsNegateReqs)
+                .arg(
+                    Arg::with_name("WASM")
+                        .help("The service (.wasm)")
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("json")
+                        .long("json")
+                        .help("Prints only the interface, as JSON"),
+                )
+                .arg(
+                    Arg::with_name("idl")
+                        .long("idl")
+                        .conflicts_with("json")
+                        .help("Prints only the interface, in the textual IDL format"),
+                )
+                .subcommand(
+                    SubCommand::with_name("extract")
+                        .about("Writes the interface of a service to a file")
+                        .arg(
+                            Arg::with_name("WASM")
+                                .help("The service (.wasm)")
+                                .required(true),
+                        )
+                        .arg(
+                            Arg::with_name("OUT")
+                                .help("The output file (.json or .idl)")
+                                .required(true),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("replace")
+                        .about("Replaces the interface section of a service")
+                        .arg(
+                            Arg::with_name("WASM")
+                                .help("The service (.wasm)")
+                                .required(true),
+                        )
+                        .arg(
+                            Arg::with_name("INTERFACE")
+                                .help("The new interface (.json or .idl) or service (.wasm)")
+                                .required(true),
+                        )
+                        .arg(out_arg()),
+                )
+                .subcommand(
+                    SubCommand::with_name("strip")
+                        .about("Removes the interface section of a service")
+                        .arg(
+                            Arg::with_name("WASM")
+                                .help("The service (.wasm)")
+                                .required(true),
+                        )
+                        .arg(out_arg()),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("registry")
+                .about("Runs or uses a service registry")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(
+                    SubCommand::with_name("serve")
+                        .about("Serves the services stored in a directory")
+                        .arg(
+                            Arg::with_name("DIR")
+                                .help("The directory in which services are stored")
+                                .required(true),
+                        )
+                        .arg(
+                            Arg::with_name("addr")
+                                .long("addr")
+                                .takes_value(true)
+                                .default_value("127.0.0.1:8000")
+                                .help("The address on which to listen"),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("publish")
+                        .about("Publishes a service")
+                        .arg(registry_arg())
+                        .arg(
+                            Arg::with_name("WASM")
+                                .help("The service (.wasm)")
+                                .required(true),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("versions")
+                        .about("Lists the published versions of a service")
+                        .arg(registry_arg())
+                        .arg(
+                            Arg::with_name("NAME")
+                                .help("The name of the service")
+                                .required(true),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("fetch")
+                        .about("Downloads the greatest version of a service that matches --version")
+                        .arg(registry_arg())
+                        .arg(
+                            Arg::with_name("NAME")
+                                .help("The name of the service")
+                                .required(true),
+                        )
+                        .arg(
+                            Arg::with_name("OUT")
+                                .help("The file to which the service is written")
+                                .required(true),
+                        )
+                        .arg(
+                            Arg::with_name("version")
+                                .long("version")
+                                .takes_value(true)
+                                .default_value("*")
+                                .help("The semver range of versions to consider"),
+                        ),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("lock")
+                .about("Manages the lockfile that records the services imported by a crate")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(
+                    SubCommand::with_name("update")
+                        .about("Resolves the imports of a crate anew and records them")
+                        .arg(manifest_path_arg()),
+                )
+                .subcommand(
+                    SubCommand::with_name("verify")
+                        .about(
+                            "Checks that the lockfile of a crate matches the services it imports",
+                        )
+                        .arg(manifest_path_arg()),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("gen")
+                .about(
+                    "Generates the types and client of a service in another language \
+                     or its documentation",
+                )
+                .arg(
+                    Arg::with_name("LANG")
+                        .help("The language of the generated code")
+                        .possible_values(&[
+                            "html",
+                            "json-schema",
+                            "markdown",
+                            "python",
+                            "typescript",
+                        ])
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("INTERFACE")
+                        .help("The interface (.json or .idl) or service (.wasm)")
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("out")
+                        .short("o")
+                        .long("out")
+                        .takes_value(true)
+                        .help(
+                            "The file to which the generated code is written. Defaults to stdout.",
+                        ),
+                ),
+        )
+        .get_matches();
+
+    let result = match matches.subcommand() {
+        ("compat", Some(matches)) => compat(matches),
+        ("encode", Some(matches)) => encode(matches),
+        ("decode", Some(matches)) => decode(matches),
+        ("idl", Some(matches)) => idl(matches),
+        ("inspect", Some(matches)) => inspect(matches),
+        ("registry", Some(matches)) => registry(matches),
+        ("lock", Some(matches)) => lock(matches),
+        ("gen", Some(matches)) => gen(matches),
+        _ => unreachable!("Subcommand is required"),
+    };
+
+    if let Err(err) = result {
+        eprintln!("error: {}", err);
+        std::process::exit(1);
+    }
+}
+
+fn compat(matches: &ArgMatches) -> Result<(), failure::Error> {
+    let old = load_interface(matches.value_of("OLD").unwrap())?;
+    let new = load_interface(matches.value_of("NEW").unwrap())?;
+    let report = oasis_rpc::compat::check(&old, &new)?;
+
+    for change in report.changes.iter() {
+        let severity = if change.is_breaking() {
+            "breaking"
+        } else {
+            "compatible"
+        };
+        println!("{}: {}", severity, change);
+    }
+    println!("required version bump: {}", report.required_bump);
+
+    if !report.version_ok {
+        return Err(failure::format_err!(
+            "version {} of `{}` does not publish a {} change from version {}",
+            new.version,
+            new.name,
+            report.required_bump,
+            old.version
+        ));
+    }
+    Ok(())
+}
+
+fn encode(matches: &ArgMatches) -> Result<(), failure::Error> {
+    let iface = load_interface(matches.value_of("INTERFACE").unwrap())?;
+    let args = matches
+        .values_of("ARGS")
+        .unwrap_or_default()
+        .map(serde_json::from_str)
+        .collect::<Result<Vec<serde_json::Value>, _>>()?;
+    let codec = oasis_rpc::codec::Codec::new(&iface);
+    let input = match matches.value_of("method") {
+        Some(method) => codec.encode_call(method, &args)?,
+        None => codec.encode_ctor(&args)?,
+    };
+    println!("{}", hex::encode(input));
+    Ok(())
+}
+
+fn decode(matches: &ArgMatches) -> Result<(), failure::Error> {
+    let iface = load_interface(matches.value_of("INTERFACE").unwrap())?;
+    let data = hex::decode(matches.value_of("DATA").unwrap().trim_start_matches("0x"))?;
+    let codec = oasis_rpc::codec::Codec::new(&iface);
+    let value = match (matches.value_of("method"), matches.value_of("event")) {
+        (Some(method), _) if matches.is_present("error") => codec.decode_error(method, &data)?,
+        (Some(method), _) => codec.decode_output(method, &data)?,
+        (None, Some(event)) => codec.decode_event(event, &data)?,
+        (None, None) => unreachable!("clap requires a method or event"),
+    };
+    println!("{}", serde_json::to_string_pretty(&value)?);
+    Ok(())
+}
+
+fn idl(matches: &ArgMatches) -> Result<(), failure::Error> {
+    let iface = load_interface(matches.value_of("INTERFACE").unwrap())?;
+    if matches.is_present("json") {
+        println!("{}", iface.to_string()?);
+    } else {
+        print!("{}", oasis_rpc::idl::print(&iface));
+    }
+    Ok(())
+}
+
+fn gen(matches: &ArgMatches) -> Result<(), failure::Error> {
+    let iface = load_interface(matches.value_of("INTERFACE").unwrap())?;
+    let code = match matches.value_of("LANG").unwrap() {
+        "json-schema" => {
+            serde_json::to_string_pretty(&oasis_rpc::gen::json_schema::generate(&iface))? + "\n"
+        }
+        "html" => oasis_rpc::gen::docs::html(&iface),
+        "markdown" => oasis_rpc::gen::docs::markdown(&iface),
+        "python" => oasis_rpc::gen::python::generate(&iface)?,
+        "typescript" => oasis_rpc::gen::typescript::generate(&iface),
+        _ => unreachable!("clap checks possible values"),
+    };
+    match matches.value_of("out") {
+        Some(out_path) => std::fs::write(out_path, code)?,
+        None => print!("{}", code),
+    }
+    Ok(())
+}
+
+fn inspect(matches: &ArgMatches) -> Result<(), failure::Error> {
+    match matches.subcommand() {
+        ("extract", Some(matches)) => return inspect_extract(matches),
+        ("replace", Some(matches)) => return inspect_replace(matches),
+        ("strip", Some(matches)) => return inspect_strip(matches),
+        _ => (),
+    }
+    let wasm_path = matches.value_of("WASM").unwrap();
+    let wasm = read_wasm(wasm_path)?;
+    let iface = oasis_rpc::wasm::interface(&wasm)?;
+    if matches.is_present("json") || matches.is_present("idl") {
+        let iface = iface.ok_or_else(|| no_interface(wasm_path))?;
+        if matches.is_present("json") {
+            println!("{}", iface.to_string()?);
+        } else {
+            print!("{}", oasis_rpc::idl::print(&iface));
+        }
+        return Ok(());
+    }
+
+    match &iface {
+        Some(iface) => {
+            println!(
+                "service:     {} v{} ({})",
+                iface.name, iface.version, iface.namespace
+            );
+            println!("oasis-build: {}", iface.oasis_build_version);
+            println!(
+                "interface:   {} imports, {} types, {} functions",
+                iface.imports.len(),
+                iface.type_defs.len(),
+                iface.functions.len()
+            );
+        }
+        None => println!("service:     (no interface section)"),
+    }
+    println!("sections:");
+    for section in oasis_rpc::wasm::sections(&wasm)? {
+        let note = if section.name == Some(oasis_rpc::wasm::INTERFACE_SECTION) {
+            // The interface is stored deflated, so the JSON is usually much larger.
+            let json_len = iface
+                .as_ref()
+                .map(|iface| iface.to_string().map(|s| s.len()));
+            format!(" ({} bytes of JSON)", json_len.unwrap_or(Ok(0))?)
+        } else if section.is_custom() {
+            " (custom)".to_string()
+        } else {
+            String::new()
+        };
+        println!(
+            "  {:<20} {:>10} bytes{}",
+            section.kind(),
+            section.encoded_len,
+            note
+        );
+    }
+    println!("  {:<20} {:>10} bytes", "total", wasm.len());
+    Ok(())
+}
+
+fn inspect_extract(matches: &ArgMatches) -> Result<(), failure::Error> {
+    let wasm_path = matches.value_of("WASM").unwrap();
+    let iface = oasis_rpc::wasm::interface(&read_wasm(wasm_path)?)?
+        .ok_or_else(|| no_interface(wasm_path))?;
+    let out_path = Path::new(matches.value_of("OUT").unwrap());
+    let contents = if out_path.extension() == Some("idl".as_ref()) {
+        oasis_rpc::idl::print(&iface)
+    } else {
+        iface.to_string()? + "\n"
+    };
+    std::fs::write(out_path, contents)?;
+    Ok(())
+}
+
+fn inspect_replace(matches: &ArgMatches) -> Result<(), failure::Error> {
+    let wasm_path = matches.value_of("WASM").unwrap();
+    let iface = load_interface(matches.value_of("INTERFACE").unwrap())?;
+    let replaced = oasis_rpc::wasm::replace_interface(&read_wasm(wasm_path)?, &iface)?;
+    std::fs::write(matches.value_of("out").unwrap_or(wasm_path), replaced)?;
+    Ok(())
+}
+
+fn inspect_strip(matches: &ArgMatches) -> Result<(), failure::Error> {
+    let wasm_path = matches.value_of("WASM").unwrap();
+    let stripped = oasis_rpc::wasm::strip_interface(&read_wasm(wasm_path)?)?;
+    std::fs::write(matches.value_of("out").unwrap_or(wasm_path), stripped)?;
+    Ok(())
+}
+
+fn registry(matches: &ArgMatches) -> Result<(), failure::Error> {
+    use oasis_rpc::registry::{Client, Server};
+
+    let client = |matches: &ArgMatches| -> Result<Client, failure::Error> {
+        Ok(Client::new(&url::Url::parse(
+            matches.value_of("REGISTRY").unwrap(),
+        )?)?)
+    };
+    match matches.subcommand() {
+        ("serve", Some(matches)) => {
+            let listener = std::net::TcpListener::bind(matches.value_of("addr").unwrap())?;
+            eprintln!(
+                "Serving `{}` at registry://{}",
+                matches.value_of("DIR").unwrap(),
+                listener.local_addr()?
+            );
+            Server::new(matches.value_of("DIR").unwrap()).serve(listener)?;
+        }
+        ("publish", Some(matches)) => {
+            let iface = client(matches)?.publish(&read_wasm(matches.value_of("WASM").unwrap())?)?;
+            println!("published {} v{}", iface.name, iface.version);
+        }
+        ("versions", Some(matches)) => {
+            let published = client(matches)?.versions(matches.value_of("NAME").unwrap())?;
+            for version in published.versions {
+                println!("{}", version);
+            }
+        }
+        ("fetch", Some(matches)) => {
+            let mut url = url::Url::parse(matches.value_of("REGISTRY").unwrap())?;
+            url.query_pairs_mut()
+                .append_pair("version", matches.value_of("version").unwrap());
+            let service = oasis_rpc::import::Importer::for_url(url.as_str(), Default::default())?
+                .import(matches.value_of("NAME").unwrap())?;
+            std::fs::write(matches.value_of("OUT").unwrap(), service.bytecode)?;
+            println!(
+                "fetched {} v{}",
+                service.interface.name, service.interface.version
+            );
+        }
+        _ => unreachable!("Subcommand is required"),
+    }
+    Ok(())
+}
+
+fn lock(matches: &ArgMatches) -> Result<(), failure::Error> {
+    use oasis_rpc::import::{Lockfile, Resolver, LOCKFILE_NAME};
+
+    let (subcommand, matches) = match matches.subcommand() {
+        (subcommand, Some(matches)) => (subcommand, matches),
+        _ => unreachable!("Subcommand is required"),
+    };
+    let manifest_path = Path::new(matches.value_of("manifest-path").unwrap());
+    let crate_dir = manifest_path.parent().unwrap_or_else(|| Path::new(""));
+    let lock_path = crate_dir.join(LOCKFILE_NAME);
+    let deps = oasis_rpc::import::manifest_deps(manifest_path)?;
+    let resolver = Resolver::new(deps.into_iter().collect(), crate_dir.to_path_buf());
+    match subcommand {
+        "update" => {
+            let (services, lockfile) = resolver.resolve_locked()?;
+            lockfile.save(&lock_path)?;
+            for service in services {
+                println!(
+                    "locked {} v{}",
+                    service.interface.name, service.interface.version
+                );
+            }
+        }
+        "verify" => {
+            let lockfile = Lockfile::load(&lock_path)?
+                .ok_or_else(|| failure::format_err!("`{}` does not exist", lock_path.display()))?;
+            let (_, resolved) = resolver.with_lockfile(lockfile.clone()).resolve_locked()?;
+            if resolved != lockfile {
+                return Err(failure::format_err!(
+                    "`{}` is out of date. Run `oasis-rpc lock update`.",
+                    lock_path.display()
+                ));
+            }
+            println!("`{}` is up to date", lock_path.display());
+        }
+        _ => unreachable!("Subcommand is required"),
+    }
+    Ok(())
+}
+
+fn manifest_path_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("manifest-path")
+        .long("manifest-path")
+        .takes_value(true)
+        .default_value("Cargo.toml")
+        .help("The manifest of the crate that imports the services")
+}
+
+fn registry_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("REGISTRY")
+        .help("The URL of the registry (e.g., registry://localhost:8000)")
+        .required(true)
+}
+
+fn out_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("out")
+        .short("o")
+        .long("out")
+        .takes_value(true)
+        .help("The file to which the service is written. Defaults to overwriting WASM.")
+}
+
+fn read_wasm(path: &str) -> Result<Vec<u8>, failure::Error> {
+    std::fs::read(path).map_err(|err| failure::format_err!("Could not read `{}`: {}", path, err))
+}
+
+fn no_interface(wasm_path: &str) -> failure::Error {
+    failure::format_err!("`{}` has no interface section", wasm_path)
+}
+
+/// Loads an interface from either its JSON or IDL definition or the service that contains it.
+fn load_interface(path: &str) -> Result<Interface, failure::Error> {
+    let path = Path::new(path);
+    if path.extension() == Some("wasm".as_ref()) {
+        let url = format!("file://{}", path.canonicalize()?.display());
+        let base_dir = std::env::current_dir()?;
+        return oasis_rpc::import::Importer::for_url(&url, base_dir)?
+            .import_all()?
+            .pop()
+            .map(|service| service.interface)
+            .ok_or_else(|| failure::format_err!("`{}` contains no service", path.display()));
+    }
+    let bytes = std::fs::read(path)
+        .map_err(|err| failure::format_err!("Could not read `{}`: {}", path.display(), err))?;
+    if path.extension() == Some("idl".as_ref()) {
+        let src = String::from_utf8(bytes)?;
+        return oasis_rpc::idl::parse(&src)
+            .map_err(|err| failure::format_err!("{}:{}", path.display(), err));
+    }
+    Ok(serde_json::from_slice(&bytes)?)
+}
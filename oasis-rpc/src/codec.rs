@@ -0,0 +1,1149 @@
+//! Encodes calls to, and decodes outputs of, any service given only its `Interface`.
+//!
+//! Values are represented as JSON:
+//! * numbers, bools, and strings are themselves, except that 128-bit integers may also be
+//!   decimal strings and `u256` is a decimal (or `0x`-prefixed hex) string;
+//! * `bytes` and `address` are hex strings (with or without a `0x` prefix) or arrays of bytes;
+//! * structs and events are objects, tuple structs are arrays, and newtypes are their content;
+//! * unit enum variants are their name, and other variants are an object from the name of
+//!   the variant to its fields, which are represented like those of a struct;
+//! * tuples, arrays, lists, and sets are arrays, and the unit tuple is `null`;
+//! * maps are objects if the key is a `string` and arrays of `[key, value]` pairs otherwise;
+//! * optional values are `null` or the value, and results are `{"Ok": ..}` or `{"Err": ..}`.
+//!
+//! Decoding produces the same representation, with bytes as unprefixed hex strings and
+//! 128-bit integers as strings only when they do not fit in 64 bits.
+
+use std::{collections::BTreeMap, convert::TryFrom};
+
+use serde_cbor::Value as Cbor;
+use serde_json::Value;
+
+use crate::{Field, Interface, Type, TypeDef, Variant};
+
+/// Converts between JSON values and the CBOR read and written by a service's dispatcher.
+pub struct Codec<'a> {
+    iface: &'a Interface,
+    imports: BTreeMap<&'a str, &'a Interface>,
+}
+
+impl<'a> Codec<'a> {
+    pub fn new(iface: &'a Interface) -> Self {
+        Self {
+            iface,
+            imports: BTreeMap::new(),
+        }
+    }
+
+    /// Adds an imported interface, which is required to encode types that it defines.
+    pub fn with_import(mut self, import: &'a Interface) -> Self {
+        self.imports.insert(&import.namespace, import);
+        self
+    }
+
+    /// Returns the input that calls the RPC function `method` with `args`.
+    pub fn encode_call(&self, method: &str, args: &[Value]) -> Result<Vec<u8>, CodecError> {
+        let function = self.function(method)?;
+        let mut call = BTreeMap::new();
+        call.insert(
+            Cbor::Text("method".to_string()),
+            Cbor::Text(method.to_string()),
+        );
+        call.insert(
+            Cbor::Text("payload".to_string()),
+            self.encode_args(&function.inputs, args)?,
+        );
+        to_vec(&Cbor::Map(call))
+    }
+
+    /// Returns the input of the constructor when deploying the service.
+    pub fn encode_ctor(&self, args: &[Value]) -> Result<Vec<u8>, CodecError> {
+        to_vec(&self.encode_args(&self.iface.constructor.inputs, args)?)
+    }
+
+    /// Decodes the output of a successful call to `method`.
+    pub fn decode_output(&self, method: &str, output: &[u8]) -> Result<Value, CodecError> {
+        match &self.function(method)?.output {
+            Some(Type::Result(ok_ty, _)) => self.decode(ok_ty, &from_slice(output)?),
+            Some(ty) => self.decode(ty, &from_slice(output)?),
+            None => self.decode(&Type::Tuple(Vec::new()), &from_slice(output)?),
+        }
+    }
+
+    /// Decodes the error returned by a failed call to `method`.
+    pub fn decode_error(&self, method: &str, output: &[u8]) -> Result<Value, CodecError> {
+        match &self.function(method)?.output {
+            Some(Type::Result(_, err_ty)) => self.decode(err_ty, &from_slice(output)?),
+            _ => Err(CodecError::Infallible(method.to_string())),
+        }
+    }
+
+    /// Decodes the data of an event emitted by the service.
+    pub fn decode_event(&self, event_name: &str, data: &[u8]) -> Result<Value, CodecError> {
+        let ty = Type::Defined {
+            namespace: None,
+            ty: event_name.to_string(),
+        };
+        self.decode(&ty, &from_slice(data)?)
+    }
+
+    /// Converts `value` into the CBOR value that deserializes into `ty`.
+    pub fn encode(&self, ty: &Type, value: &Value) -> Result<Cbor, CodecError> {
+        self.encode_in(self.iface, ty, value, "value")
+    }
+
+    /// Converts a CBOR value serialized from `ty` into its JSON representation.
+    pub fn decode(&self, ty: &Type, cbor: &Cbor) -> Result<Value, CodecError> {
+        self.decode_in(self.iface, ty, cbor, "value")
+    }
+
+    fn function(&self, method: &str) -> Result<&'a crate::Function, CodecError> {
+        self.iface
+            .functions
+            .iter()
+            .find(|f| f.name == method)
+            .ok_or_else(|| CodecError::UnknownFunction(method.to_string()))
+    }
+
+    fn encode_args(&self, inputs: &[Field], args: &[Value]) -> Result<Cbor, CodecError> {
+        if inputs.len() != args.len() {
+            return Err(CodecError::ArgCount {
+                expected: inputs.len(),
+                found: args.len(),
+            });
+        }
+        Ok(Cbor::Array(
+            inputs
+                .iter()
+                .zip(args)
+                .map(|(inp, arg)| self.encode_in(self.iface, &inp.ty, arg, &inp.name))
+                .collect::<Result<_, _>>()?,
+        ))
+    }
+
+    /// Returns the interface that defines the type and the type definition.
+    /// `scope` is the interface in which the type is referenced.
+    fn resolve(
+        &self,
+        scope: &'a Interface,
+        namespace: &Option<String>,
+        ty: &str,
+    ) -> Result<(&'a Interface, &'a TypeDef), CodecError> {
+        let iface = match namespace {
+            Some(namespace) => self.imports.get(namespace.as_str()).copied(),
+            None => Some(scope),
+        };
+        iface
+            .and_then(|iface| {
+                iface
+                    .type_defs
+                    .iter()
+                    .find(|def| def.name() == ty)
+                    .map(|def| (iface, def))
+            })
+            .ok_or_else(|| {
+                CodecError::UnknownType(describe(&Type::Defined {
+                    namespace: namespace.clone(),
+                    ty: ty.to_string(),
+                }))
+            })
+    }
+
+    fn encode_in(
+        &self,
+        scope: &'a Interface,
+        ty: &Type,
+        value: &Value,
+        path: &str,
+    ) -> Result<Cbor, CodecError> {
+        let invalid = || CodecError::InvalidValue {
+            path: path.to_string(),
+            expected: describe(ty),
+        };
+        let elems = |value: &Value| value.as_array().cloned().ok_or_else(invalid);
+        let int = |min: i128, max: i128| {
+            value
+                .as_i64()
+                .map(i128::from)
+                .or_else(|| value.as_u64().map(i128::from))
+                .filter(|v| min <= *v && *v <= max)
+                .map(Cbor::Integer)
+                .ok_or_else(invalid)
+        };
+
+        Ok(match ty {
+            Type::Bool => Cbor::Bool(value.as_bool().ok_or_else(invalid)?),
+            Type::U8 => int(0, u8::max_value().into())?,
+            Type::I8 => int(i8::min_value().into(), i8::max_value().into())?,
+            Type::U16 => int(0, u16::max_value().into())?,
+            Type::I16 => int(i16::min_value().into(), i16::max_value().into())?,
+            Type::U32 => int(0, u32::max_value().into())?,
+            Type::I32 => int(i32::min_value().into(), i32::max_value().into())?,
+            Type::U64 => int(0, u64::max_value().into())?,
+            Type::I64 => int(i64::min_value().into(), i64::max_value().into())?,
+            // CBOR integers hold at most 64 bits of magnitude, so 128-bit integers are bytes.
+            Type::U128 => {
+                let n = match value {
+                    Value::String(s) => s.parse::<u128>().ok(),
+                    _ => value.as_u64().map(u128::from),
+                };
+                Cbor::Bytes(n.ok_or_else(invalid)?.to_be_bytes().to_vec())
+            }
+            Type::I128 => {
+                let n = match value {
+                    Value::String(s) => s.parse::<i128>().ok(),
+                    _ => value
+                        .as_i64()
+                        .map(i128::from)
+                        .or_else(|| value.as_u64().map(i128::from)),
+                };
+                Cbor::Bytes(n.ok_or_else(invalid)?.to_be_bytes().to_vec())
+            }
+            Type::U256 => {
+                let bytes = match value {
+                    Value::String(s) => parse_u256(s),
+                    Value::Number(n) => n.as_u64().map(|n| {
+                        let mut bytes = [0u8; 32];
+                        bytes[24..].copy_from_slice(&n.to_be_bytes());
+                        bytes
+                    }),
+                    _ => None,
+                };
+                Cbor::Bytes(bytes.ok_or_else(invalid)?.to_vec())
+            }
+            Type::F32 | Type::F64 => Cbor::Float(value.as_f64().ok_or_else(invalid)?),
+            Type::String => Cbor::Text(value.as_str().ok_or_else(invalid)?.to_string()),
+            Type::Bytes | Type::Address => {
+                let bytes = match value {
+                    Value::String(hex) => {
+                        hex::decode(hex.trim_start_matches("0x")).map_err(|_| invalid())?
+                    }
+                    Value::Array(elems) => elems
+                        .iter()
+                        .map(|b| b.as_u64().filter(|b| *b <= 0xff).map(|b| b as u8))
+                        .collect::<Option<_>>()
+                        .ok_or_else(invalid)?,
+                    _ => return Err(invalid()),
+                };
+                if *ty == Type::Address && bytes.len() != 20 {
+                    return Err(invalid());
+                }
+                Cbor::Array(bytes.into_iter().map(|b| Cbor::Integer(b.into())).collect())
+            }
+            Type::Defined { namespace, ty } => {
+                let (scope, def) = self.resolve(scope, namespace, ty)?;
+                match def {
+                    TypeDef::Enum { variants, .. } => {
+                        self.encode_variant(scope, variants, value, path)?
+                    }
+                    TypeDef::Struct { fields, .. } => self.encode_fields(
+                        scope,
+                        fields.iter().map(|f| (f.name.as_str(), &f.ty)).collect(),
+                        value,
+                        path,
+                    )?,
+                    TypeDef::Event { fields, .. } => self.encode_fields(
+                        scope,
+                        fields.iter().map(|f| (f.name.as_str(), &f.ty)).collect(),
+                        value,
+                        path,
+                    )?,
+                }
+            }
+            Type::Tuple(tys) if tys.is_empty() => match value {
+                Value::Null => Cbor::Null,
+                _ => return Err(invalid()),
+            },
+            Type::Tuple(tys) => {
+                let elems = elems(value)?;
+                if elems.len() != tys.len() {
+                    return Err(invalid());
+                }
+                self.encode_seq(scope, tys.iter().zip(elems.iter()), path)?
+            }
+            Type::Array(elem_ty, len) => {
+                let elems = elems(value)?;
+                if elems.len() as u64 != *len {
+                    return Err(invalid());
+                }
+                self.encode_seq(scope, std::iter::repeat(&**elem_ty).zip(elems.iter()), path)?
+            }
+            Type::List(elem_ty) | Type::Set(elem_ty) => {
+                let elems = elems(value)?;
+                self.encode_seq(scope, std::iter::repeat(&**elem_ty).zip(elems.iter()), path)?
+            }
+            Type::Map(k_ty, v_ty) => {
+                let entries: Vec<(Value, &Value)> = match value {
+                    Value::Object(obj) if **k_ty == Type::String => obj
+                        .iter()
+                        .map(|(k, v)| (Value::String(k.clone()), v))
+                        .collect(),
+                    Value::Object(obj) => obj
+                        .iter()
+                        .map(|(k, v)| Ok((serde_json::from_str(k).map_err(|_| invalid())?, v)))
+                        .collect::<Result<_, CodecError>>()?,
+                    Value::Array(pairs) => pairs
+                        .iter()
+                        .map(|pair| match pair.as_array().map(Vec::as_slice) {
+                            Some([k, v]) => Ok((k.clone(), v)),
+                            _ => Err(invalid()),
+                        })
+                        .collect::<Result<_, CodecError>>()?,
+                    _ => return Err(invalid()),
+                };
+                let mut map = BTreeMap::new();
+                for (k, v) in entries.iter() {
+                    let entry_path = format!("{}[{}]", path, k);
+                    map.insert(
+                        self.encode_in(scope, k_ty, k, &entry_path)?,
+                        self.encode_in(scope, v_ty, v, &entry_path)?,
+                    );
+                }
+                Cbor::Map(map)
+            }
+            Type::Optional(ty) => match value {
+                Value::Null => Cbor::Null,
+                _ => self.encode_in(scope, ty, value, path)?,
+            },
+            Type::Result(ok_ty, err_ty) => {
+                let (variant, ty, inner) = match value.as_object() {
+                    Some(obj) if obj.len() == 1 => match obj.iter().next().unwrap() {
+                        (variant, inner) if variant == "Ok" => (variant, ok_ty, inner),
+                        (variant, inner) if variant == "Err" => (variant, err_ty, inner),
+                        _ => return Err(invalid()),
+                    },
+                    _ => return Err(invalid()),
+                };
+                let mut map = BTreeMap::new();
+                map.insert(
+                    Cbor::Text(variant.clone()),
+                    self.encode_in(scope, ty, inner, &format!("{}.{}", path, variant))?,
+                );
+                Cbor::Map(map)
+            }
+        })
+    }
+
+    fn encode_seq<'t>(
+        &self,
+        scope: &'a Interface,
+        elems: impl Iterator<Item = (&'t Type, &'t Value)>,
+        path: &str,
+    ) -> Result<Cbor, CodecError> {
+        Ok(Cbor::Array(
+            elems
+                .enumerate()
+                .map(|(i, (ty, elem))| self.encode_in(scope, ty, elem, &format!("{}[{}]", path, i)))
+                .collect::<Result<_, _>>()?,
+        ))
+    }
+
+    fn encode_fields(
+        &self,
+        scope: &'a Interface,
+        fields: Vec<(&str, &Type)>,
+        value: &Value,
+        path: &str,
+    ) -> Result<Cbor, CodecError> {
+        let invalid = |expected: &str| CodecError::InvalidValue {
+            path: path.to_string(),
+            expected: expected.to_string(),
+        };
+        if !crate::is_tuple_struct(fields.iter().map(|(name, _)| *name)) {
+            let obj = value.as_object().ok_or_else(|| invalid("an object"))?;
+            if let Some(unknown) = obj
+                .keys()
+                .find(|k| fields.iter().all(|(name, _)| name != k))
+            {
+                return Err(invalid(&format!("no field named `{}`", unknown)));
+            }
+            let mut map = BTreeMap::new();
+            for (name, ty) in fields {
+                let field_path = format!("{}.{}", path, name);
+                let field = obj.get(name).ok_or_else(|| CodecError::InvalidValue {
+                    path: field_path.clone(),
+                    expected: describe(ty),
+                })?;
+                map.insert(
+                    Cbor::Text(name.to_string()),
+                    self.encode_in(scope, ty, field, &field_path)?,
+                );
+            }
+            return Ok(Cbor::Map(map));
+        }
+        if let [(_, ty)] = fields.as_slice() {
+            return self.encode_in(scope, ty, value, path); // newtypes are transparent
+        }
+        match value.as_array() {
+            Some(elems) if elems.len() == fields.len() => self.encode_seq(
+                scope,
+                fields.iter().map(|(_, ty)| *ty).zip(elems.iter()),
+                path,
+            ),
+            _ => Err(invalid(&format!("an array of length {}", fields.len()))),
+        }
+    }
+
+    /// Encodes a value of an enum, which is either the name of a unit variant or an object
+    /// containing only the fields of a tuple or struct variant keyed by the variant's name.
+    fn encode_variant(
+        &self,
+        scope: &'a Interface,
+        variants: &[Variant],
+        value: &Value,
+        path: &str,
+    ) -> Result<Cbor, CodecError> {
+        let invalid = |expected: String| CodecError::InvalidValue {
+            path: path.to_string(),
+            expected,
+        };
+        let (name, fields) = match value {
+            Value::String(name) => (name, None),
+            Value::Object(obj) if obj.len() == 1 => {
+                let (name, fields) = obj.iter().next().unwrap();
+                (name, Some(fields))
+            }
+            _ => return Err(invalid(describe_variants(variants))),
+        };
+        let variant = variants
+            .iter()
+            .find(|v| v.name() == name)
+            .ok_or_else(|| invalid(describe_variants(variants)))?;
+        let fields_path = format!("{}.{}", path, name);
+        let encoded_fields = match (variant, fields) {
+            (Variant::Unit(_), None) => return Ok(Cbor::Text(name.clone())),
+            (Variant::Tuple { items, .. }, Some(Value::Array(elems)))
+                if items.is_empty() && elems.is_empty() =>
+            {
+                Cbor::Array(Vec::new())
+            }
+            (Variant::Tuple { items, .. }, Some(fields)) if !items.is_empty() => {
+                let names: Vec<_> = (0..items.len()).map(|i| i.to_string()).collect();
+                let fields_sig = names.iter().map(String::as_str).zip(items.iter()).collect();
+                self.encode_fields(scope, fields_sig, fields, &fields_path)?
+            }
+            (
+                Variant::Struct {
+                    fields: variant_fields,
+                    ..
+                },
+                Some(fields),
+            ) => {
+                let fields_sig = variant_fields
+                    .iter()
+                    .map(|f| (f.name.as_str(), &f.ty))
+                    .collect();
+                self.encode_fields(scope, fields_sig, fields, &fields_path)?
+            }
+            _ => return Err(invalid(describe_variant(variant))),
+        };
+        let mut map = BTreeMap::new();
+        map.insert(Cbor::Text(name.clone()), encoded_fields);
+        Ok(Cbor::Map(map))
+    }
+
+    fn decode_in(
+        &self,
+        scope: &'a Interface,
+        ty: &Type,
+        cbor: &Cbor,
+        path: &str,
+    ) -> Result<Value, CodecError> {
+        let invalid = || CodecError::InvalidValue {
+            path: path.to_string(),
+            expected: describe(ty),
+        };
+        let elems = |cbor: &'_ Cbor| match cbor {
+            Cbor::Array(elems) => Ok(elems.clone()),
+            _ => Err(invalid()),
+        };
+        let int = |min: i128, max: i128| match cbor {
+            Cbor::Integer(v) if min <= *v && *v <= max => Ok(if *v < 0 {
+                Value::from(*v as i64)
+            } else {
+                Value::from(*v as u64)
+            }),
+            _ => Err(invalid()),
+        };
+        Ok(match ty {
+            Type::Bool => match cbor {
+                Cbor::Bool(b) => Value::Bool(*b),
+                _ => return Err(invalid()),
+            },
+            Type::U8 => int(0, u8::max_value().into())?,
+            Type::I8 => int(i8::min_value().into(), i8::max_value().into())?,
+            Type::U16 => int(0, u16::max_value().into())?,
+            Type::I16 => int(i16::min_value().into(), i16::max_value().into())?,
+            Type::U32 => int(0, u32::max_value().into())?,
+            Type::I32 => int(i32::min_value().into(), i32::max_value().into())?,
+            Type::U64 => int(0, u64::max_value().into())?,
+            Type::I64 => int(i64::min_value().into(), i64::max_value().into())?,
+            Type::U128 | Type::I128 => match cbor {
+                Cbor::Bytes(bytes) if bytes.len() == 16 => {
+                    let mut be_bytes = [0u8; 16];
+                    be_bytes.copy_from_slice(bytes);
+                    if *ty == Type::U128 {
+                        let n = u128::from_be_bytes(be_bytes);
+                        u64::try_from(n)
+                            .map(Value::from)
+                            .unwrap_or_else(|_| Value::String(n.to_string()))
+                    } else {
+                        let n = i128::from_be_bytes(be_bytes);
+                        i64::try_from(n)
+                            .map(Value::from)
+                            .or_else(|_| u64::try_from(n).map(Value::from))
+                            .unwrap_or_else(|_| Value::String(n.to_string()))
+                    }
+                }
+                _ => return Err(invalid()),
+            },
+            Type::U256 => match cbor {
+                Cbor::Bytes(bytes) if bytes.len() <= 32 => Value::String(u256_to_string(bytes)),
+                _ => return Err(invalid()),
+            },
+            Type::F32 | Type::F64 => match cbor {
+                Cbor::Float(f) => serde_json::Number::from_f64(*f)
+                    .map(Value::Number)
+                    .ok_or_else(invalid)?,
+                _ => return Err(invalid()),
+            },
+            Type::String => match cbor {
+                Cbor::Text(s) => Value::String(s.clone()),
+                _ => return Err(invalid()),
+            },
+            Type::Bytes | Type::Address => {
+                let bytes = match cbor {
+                    Cbor::Bytes(bytes) => bytes.clone(),
+                    Cbor::Array(elems) => elems
+                        .iter()
+                        .map(|b| match b {
+                            Cbor::Integer(b) if 0 <= *b && *b <= 0xff => Some(*b as u8),
+                            _ => None,
+                        })
+                        .collect::<Option<_>>()
+                        .ok_or_else(invalid)?,
+                    _ => return Err(invalid()),
+                };
+                if *ty == Type::Address && bytes.len() != 20 {
+                    return Err(invalid());
+                }
+                Value::String(hex::encode(bytes))
+            }
+            Type::Defined { namespace, ty } => {
+                let (scope, def) = self.resolve(scope, namespace, ty)?;
+                match def {
+                    TypeDef::Enum { variants, .. } => {
+                        self.decode_variant(scope, variants, cbor, path)?
+                    }
+                    TypeDef::Struct { fields, .. } => self.decode_fields(
+                        scope,
+                        fields.iter().map(|f| (f.name.as_str(), &f.ty)).collect(),
+                        cbor,
+                        path,
+                    )?,
+                    TypeDef::Event { fields, .. } => self.decode_fields(
+                        scope,
+                        fields.iter().map(|f| (f.name.as_str(), &f.ty)).collect(),
+                        cbor,
+                        path,
+                    )?,
+                }
+            }
+            Type::Tuple(tys) if tys.is_empty() => match cbor {
+                Cbor::Null => Value::Null,
+                Cbor::Array(elems) if elems.is_empty() => Value::Null,
+                _ => return Err(invalid()),
+            },
+            Type::Tuple(tys) => {
+                let elems = elems(cbor)?;
+                if elems.len() != tys.len() {
+                    return Err(invalid());
+                }
+                self.decode_seq(scope, tys.iter().zip(elems.iter()), path)?
+            }
+            Type::Array(elem_ty, len) => {
+                let elems = elems(cbor)?;
+                if elems.len() as u64 != *len {
+                    return Err(invalid());
+                }
+                self.decode_seq(scope, std::iter::repeat(&**elem_ty).zip(elems.iter()), path)?
+            }
+            Type::List(elem_ty) | Type::Set(elem_ty) => {
+                let elems = elems(cbor)?;
+                self.decode_seq(scope, std::iter::repeat(&**elem_ty).zip(elems.iter()), path)?
+            }
+            Type::Map(k_ty, v_ty) => {
+                let map = match cbor {
+                    Cbor::Map(map) => map,
+                    _ => return Err(invalid()),
+                };
+                let mut entries = Vec::with_capacity(map.len());
+                for (k, v) in map.iter() {
+                    let k = self.decode_in(scope, k_ty, k, path)?;
+                    let entry_path = format!("{}[{}]", path, k);
+                    entries.push((k, self.decode_in(scope, v_ty, v, &entry_path)?));
+                }
+                if **k_ty == Type::String {
+                    Value::Object(
+                        entries
+                            .into_iter()
+                            .map(|(k, v)| match k {
+                                Value::String(k) => (k, v),
+                                _ => unreachable!("key was decoded as a string"),
+                            })
+                            .collect(),
+                    )
+                } else {
+                    Value::Array(
+                        entries
+                            .into_iter()
+                            .map(|(k, v)| Value::Array(vec![k, v]))
+                            .collect(),
+                    )
+                }
+            }
+            Type::Optional(ty) => match cbor {
+                Cbor::Null => Value::Null,
+                _ => self.decode_in(scope, ty, cbor, path)?,
+            },
+            Type::Result(ok_ty, err_ty) => {
+                let map = match cbor {
+                    Cbor::Map(map) if map.len() == 1 => map,
+                    _ => return Err(invalid()),
+                };
+                let (variant, ty, inner) = match map.iter().next().unwrap() {
+                    (Cbor::Text(variant), inner) if variant == "Ok" => (variant, ok_ty, inner),
+                    (Cbor::Text(variant), inner) if variant == "Err" => (variant, err_ty, inner),
+                    _ => return Err(invalid()),
+                };
+                let mut obj = serde_json::Map::new();
+                obj.insert(
+                    variant.clone(),
+                    self.decode_in(scope, ty, inner, &format!("{}.{}", path, variant))?,
+                );
+                Value::Object(obj)
+            }
+        })
+    }
+
+    fn decode_seq<'t>(
+        &self,
+        scope: &'a Interface,
+        elems: impl Iterator<Item = (&'t Type, &'t Cbor)>,
+        path: &str,
+    ) -> Result<Value, CodecError> {
+        Ok(Value::Array(
+            elems
+                .enumerate()
+                .map(|(i, (ty, elem))| self.decode_in(scope, ty, elem, &format!("{}[{}]", path, i)))
+                .collect::<Result<_, _>>()?,
+        ))
+    }
+
+    fn decode_fields(
+        &self,
+        scope: &'a Interface,
+        fields: Vec<(&str, &Type)>,
+        cbor: &Cbor,
+        path: &str,
+    ) -> Result<Value, CodecError> {
+        let invalid = |expected: &str| CodecError::InvalidValue {
+            path: path.to_string(),
+            expected: expected.to_string(),
+        };
+        if !crate::is_tuple_struct(fields.iter().map(|(name, _)| *name)) {
+            let map = match cbor {
+                Cbor::Map(map) => map,
+                Cbor::Null if fields.is_empty() => return Ok(Value::Object(Default::default())),
+                _ => return Err(invalid("a map")),
+            };
+            let mut obj = serde_json::Map::new();
+            for (name, ty) in fields {
+                let field_path = format!("{}.{}", path, name);
+                let field = map.get(&Cbor::Text(name.to_string())).ok_or_else(|| {
+                    CodecError::InvalidValue {
+                        path: field_path.clone(),
+                        expected: describe(ty),
+                    }
+                })?;
+                obj.insert(
+                    name.to_string(),
+                    self.decode_in(scope, ty, field, &field_path)?,
+                );
+            }
+            return Ok(Value::Object(obj));
+        }
+        if let [(_, ty)] = fields.as_slice() {
+            return self.decode_in(scope, ty, cbor, path);
+        }
+        match cbor {
+            Cbor::Array(elems) if elems.len() == fields.len() => self.decode_seq(
+                scope,
+                fields.iter().map(|(_, ty)| *ty).zip(elems.iter()),
+                path,
+            ),
+            _ => Err(invalid(&format!("an array of length {}", fields.len()))),
+        }
+    }
+    fn decode_variant(
+        &self,
+        scope: &'a Interface,
+        variants: &[Variant],
+        cbor: &Cbor,
+        path: &str,
+    ) -> Result<Value, CodecError> {
+        let invalid = |expected: String| CodecError::InvalidValue {
+            path: path.to_string(),
+            expected,
+        };
+        let (name, fields) = match cbor {
+            Cbor::Text(name) => (name, None),
+            Cbor::Map(map) if map.len() == 1 => match map.iter().next().unwrap() {
+                (Cbor::Text(name), fields) => (name, Some(fields)),
+                _ => return Err(invalid(describe_variants(variants))),
+            },
+            _ => return Err(invalid(describe_variants(variants))),
+        };
+        let variant = variants
+            .iter()
+            .find(|v| v.name() == name)
+            .ok_or_else(|| invalid(describe_variants(variants)))?;
+        let fields_path = format!("{}.{}", path, name);
+        let decoded_fields = match (variant, fields) {
+            (Variant::Unit(_), None) => return Ok(Value::String(name.clone())),
+            (Variant::Tuple { items, .. }, Some(Cbor::Array(elems)))
+                if items.is_empty() && elems.is_empty() =>
+            {
+                Value::Array(Vec::new())
+            }
+            (Variant::Tuple { items, .. }, Some(fields)) if !items.is_empty() => {
+                let names: Vec<_> = (0..items.len()).map(|i| i.to_string()).collect();
+                let fields_sig = names.iter().map(String::as_str).zip(items.iter()).collect();
+                self.decode_fields(scope, fields_sig, fields, &fields_path)?
+            }
+            (
+                Variant::Struct {
+                    fields: variant_fields,
+                    ..
+                },
+                Some(fields),
+            ) => {
+                let fields_sig = variant_fields
+                    .iter()
+                    .map(|f| (f.name.as_str(), &f.ty))
+                    .collect();
+                self.decode_fields(scope, fields_sig, fields, &fields_path)?
+            }
+            _ => return Err(invalid(describe_variant(variant))),
+        };
+        let mut obj = serde_json::Map::new();
+        obj.insert(name.clone(), decoded_fields);
+        Ok(Value::Object(obj))
+    }
+}
+
+#[derive(Debug, failure::Fail)]
+pub enum CodecError {
+    #[fail(display = "Unknown function `{}`", _0)]
+    UnknownFunction(String),
+
+    #[fail(display = "Unknown type {}", _0)]
+    UnknownType(String),
+
+    #[fail(display = "Function `{}` does not return errors", _0)]
+    Infallible(String),
+
+    #[fail(display = "Expected {} arguments but found {}", expected, found)]
+    ArgCount { expected: usize, found: usize },
+
+    #[fail(display = "Invalid `{}`: expected {}", path, expected)]
+    InvalidValue { path: String, expected: String },
+
+    #[fail(display = "Invalid CBOR: {}", _0)]
+    Cbor(#[fail(cause)] serde_cbor::Error),
+}
+
+fn to_vec(cbor: &Cbor) -> Result<Vec<u8>, CodecError> {
+    serde_cbor::to_vec(cbor).map_err(CodecError::Cbor)
+}
+
+fn from_slice(bytes: &[u8]) -> Result<Cbor, CodecError> {
+    serde_cbor::from_slice(bytes).map_err(CodecError::Cbor)
+}
+
+fn describe_variants(variants: &[Variant]) -> String {
+    let names: Vec<_> = variants.iter().map(|v| format!("`{}`", v.name())).collect();
+    format!("one of the variants {}", names.join(", "))
+}
+
+fn describe_variant(variant: &Variant) -> String {
+    match variant {
+        Variant::Unit(name) => format!("`\"{}\"`", name),
+        Variant::Tuple { name, items } => {
+            format!("`{{\"{}\": ..}}` with {} items", name, items.len())
+        }
+        Variant::Struct { name, .. } => format!("`{{\"{}\": {{..}}}}`", name),
+    }
+}
+
+/// Parses a decimal or `0x`-prefixed hexadecimal number into 32 big-endian bytes.
+fn parse_u256(s: &str) -> Option<[u8; 32]> {
+    let (digits, radix) = if s.starts_with("0x") {
+        (&s[2..], 16)
+    } else {
+        (s, 10)
+    };
+    if digits.is_empty() {
+        return None;
+    }
+    let mut bytes = [0u8; 32];
+    for ch in digits.chars() {
+        let mut carry = ch.to_digit(radix)?;
+        for byte in bytes.iter_mut().rev() {
+            let v = u32::from(*byte) * radix + carry;
+            *byte = v as u8;
+            carry = v >> 8;
+        }
+        if carry != 0 {
+            return None; // overflow
+        }
+    }
+    Some(bytes)
+}
+
+/// Formats big-endian bytes as a decimal number.
+fn u256_to_string(bytes: &[u8]) -> String {
+    let mut bytes = bytes.to_vec();
+    let mut digits = Vec::new();
+    while bytes.iter().any(|b| *b != 0) {
+        let mut rem = 0u32;
+        for byte in bytes.iter_mut() {
+            let v = rem << 8 | u32::from(*byte);
+            *byte = (v / 10) as u8;
+            rem = v % 10;
+        }
+        digits.push(b'0' + rem as u8);
+    }
+    if digits.is_empty() {
+        digits.push(b'0');
+    }
+    digits.reverse();
+    String::from_utf8(digits).unwrap()
+}
+
+/// Describes the JSON representation of `ty` for use in error messages.
+fn describe(ty: &Type) -> String {
+    match ty {
+        Type::Bool => "a bool".to_string(),
+        Type::U8 => "a u8".to_string(),
+        Type::I8 => "an i8".to_string(),
+        Type::U16 => "a u16".to_string(),
+        Type::I16 => "an i16".to_string(),
+        Type::U32 => "a u32".to_string(),
+        Type::I32 => "an i32".to_string(),
+        Type::U64 => "a u64".to_string(),
+        Type::I64 => "an i64".to_string(),
+        Type::U128 => "a u128".to_string(),
+        Type::I128 => "an i128".to_string(),
+        Type::U256 => "a decimal or 0x-prefixed hex string of a u256".to_string(),
+        Type::F32 | Type::F64 => "a number".to_string(),
+        Type::Bytes => "hex-encoded bytes".to_string(),
+        Type::String => "a string".to_string(),
+        Type::Address => "a hex-encoded 20-byte address".to_string(),
+        Type::Defined { namespace, ty } => match namespace {
+            Some(namespace) => format!("`{}::{}`", namespace, ty),
+            None => format!("`{}`", ty),
+        },
+        Type::Tuple(tys) if tys.is_empty() => "null".to_string(),
+        Type::Tuple(tys) => format!("an array of length {}", tys.len()),
+        Type::Array(_, len) => format!("an array of length {}", len),
+        Type::List(_) | Type::Set(_) => "an array".to_string(),
+        Type::Map(k_ty, _) if **k_ty == Type::String => "an object".to_string(),
+        Type::Map(..) => "an array of [key, value] pairs".to_string(),
+        Type::Optional(ty) => format!("null or {}", describe(ty)),
+        Type::Result(..) => "`{\"Ok\": ..}` or `{\"Err\": ..}`".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::{BTreeMap, BTreeSet};
+
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+
+    // Mirrors the types of `tests/idl-gen/res/TestService.json` as they would be declared in a
+    // service so that the codec can be checked against `serde_cbor`.
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    enum InnerTy {
+        Field1,
+        Field2,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct DefTy {
+        f1: Option<i64>,
+        f2: Vec<Option<DefTy>>,
+        f3: BTreeMap<String, InnerTy>,
+        f4: ([u8; 20], u64, [u8; 20]),
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct TupleStruct(String, String, i32);
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    enum Error {
+        InsufficientFunds { address: [u8; 20] },
+        Rejected(String),
+        Clamped(u64, Option<InnerTy>),
+        Unknown,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    #[serde(tag = "method", content = "payload")]
+    #[allow(non_camel_case_types)]
+    enum RpcPayload {
+        the(Vec<DefTy>, Vec<u8>),
+        it(BTreeMap<bool, [u32; 12]>, BTreeSet<i64>),
+        void(),
+        mint(serde_cbor::Value, serde_cbor::Value),
+    }
+
+    fn def_ty() -> DefTy {
+        DefTy {
+            f1: Some(-1),
+            f2: vec![
+                None,
+                Some(DefTy {
+                    f1: None,
+                    f2: Vec::new(),
+                    f3: BTreeMap::new(),
+                    f4: ([0; 20], 0, [0; 20]),
+                }),
+            ],
+            f3: vec![("a".to_string(), InnerTy::Field2)]
+                .into_iter()
+                .collect(),
+            f4: ([1; 20], u64::max_value(), [0xab; 20]),
+        }
+    }
+
+    fn def_ty_json() -> Value {
+        json!({
+            "f1": -1,
+            "f2": [
+                null,
+                { "f1": null, "f2": [], "f3": {}, "f4": ["00".repeat(20), 0, "00".repeat(20)] },
+            ],
+            "f3": { "a": "Field2" },
+            "f4": ["01".repeat(20), u64::max_value(), "ab".repeat(20)],
+        })
+    }
+
+    #[test]
+    fn test_encode_call() {
+        let iface = crate::testing::test_service();
+        let codec = Codec::new(&iface);
+
+        let input = codec
+            .encode_call("the", &[json!([def_ty_json()]), json!("0xff00")])
+            .unwrap();
+        assert_eq!(
+            serde_cbor::from_slice::<RpcPayload>(&input).unwrap(),
+            RpcPayload::the(vec![def_ty()], vec![0xff, 0x00])
+        );
+
+        let input = codec
+            .encode_call("it", &[json!([[true, vec![7; 12]]]), json!([-3])])
+            .unwrap();
+        assert_eq!(
+            serde_cbor::from_slice::<RpcPayload>(&input).unwrap(),
+            RpcPayload::it(
+                vec![(true, [7; 12])].into_iter().collect(),
+                vec![-3].into_iter().collect()
+            )
+        );
+
+        let input = codec.encode_call("void", &[]).unwrap();
+        assert_eq!(
+            serde_cbor::from_slice::<RpcPayload>(&input).unwrap(),
+            RpcPayload::void()
+        );
+
+        let input = codec
+            .encode_call(
+                "mint",
+                &[json!(u128::max_value().to_string()), json!("0x0102")],
+            )
+            .unwrap();
+        let mut cap = vec![0; 32];
+        cap[30..].copy_from_slice(&[1, 2]);
+        assert_eq!(
+            serde_cbor::from_slice::<RpcPayload>(&input).unwrap(),
+            RpcPayload::mint(
+                serde_cbor::Value::Bytes(vec![0xff; 16]),
+                serde_cbor::Value::Bytes(cap)
+            )
+        );
+
+        let ctor_input = codec.encode_ctor(&[json!(["a", "b", 1])]).unwrap();
+        assert_eq!(
+            serde_cbor::from_slice::<(TupleStruct,)>(&ctor_input).unwrap(),
+            (TupleStruct("a".to_string(), "b".to_string(), 1),)
+        );
+    }
+
+    #[test]
+    fn test_decode() {
+        let iface = crate::testing::test_service();
+        let codec = Codec::new(&iface);
+
+        let output = serde_cbor::to_vec(&vec![[1u8; 20]]).unwrap();
+        assert_eq!(
+            codec.decode_output("the", &output).unwrap(),
+            json!(["01".repeat(20)])
+        );
+        let error = serde_cbor::to_vec(&vec![("k", "v")].into_iter().collect::<BTreeMap<_, _>>());
+        assert_eq!(
+            codec.decode_error("it", &error.unwrap()).unwrap(),
+            json!({ "k": "v" })
+        );
+        assert_eq!(
+            codec
+                .decode_output("void", &serde_cbor::to_vec(&()).unwrap())
+                .unwrap(),
+            Value::Null
+        );
+        let i128_output =
+            |n: i128| serde_cbor::to_vec(&serde_cbor::Value::Bytes(n.to_be_bytes().to_vec()));
+        assert_eq!(
+            codec
+                .decode_output("mint", &i128_output(-(1 << 64)).unwrap())
+                .unwrap(),
+            json!("-18446744073709551616")
+        );
+        assert_eq!(
+            codec
+                .decode_output("mint", &i128_output(1 << 64).unwrap())
+                .unwrap(),
+            json!("18446744073709551616")
+        );
+        assert_eq!(
+            codec
+                .decode_output("mint", &i128_output(-1).unwrap())
+                .unwrap(),
+            json!(-1)
+        );
+        let u128_ty = Type::U128;
+        for n in &[
+            json!(0),
+            json!(u64::max_value()),
+            json!((1u128 << 64).to_string()),
+        ] {
+            let cbor = codec.encode(&u128_ty, n).unwrap();
+            assert_eq!(&codec.decode(&u128_ty, &cbor).unwrap(), n);
+        }
+        let u256_ty = Type::U256;
+        let max_u256 = format!("0x{}", "f".repeat(64));
+        let max_u256_cbor = codec.encode(&u256_ty, &json!(max_u256)).unwrap();
+        assert_eq!(
+            codec.decode(&u256_ty, &max_u256_cbor).unwrap(),
+            json!("115792089237316195423570985008687907853269984665640564039457584007913129639935")
+        );
+        assert_eq!(
+            codec
+                .decode(&u256_ty, &codec.encode(&u256_ty, &json!(0)).unwrap())
+                .unwrap(),
+            json!("0")
+        );
+
+        let errors = vec![
+            (
+                Error::InsufficientFunds { address: [2; 20] },
+                json!({ "InsufficientFunds": { "address": "02".repeat(20) } }),
+            ),
+            (
+                Error::Rejected("no".to_string()),
+                json!({ "Rejected": "no" }),
+            ),
+            (
+                Error::Clamped(3, Some(InnerTy::Field1)),
+                json!({ "Clamped": [3, "Field1"] }),
+            ),
+            (Error::Unknown, json!("Unknown")),
+        ];
+        let error_ty = Type::Defined {
+            namespace: None,
+            ty: "Error".to_string(),
+        };
+        for (error, error_json) in errors {
+            let error_cbor = serde_cbor::to_vec(&error).unwrap();
+            assert_eq!(
+                codec.decode_error("transfer", &error_cbor).unwrap(),
+                error_json
+            );
+            let encoded = serde_cbor::to_vec(&codec.encode(&error_ty, &error_json).unwrap());
+            assert_eq!(
+                serde_cbor::from_slice::<Error>(&encoded.unwrap()).unwrap(),
+                error
+            );
+        }
+
+        let def_ty_ty = Type::Defined {
+            namespace: None,
+            ty: "DefTy".to_string(),
+        };
+        let cbor = serde_cbor::from_slice(&serde_cbor::to_vec(&def_ty()).unwrap()).unwrap();
+        assert_eq!(codec.decode(&def_ty_ty, &cbor).unwrap(), def_ty_json());
+        assert_eq!(
+            codec
+                .decode(
+                    &def_ty_ty,
+                    &codec.encode(&def_ty_ty, &def_ty_json()).unwrap()
+                )
+                .unwrap(),
+            def_ty_json()
+        );
+    }
+
+    #[test]
+    fn test_invalid() {
+        let iface = crate::testing::test_service();
+        let codec = Codec::new(&iface);
+        let mut def_ty = def_ty_json();
+        def_ty["f2"][1]["f1"] = json!("1");
+
+        match codec.encode_call("the", &[json!([def_ty]), json!("")]) {
+            Err(CodecError::InvalidValue { path, .. }) => assert_eq!(path, "arg1[0].f2[1].f1"),
+            result => panic!("unexpected result: {:?}", result),
+        }
+        match codec.encode(&Type::U256, &json!(format!("0x1{}", "0".repeat(64)))) {
+            Err(CodecError::InvalidValue { .. }) => (),
+            result => panic!("unexpected result: {:?}", result),
+        }
+        match codec.encode(&Type::U128, &json!(-1)) {
+            Err(CodecError::InvalidValue { .. }) => (),
+            result => panic!("unexpected result: {:?}", result),
+        }
+        match codec.encode(&Type::U128, &json!(format!("1{}", u128::max_value()))) {
+            Err(CodecError::InvalidValue { .. }) => (),
+            result => panic!("unexpected result: {:?}", result),
+        }
+        let u64_cbor = serde_cbor::to_vec(&u64::max_value()).unwrap();
+        match codec.decode_output("mint", &u64_cbor) {
+            Err(CodecError::InvalidValue { .. }) => (),
+            result => panic!("unexpected result: {:?}", result),
+        }
+        match codec.encode_call("void", &[json!(null)]) {
+            Err(CodecError::ArgCount { expected, found }) => assert_eq!((expected, found), (0, 1)),
+            result => panic!("unexpected result: {:?}", result),
+        }
+        match codec.decode_error("void", &[]) {
+            Err(CodecError::Infallible(_)) => (),
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+}
@@ -0,0 +1,745 @@
+//! Compares two versions of an `Interface` to find changes that break existing clients.
+
+use std::collections::BTreeMap;
+
+use crate::{Field, Function, IndexedField, Interface, StateMutability, Type, TypeDef, Variant};
+
+/// The component of a semantic version that must be incremented to publish a change.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Bump {
+    Patch,
+    Minor,
+    Major,
+}
+
+impl std::fmt::Display for Bump {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Bump::Patch => write!(f, "patch"),
+            Bump::Minor => write!(f, "minor"),
+            Bump::Major => write!(f, "major"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Change {
+    FunctionAdded(String),
+    FunctionRemoved(String),
+    /// A function was removed and another function having the same signature was added.
+    FunctionRenamed {
+        from: String,
+        to: String,
+    },
+    InputsChanged(String),
+    OutputChanged(String),
+    MutabilityChanged {
+        function: String,
+        from: StateMutability,
+        to: StateMutability,
+    },
+    /// A function started or stopped accepting value.
+    PayableChanged {
+        function: String,
+        payable: bool,
+    },
+    /// The callers permitted to call a function changed. The change is `restrictive`
+    /// if some calls that were permitted are no longer.
+    AccessChanged {
+        function: String,
+        restrictive: bool,
+    },
+    ConstructorChanged,
+    TypeAdded(String),
+    TypeRemoved(String),
+    /// A type definition was changed from one kind (e.g., struct) to another (e.g., enum).
+    TypeKindChanged(String),
+    VariantAdded {
+        ty: String,
+        variant: String,
+    },
+    VariantRemoved {
+        ty: String,
+        variant: String,
+    },
+    /// The fields of an enum variant changed.
+    VariantChanged {
+        ty: String,
+        variant: String,
+    },
+    FieldAdded {
+        ty: String,
+        field: String,
+    },
+    FieldRemoved {
+        ty: String,
+        field: String,
+    },
+    FieldChanged {
+        ty: String,
+        field: String,
+    },
+}
+
+impl Change {
+    /// Returns the version bump required to publish this change.
+    pub fn bump(&self) -> Bump {
+        use Change::*;
+        match self {
+            FunctionAdded(..)
+            | TypeAdded(..)
+            | VariantAdded { .. }
+            | PayableChanged { payable: true, .. }
+            | AccessChanged {
+                restrictive: false, ..
+            } => Bump::Minor,
+            _ => Bump::Major,
+        }
+    }
+
+    /// Returns whether clients of the old interface may fail when using the new one.
+    pub fn is_breaking(&self) -> bool {
+        self.bump() == Bump::Major
+    }
+}
+
+impl std::fmt::Display for Change {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        use Change::*;
+        match self {
+            FunctionAdded(name) => write!(f, "function `{}` was added", name),
+            FunctionRemoved(name) => write!(f, "function `{}` was removed", name),
+            FunctionRenamed { from, to } => {
+                write!(f, "function `{}` was renamed to `{}`", from, to)
+            }
+            InputsChanged(name) => write!(f, "inputs of function `{}` changed", name),
+            OutputChanged(name) => write!(f, "output of function `{}` changed", name),
+            MutabilityChanged { function, from, to } => write!(
+                f,
+                "mutability of function `{}` changed from {:?} to {:?}",
+                function, from, to
+            ),
+            PayableChanged { function, payable } => write!(
+                f,
+                "function `{}` {} accepts value",
+                function,
+                if *payable { "now" } else { "no longer" }
+            ),
+            AccessChanged {
+                function,
+                restrictive,
+            } => write!(
+                f,
+                "access to function `{}` was {}",
+                function,
+                if *restrictive {
+                    "restricted"
+                } else {
+                    "relaxed"
+                }
+            ),
+            ConstructorChanged => write!(f, "constructor changed"),
+            TypeAdded(name) => write!(f, "type `{}` was added", name),
+            TypeRemoved(name) => write!(f, "type `{}` was removed", name),
+            TypeKindChanged(name) => write!(f, "kind of type `{}` changed", name),
+            VariantAdded { ty, variant } => write!(f, "variant `{}::{}` was added", ty, variant),
+            VariantRemoved { ty, variant } => {
+                write!(f, "variant `{}::{}` was removed", ty, variant)
+            }
+            VariantChanged { ty, variant } => {
+                write!(f, "fields of variant `{}::{}` changed", ty, variant)
+            }
+            FieldAdded { ty, field } => write!(f, "field `{}.{}` was added", ty, field),
+            FieldRemoved { ty, field } => write!(f, "field `{}.{}` was removed", ty, field),
+            FieldChanged { ty, field } => write!(f, "type of field `{}.{}` changed", ty, field),
+        }
+    }
+}
+
+/// Returns the changes that turn `old` into `new`.
+pub fn diff(old: &Interface, new: &Interface) -> Vec<Change> {
+    let mut changes = Vec::new();
+    diff_functions(&old.functions, &new.functions, &mut changes);
+    if input_types(&old.constructor.inputs) != input_types(&new.constructor.inputs)
+        || old.constructor.error != new.constructor.error
+    {
+        changes.push(Change::ConstructorChanged);
+    }
+    diff_type_defs(&old.type_defs, &new.type_defs, &mut changes);
+    changes
+}
+
+/// Returns the smallest version bump that publishes all of `changes`.
+/// An interface without changes requires only a patch release.
+pub fn required_bump(changes: &[Change]) -> Bump {
+    changes
+        .iter()
+        .map(Change::bump)
+        .max()
+        .unwrap_or(Bump::Patch)
+}
+
+/// Returns whether going from `old_version` to `new_version` publishes a `required` bump.
+/// As with Cargo, a `0.y.z` version treats the minor version as the major version.
+pub fn is_sufficient_bump(
+    old_version: &semver::Version,
+    new_version: &semver::Version,
+    required: Bump,
+) -> bool {
+    if new_version <= old_version {
+        return false;
+    }
+    let actual = if new_version.major != old_version.major {
+        Bump::Major
+    } else if new_version.minor != old_version.minor {
+        if new_version.major == 0 {
+            Bump::Major
+        } else {
+            Bump::Minor
+        }
+    } else if new_version.major == 0 {
+        Bump::Minor
+    } else {
+        Bump::Patch
+    };
+    actual >= required
+}
+
+/// The result of checking that a new interface is correctly versioned.
+#[derive(Debug)]
+pub struct Report {
+    pub changes: Vec<Change>,
+    pub required_bump: Bump,
+    /// Whether the version of the new interface is sufficiently greater than the old version.
+    pub version_ok: bool,
+}
+
+/// Diffs `old` and `new` and checks the new interface's version against the changes.
+pub fn check(old: &Interface, new: &Interface) -> Result<Report, CompatError> {
+    let parse_version = |iface: &Interface| {
+        semver::Version::parse(&iface.version)
+            .map_err(|err| CompatError::InvalidVersion(iface.version.clone(), err))
+    };
+    let old_version = parse_version(old)?;
+    let new_version = parse_version(new)?;
+    let changes = diff(old, new);
+    let required_bump = required_bump(&changes);
+    Ok(Report {
+        version_ok: is_sufficient_bump(&old_version, &new_version, required_bump),
+        changes,
+        required_bump,
+    })
+}
+
+#[derive(Debug, failure::Fail)]
+pub enum CompatError {
+    #[fail(display = "Invalid version `{}`: {}", _0, _1)]
+    InvalidVersion(String, #[fail(cause)] semver::SemVerError),
+}
+
+fn diff_functions(old_fns: &[Function], new_fns: &[Function], changes: &mut Vec<Change>) {
+    let new_fns_by_name: BTreeMap<_, _> = new_fns.iter().map(|f| (&f.name, f)).collect();
+    let mut removed_fns = Vec::new();
+    for old_fn in old_fns.iter() {
+        let new_fn = match new_fns_by_name.get(&old_fn.name) {
+            Some(new_fn) => new_fn,
+            None => {
+                removed_fns.push(old_fn);
+                continue;
+            }
+        };
+        if old_fn.mutability != new_fn.mutability {
+            changes.push(Change::MutabilityChanged {
+                function: old_fn.name.clone(),
+                from: old_fn.mutability.clone(),
+                to: new_fn.mutability.clone(),
+            });
+        }
+        if old_fn.payable != new_fn.payable {
+            changes.push(Change::PayableChanged {
+                function: old_fn.name.clone(),
+                payable: new_fn.payable,
+            });
+        }
+        if old_fn.only != new_fn.only || old_fn.when_not_paused != new_fn.when_not_paused {
+            // Permitting more principals relaxes access, since any one of them suffices.
+            let restrictive = (!new_fn.only.is_empty()
+                && (old_fn.only.is_empty()
+                    || old_fn.only.iter().any(|p| !new_fn.only.contains(p))))
+                || (new_fn.when_not_paused && !old_fn.when_not_paused);
+            changes.push(Change::AccessChanged {
+                function: old_fn.name.clone(),
+                restrictive,
+            });
+        }
+        if input_types(&old_fn.inputs) != input_types(&new_fn.inputs) {
+            changes.push(Change::InputsChanged(old_fn.name.clone()));
+        }
+        if old_fn.output != new_fn.output {
+            changes.push(Change::OutputChanged(old_fn.name.clone()));
+        }
+    }
+
+    let mut added_fns: Vec<_> = new_fns
+        .iter()
+        .filter(|new_fn| !old_fns.iter().any(|f| f.name == new_fn.name))
+        .collect();
+    for removed_fn in removed_fns {
+        let renamed_to = added_fns.iter().position(|added_fn| {
+            added_fn.mutability == removed_fn.mutability
+                && input_types(&added_fn.inputs) == input_types(&removed_fn.inputs)
+                && added_fn.output == removed_fn.output
+        });
+        changes.push(match renamed_to {
+            Some(idx) => Change::FunctionRenamed {
+                from: removed_fn.name.clone(),
+                to: added_fns.remove(idx).name.clone(),
+            },
+            None => Change::FunctionRemoved(removed_fn.name.clone()),
+        });
+    }
+    changes.extend(
+        added_fns
+            .into_iter()
+            .map(|f| Change::FunctionAdded(f.name.clone())),
+    );
+}
+
+fn diff_type_defs(old_defs: &[TypeDef], new_defs: &[TypeDef], changes: &mut Vec<Change>) {
+    for old_def in old_defs.iter() {
+        let ty = old_def.name().to_string();
+        let new_def = match new_defs.iter().find(|d| d.name() == old_def.name()) {
+            Some(new_def) => new_def,
+            None => {
+                changes.push(Change::TypeRemoved(ty));
+                continue;
+            }
+        };
+        match (old_def, new_def) {
+            (
+                TypeDef::Struct {
+                    fields: old_fields, ..
+                },
+                TypeDef::Struct {
+                    fields: new_fields, ..
+                },
+            ) => diff_fields(&ty, fields(old_fields), fields(new_fields), changes),
+            (
+                TypeDef::Event {
+                    fields: old_fields, ..
+                },
+                TypeDef::Event {
+                    fields: new_fields, ..
+                },
+            ) => diff_fields(
+                &ty,
+                indexed_fields(old_fields),
+                indexed_fields(new_fields),
+                changes,
+            ),
+            (
+                TypeDef::Enum {
+                    variants: old_variants,
+                    ..
+                },
+                TypeDef::Enum {
+                    variants: new_variants,
+                    ..
+                },
+            ) => {
+                for old_variant in old_variants.iter() {
+                    let variant = old_variant.name().to_string();
+                    match new_variants.iter().find(|v| v.name() == variant) {
+                        Some(new_variant) if same_variant(new_variant, old_variant) => (),
+                        Some(_) => changes.push(Change::VariantChanged {
+                            ty: ty.clone(),
+                            variant,
+                        }),
+                        None => changes.push(Change::VariantRemoved {
+                            ty: ty.clone(),
+                            variant,
+                        }),
+                    }
+                }
+                for new_variant in new_variants.iter() {
+                    if !old_variants.iter().any(|v| v.name() == new_variant.name()) {
+                        changes.push(Change::VariantAdded {
+                            ty: ty.clone(),
+                            variant: new_variant.name().to_string(),
+                        });
+                    }
+                }
+            }
+            _ => changes.push(Change::TypeKindChanged(ty)),
+        }
+    }
+    for new_def in new_defs.iter() {
+        if !old_defs.iter().any(|d| d.name() == new_def.name()) {
+            changes.push(Change::TypeAdded(new_def.name().to_string()));
+        }
+    }
+}
+
+/// Returns the types of the inputs of a function or constructor. Inputs are passed by
+/// position, so their names are not part of the interface.
+fn input_types(inputs: &[Field]) -> Vec<&Type> {
+    inputs.iter().map(|inp| &inp.ty).collect()
+}
+
+/// Field name, type, and whether the field is indexed.
+type FieldSig<'a> = (&'a str, &'a Type, bool);
+
+fn fields(fields: &[Field]) -> Vec<FieldSig<'_>> {
+    fields
+        .iter()
+        .map(|f| (f.name.as_str(), &f.ty, false))
+        .collect()
+}
+
+fn indexed_fields(fields: &[IndexedField]) -> Vec<FieldSig<'_>> {
+    fields
+        .iter()
+        .map(|f| (f.name.as_str(), &f.ty, f.indexed))
+        .collect()
+}
+
+/// Returns whether two variants have the same name and payload, ignoring docs.
+fn same_variant(a: &Variant, b: &Variant) -> bool {
+    match (a, b) {
+        (Variant::Unit(a), Variant::Unit(b)) => a == b,
+        (
+            Variant::Tuple { name, items },
+            Variant::Tuple {
+                name: b_name,
+                items: b_items,
+            },
+        ) => name == b_name && items == b_items,
+        (
+            Variant::Struct {
+                name,
+                fields: a_fields,
+            },
+            Variant::Struct {
+                name: b_name,
+                fields: b_fields,
+            },
+        ) => name == b_name && fields(a_fields) == fields(b_fields),
+        _ => false,
+    }
+}
+
+fn diff_fields(
+    ty: &str,
+    old_fields: Vec<FieldSig>,
+    new_fields: Vec<FieldSig>,
+    changes: &mut Vec<Change>,
+) {
+    for (name, old_ty, old_indexed) in old_fields.iter() {
+        match new_fields.iter().find(|(n, _, _)| n == name) {
+            Some((_, new_ty, new_indexed)) => {
+                if old_ty != new_ty || old_indexed != new_indexed {
+                    changes.push(Change::FieldChanged {
+                        ty: ty.to_string(),
+                        field: name.to_string(),
+                    });
+                }
+            }
+            None => changes.push(Change::FieldRemoved {
+                ty: ty.to_string(),
+                field: name.to_string(),
+            }),
+        }
+    }
+    for (name, _, _) in new_fields.iter() {
+        if !old_fields.iter().any(|(n, _, _)| n == name) {
+            changes.push(Change::FieldAdded {
+                ty: ty.to_string(),
+                field: name.to_string(),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{Constructor, Principal, Variant};
+
+    fn iface(version: &str, functions: Vec<Function>, type_defs: Vec<TypeDef>) -> Interface {
+        Interface {
+            name: "TestService".to_string(),
+            namespace: "test_service".to_string(),
+            version: version.to_string(),
+            imports: Vec::new(),
+            type_defs,
+            constructor: Constructor {
+                inputs: Vec::new(),
+                error: None,
+                docs: None,
+            },
+            migration: None,
+            functions,
+            oasis_build_version: "0.2.2".to_string(),
+            docs: None,
+        }
+    }
+
+    fn function(name: &str, inputs: Vec<Type>, output: Option<Type>) -> Function {
+        Function {
+            name: name.to_string(),
+            mutability: StateMutability::Mutable,
+            inputs: inputs
+                .into_iter()
+                .enumerate()
+                .map(|(i, ty)| Field {
+                    name: format!("arg{}", i),
+                    ty,
+                    docs: None,
+                })
+                .collect(),
+            output,
+            docs: None,
+            selector: None,
+            payable: false,
+            only: Vec::new(),
+            when_not_paused: false,
+        }
+    }
+
+    fn enum_def(variants: &[&str]) -> TypeDef {
+        TypeDef::Enum {
+            name: "Color".to_string(),
+            variants: variants
+                .iter()
+                .map(|v| Variant::Unit(v.to_string()))
+                .collect(),
+            docs: None,
+        }
+    }
+
+    #[test]
+    fn unchanged() {
+        let old = iface(
+            "1.0.0",
+            vec![function("get", vec![], Some(Type::U32))],
+            vec![],
+        );
+        let report = check(&old, &iface("1.0.1", old.functions.clone(), vec![])).unwrap();
+        assert!(report.changes.is_empty());
+        assert_eq!(report.required_bump, Bump::Patch);
+        assert!(report.version_ok);
+        assert!(!check(&old, &old).unwrap().version_ok);
+    }
+
+    #[test]
+    fn docs_changes() {
+        let old = iface(
+            "1.0.0",
+            vec![function("set", vec![Type::U32], None)],
+            vec![enum_def(&["Red"])],
+        );
+        let mut new = old.clone();
+        new.version = "1.0.1".to_string();
+        new.docs = Some("A service.".to_string());
+        new.functions[0].docs = Some("Sets the value.".to_string());
+        new.functions[0].inputs[0].docs = Some("The new value.".to_string());
+        if let TypeDef::Enum { docs, .. } = &mut new.type_defs[0] {
+            *docs = Some("A color.".to_string());
+        }
+        let report = check(&old, &new).unwrap();
+        assert!(report.changes.is_empty());
+        assert!(report.version_ok);
+    }
+
+    #[test]
+    fn input_changes() {
+        let mut old = iface(
+            "1.0.0",
+            vec![function("set", vec![Type::U32, Type::Bool], None)],
+            vec![],
+        );
+        old.constructor.inputs = old.functions[0].inputs.clone();
+
+        let mut renamed = old.clone();
+        renamed.version = "1.0.1".to_string();
+        renamed.functions[0].inputs[0].name = "value".to_string();
+        renamed.constructor.inputs[1].name = "flag".to_string();
+        assert!(check(&old, &renamed).unwrap().changes.is_empty());
+
+        let mut reordered = old.clone();
+        reordered.version = "2.0.0".to_string();
+        reordered.functions[0].inputs.reverse();
+        reordered.constructor.inputs.reverse();
+        assert_eq!(
+            check(&old, &reordered).unwrap().changes,
+            vec![
+                Change::InputsChanged("set".to_string()),
+                Change::ConstructorChanged
+            ]
+        );
+    }
+
+    #[test]
+    fn function_changes() {
+        let old = iface(
+            "1.0.0",
+            vec![
+                function("get", vec![], Some(Type::U32)),
+                function("set", vec![Type::U32], None),
+                function("remove", vec![Type::String], None),
+                function("old_name", vec![Type::Bool], Some(Type::Bool)),
+            ],
+            vec![],
+        );
+        let mut get = function("get", vec![], Some(Type::U32));
+        get.mutability = StateMutability::Immutable;
+        get.payable = true;
+        get.only = vec![Principal::Owner];
+        let new = iface(
+            "1.1.0",
+            vec![
+                get,
+                function("set", vec![Type::U64], Some(Type::U32)),
+                function("new_name", vec![Type::Bool], Some(Type::Bool)),
+                function("added", vec![], None),
+            ],
+            vec![],
+        );
+        let report = check(&old, &new).unwrap();
+        assert_eq!(
+            report.changes,
+            vec![
+                Change::MutabilityChanged {
+                    function: "get".to_string(),
+                    from: StateMutability::Mutable,
+                    to: StateMutability::Immutable,
+                },
+                Change::PayableChanged {
+                    function: "get".to_string(),
+                    payable: true,
+                },
+                Change::AccessChanged {
+                    function: "get".to_string(),
+                    restrictive: true,
+                },
+                Change::InputsChanged("set".to_string()),
+                Change::OutputChanged("set".to_string()),
+                Change::FunctionRemoved("remove".to_string()),
+                Change::FunctionRenamed {
+                    from: "old_name".to_string(),
+                    to: "new_name".to_string(),
+                },
+                Change::FunctionAdded("added".to_string()),
+            ]
+        );
+        assert_eq!(report.required_bump, Bump::Major);
+        assert!(!report.version_ok);
+    }
+
+    #[test]
+    fn type_def_changes() {
+        let old = iface(
+            "0.1.0",
+            vec![],
+            vec![
+                enum_def(&["Red", "Green"]),
+                TypeDef::Struct {
+                    name: "Point".to_string(),
+                    fields: vec![
+                        Field {
+                            name: "x".to_string(),
+                            ty: Type::U32,
+                            docs: None,
+                        },
+                        Field {
+                            name: "y".to_string(),
+                            ty: Type::U32,
+                            docs: None,
+                        },
+                    ],
+                    docs: None,
+                },
+            ],
+        );
+
+        let extended = iface("0.1.1", vec![], {
+            let mut defs = old.type_defs.clone();
+            defs[0] = enum_def(&["Red", "Green", "Blue"]);
+            defs
+        });
+        let report = check(&old, &extended).unwrap();
+        assert_eq!(
+            report.changes,
+            vec![Change::VariantAdded {
+                ty: "Color".to_string(),
+                variant: "Blue".to_string(),
+            }]
+        );
+        assert_eq!(report.required_bump, Bump::Minor);
+        assert!(report.version_ok);
+
+        let broken = iface(
+            "0.1.2",
+            vec![],
+            vec![
+                TypeDef::Enum {
+                    name: "Color".to_string(),
+                    variants: vec![Variant::Tuple {
+                        name: "Red".to_string(),
+                        items: vec![Type::U8],
+                    }],
+                    docs: None,
+                },
+                TypeDef::Struct {
+                    name: "Point".to_string(),
+                    fields: vec![
+                        Field {
+                            name: "x".to_string(),
+                            ty: Type::I32,
+                            docs: None,
+                        },
+                        Field {
+                            name: "z".to_string(),
+                            ty: Type::U32,
+                            docs: None,
+                        },
+                    ],
+                    docs: None,
+                },
+            ],
+        );
+        let report = check(&old, &broken).unwrap();
+        assert_eq!(
+            report.changes,
+            vec![
+                Change::VariantChanged {
+                    ty: "Color".to_string(),
+                    variant: "Red".to_string(),
+                },
+                Change::VariantRemoved {
+                    ty: "Color".to_string(),
+                    variant: "Green".to_string(),
+                },
+                Change::FieldChanged {
+                    ty: "Point".to_string(),
+                    field: "x".to_string(),
+                },
+                Change::FieldRemoved {
+                    ty: "Point".to_string(),
+                    field: "y".to_string(),
+                },
+                Change::FieldAdded {
+                    ty: "Point".to_string(),
+                    field: "z".to_string(),
+                },
+            ]
+        );
+        assert!(!report.version_ok); // 0.x requires a minor bump for breaking changes
+        assert!(is_sufficient_bump(
+            &semver::Version::parse("0.1.0").unwrap(),
+            &semver::Version::parse("0.2.0").unwrap(),
+            Bump::Major
+        ));
+    }
+}
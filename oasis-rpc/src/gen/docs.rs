@@ -0,0 +1,573 @@
+//! Renders the API reference of a service as Markdown or as a standalone HTML page.
+//!
+//! Types defined by an imported service are linked to its reference, which is expected
+//! to be found next to this one as `<namespace>.md` or `<namespace>.html`.
+
+use std::fmt::Write as _;
+
+use crate::{Field, Function, Interface, StateMutability, Type, TypeDef, Variant};
+
+#[derive(Clone, Copy)]
+enum Format {
+    Markdown,
+    Html,
+}
+
+impl Format {
+    fn type_link(self, namespace: Option<&str>, ty: &str) -> String {
+        match (self, namespace) {
+            (Format::Markdown, None) => format!("[{}](#{})", ty, ty.to_lowercase()),
+            (Format::Markdown, Some(ns)) => {
+                format!("[{0}::{1}]({0}.md#{2})", ns, ty, ty.to_lowercase())
+            }
+            (Format::Html, None) => format!("<a href=\"#type.{0}\">{0}</a>", ty),
+            (Format::Html, Some(ns)) => {
+                format!("<a href=\"{0}.html#type.{1}\">{0}::{1}</a>", ns, ty)
+            }
+        }
+    }
+}
+
+pub fn markdown(iface: &Interface) -> String {
+    let fmt = Format::Markdown;
+    let mut md = String::new();
+    writeln!(md, "# {}", iface.name).unwrap();
+    writeln!(md).unwrap();
+    if let Some(docs) = &iface.docs {
+        writeln!(md, "{}\n", docs).unwrap();
+    }
+    writeln!(md, "| | |\n| --- | --- |").unwrap();
+    writeln!(md, "| Namespace | `{}` |", iface.namespace).unwrap();
+    writeln!(md, "| Version | {} |", iface.version).unwrap();
+    writeln!(
+        md,
+        "| Built with | oasis-build {} |",
+        iface.oasis_build_version
+    )
+    .unwrap();
+
+    if !iface.imports.is_empty() {
+        writeln!(md, "\n## Imports\n").unwrap();
+        writeln!(md, "| Service | Version | Registry |\n| --- | --- | --- |").unwrap();
+        for import in iface.imports.iter() {
+            writeln!(
+                md,
+                "| [{0}]({0}.md) | {1} | {2} |",
+                import.name,
+                import.version,
+                import.registry.as_ref().map(String::as_str).unwrap_or("")
+            )
+            .unwrap();
+        }
+    }
+
+    writeln!(md, "\n## Constructor\n").unwrap();
+    if let Some(docs) = &iface.constructor.docs {
+        writeln!(md, "{}\n", docs).unwrap();
+    }
+    md_inputs(&mut md, &iface.constructor.inputs);
+    if let Some(error) = &iface.constructor.error {
+        writeln!(md, "**Error:** {}\n", fmt_ty(error, fmt)).unwrap();
+    }
+
+    if let Some(migration) = &iface.migration {
+        writeln!(md, "## Migration\n").unwrap();
+        if let Some(docs) = &migration.docs {
+            writeln!(md, "{}\n", docs).unwrap();
+        }
+        md_inputs(&mut md, &migration.inputs);
+        if let Some(error) = &migration.error {
+            writeln!(md, "**Error:** {}\n", fmt_ty(error, fmt)).unwrap();
+        }
+    }
+
+    if !iface.functions.is_empty() {
+        writeln!(md, "## Functions\n").unwrap();
+    }
+    for func in iface.functions.iter() {
+        writeln!(md, "### {}\n", func.name).unwrap();
+        writeln!(md, "*{}*\n", function_note(func)).unwrap();
+        if let Some(docs) = &func.docs {
+            writeln!(md, "{}\n", docs).unwrap();
+        }
+        md_inputs(&mut md, &func.inputs);
+        let (output_ty, err_ty) = super::split_output(&func.output);
+        if let Some(output_ty) = output_ty {
+            writeln!(md, "**Returns:** {}\n", fmt_ty(output_ty, fmt)).unwrap();
+        }
+        if let Some(err_ty) = err_ty {
+            writeln!(md, "**Error:** {}\n", fmt_ty(err_ty, fmt)).unwrap();
+        }
+    }
+
+    let (events, types): (Vec<_>, Vec<_>) = iface.type_defs.iter().partition(|def| match def {
+        TypeDef::Event { .. } => true,
+        _ => false,
+    });
+    if !types.is_empty() {
+        writeln!(md, "## Types\n").unwrap();
+    }
+    for def in types {
+        writeln!(md, "### {}\n", def.name()).unwrap();
+        if let Some(docs) = def.docs() {
+            writeln!(md, "{}\n", docs).unwrap();
+        }
+        match def {
+            TypeDef::Struct { fields, .. } => {
+                writeln!(md, "*struct*\n").unwrap();
+                md_fields(&mut md, "Field", fields);
+            }
+            TypeDef::Enum { variants, .. } => {
+                writeln!(md, "*enum*\n").unwrap();
+                writeln!(md, "| Variant | Payload |\n| --- | --- |").unwrap();
+                for variant in variants.iter() {
+                    writeln!(
+                        md,
+                        "| `{}` | {} |",
+                        variant.name(),
+                        variant_payload(variant, fmt)
+                    )
+                    .unwrap();
+                }
+                writeln!(md).unwrap();
+            }
+            TypeDef::Event { .. } => unreachable!("events were partitioned out"),
+        }
+    }
+
+    if !events.is_empty() {
+        writeln!(md, "## Events\n").unwrap();
+    }
+    for def in events {
+        let fields = match def {
+            TypeDef::Event { fields, .. } => fields,
+            _ => unreachable!("only events were partitioned"),
+        };
+        writeln!(md, "### {}\n", def.name()).unwrap();
+        if let Some(docs) = def.docs() {
+            writeln!(md, "{}\n", docs).unwrap();
+        }
+        writeln!(md, "**Topic:** `0x{}`\n", super::event_topic(def.name())).unwrap();
+        writeln!(
+            md,
+            "| Field | Type | Indexed | Description |\n| --- | --- | --- | --- |"
+        )
+        .unwrap();
+        for field in fields.iter() {
+            writeln!(
+                md,
+                "| `{}` | {} | {} | {} |",
+                field.name,
+                fmt_ty(&field.ty, fmt),
+                if field.indexed { "yes" } else { "no" },
+                md_cell(field.docs.as_ref())
+            )
+            .unwrap();
+        }
+        writeln!(md).unwrap();
+    }
+    md.truncate(md.trim_end().len());
+    md.push('\n');
+    md
+}
+
+fn md_inputs(md: &mut String, inputs: &[Field]) {
+    if inputs.is_empty() {
+        writeln!(md, "Takes no inputs.\n").unwrap();
+    } else {
+        md_fields(md, "Input", inputs);
+    }
+}
+
+fn md_fields(md: &mut String, heading: &str, fields: &[Field]) {
+    writeln!(
+        md,
+        "| {} | Type | Description |\n| --- | --- | --- |",
+        heading
+    )
+    .unwrap();
+    for field in fields.iter() {
+        writeln!(
+            md,
+            "| `{}` | {} | {} |",
+            field.name,
+            fmt_ty(&field.ty, Format::Markdown),
+            md_cell(field.docs.as_ref())
+        )
+        .unwrap();
+    }
+    writeln!(md).unwrap();
+}
+
+/// Formats docs for use in a table cell, which must fit on one line.
+fn md_cell(docs: Option<&String>) -> String {
+    docs.map(|docs| docs.replace('\n', " ").replace('|', "\\|"))
+        .unwrap_or_default()
+}
+
+pub fn html(iface: &Interface) -> String {
+    let fmt = Format::Html;
+    let mut html = String::new();
+    writeln!(html, "<!DOCTYPE html>").unwrap();
+    writeln!(html, "<html>").unwrap();
+    writeln!(html, "<head>").unwrap();
+    writeln!(html, "<meta charset=\"utf-8\">").unwrap();
+    writeln!(
+        html,
+        "<title>{} v{}</title>",
+        escape(&iface.name),
+        escape(&iface.version)
+    )
+    .unwrap();
+    writeln!(html, "<style>{}</style>", STYLE).unwrap();
+    writeln!(html, "</head>").unwrap();
+    writeln!(html, "<body>").unwrap();
+    writeln!(html, "<h1>{}</h1>", escape(&iface.name)).unwrap();
+    html_docs(&mut html, iface.docs.as_ref().map(String::as_str));
+    writeln!(html, "<table>").unwrap();
+    writeln!(
+        html,
+        "<tr><th>Namespace</th><td><code>{}</code></td></tr>",
+        escape(&iface.namespace)
+    )
+    .unwrap();
+    writeln!(
+        html,
+        "<tr><th>Version</th><td>{}</td></tr>",
+        escape(&iface.version)
+    )
+    .unwrap();
+    writeln!(
+        html,
+        "<tr><th>Built with</th><td>oasis-build {}</td></tr>",
+        escape(&iface.oasis_build_version)
+    )
+    .unwrap();
+    writeln!(html, "</table>").unwrap();
+
+    if !iface.imports.is_empty() {
+        writeln!(html, "<h2 id=\"imports\">Imports</h2>").unwrap();
+        writeln!(
+            html,
+            "<table>\n<tr><th>Service</th><th>Version</th><th>Registry</th></tr>"
+        )
+        .unwrap();
+        for import in iface.imports.iter() {
+            writeln!(
+                html,
+                "<tr><td><a href=\"{0}.html\">{0}</a></td><td>{1}</td><td>{2}</td></tr>",
+                escape(&import.name),
+                escape(&import.version),
+                escape(import.registry.as_ref().map(String::as_str).unwrap_or(""))
+            )
+            .unwrap();
+        }
+        writeln!(html, "</table>").unwrap();
+    }
+
+    writeln!(html, "<h2 id=\"constructor\">Constructor</h2>").unwrap();
+    html_docs(
+        &mut html,
+        iface.constructor.docs.as_ref().map(String::as_str),
+    );
+    html_inputs(&mut html, &iface.constructor.inputs);
+    if let Some(error) = &iface.constructor.error {
+        writeln!(html, "<p><b>Error:</b> {}</p>", fmt_ty(error, fmt)).unwrap();
+    }
+
+    if let Some(migration) = &iface.migration {
+        writeln!(html, "<h2 id=\"migration\">Migration</h2>").unwrap();
+        html_docs(&mut html, migration.docs.as_ref().map(String::as_str));
+        html_inputs(&mut html, &migration.inputs);
+        if let Some(error) = &migration.error {
+            writeln!(html, "<p><b>Error:</b> {}</p>", fmt_ty(error, fmt)).unwrap();
+        }
+    }
+
+    if !iface.functions.is_empty() {
+        writeln!(html, "<h2 id=\"functions\">Functions</h2>").unwrap();
+    }
+    for func in iface.functions.iter() {
+        writeln!(
+            html,
+            "<h3 id=\"fn.{0}\"><code>{0}</code></h3>",
+            escape(&func.name)
+        )
+        .unwrap();
+        writeln!(html, "<p><i>{}</i></p>", function_note(func)).unwrap();
+        html_docs(&mut html, func.docs.as_ref().map(String::as_str));
+        html_inputs(&mut html, &func.inputs);
+        let (output_ty, err_ty) = super::split_output(&func.output);
+        if let Some(output_ty) = output_ty {
+            writeln!(html, "<p><b>Returns:</b> {}</p>", fmt_ty(output_ty, fmt)).unwrap();
+        }
+        if let Some(err_ty) = err_ty {
+            writeln!(html, "<p><b>Error:</b> {}</p>", fmt_ty(err_ty, fmt)).unwrap();
+        }
+    }
+
+    let (events, types): (Vec<_>, Vec<_>) = iface.type_defs.iter().partition(|def| match def {
+        TypeDef::Event { .. } => true,
+        _ => false,
+    });
+    if !types.is_empty() {
+        writeln!(html, "<h2 id=\"types\">Types</h2>").unwrap();
+    }
+    for def in types {
+        writeln!(
+            html,
+            "<h3 id=\"type.{0}\"><code>{0}</code></h3>",
+            escape(def.name())
+        )
+        .unwrap();
+        html_docs(&mut html, def.docs());
+        match def {
+            TypeDef::Struct { fields, .. } => {
+                writeln!(html, "<p><i>struct</i></p>").unwrap();
+                html_fields(&mut html, "Field", fields);
+            }
+            TypeDef::Enum { variants, .. } => {
+                writeln!(html, "<p><i>enum</i></p>").unwrap();
+                writeln!(html, "<table>\n<tr><th>Variant</th><th>Payload</th></tr>").unwrap();
+                for variant in variants.iter() {
+                    writeln!(
+                        html,
+                        "<tr><td><code>{}</code></td><td>{}</td></tr>",
+                        escape(variant.name()),
+                        variant_payload(variant, fmt)
+                    )
+                    .unwrap();
+                }
+                writeln!(html, "</table>").unwrap();
+            }
+            TypeDef::Event { .. } => unreachable!("events were partitioned out"),
+        }
+    }
+
+    if !events.is_empty() {
+        writeln!(html, "<h2 id=\"events\">Events</h2>").unwrap();
+    }
+    for def in events {
+        let fields = match def {
+            TypeDef::Event { fields, .. } => fields,
+            _ => unreachable!("only events were partitioned"),
+        };
+        writeln!(
+            html,
+            "<h3 id=\"type.{0}\"><code>{0}</code></h3>",
+            escape(def.name())
+        )
+        .unwrap();
+        html_docs(&mut html, def.docs());
+        writeln!(
+            html,
+            "<p><b>Topic:</b> <code>0x{}</code></p>",
+            super::event_topic(def.name())
+        )
+        .unwrap();
+        writeln!(
+            html,
+            "<table>\n<tr><th>Field</th><th>Type</th><th>Indexed</th><th>Description</th></tr>"
+        )
+        .unwrap();
+        for field in fields.iter() {
+            writeln!(
+                html,
+                "<tr><td><code>{}</code></td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                escape(&field.name),
+                fmt_ty(&field.ty, fmt),
+                if field.indexed { "yes" } else { "no" },
+                escape(field.docs.as_ref().map(String::as_str).unwrap_or(""))
+            )
+            .unwrap();
+        }
+        writeln!(html, "</table>").unwrap();
+    }
+
+    writeln!(html, "</body>").unwrap();
+    writeln!(html, "</html>").unwrap();
+    html
+}
+
+const STYLE: &str = "body { font-family: sans-serif; max-width: 60em; margin: 2em auto; } \
+                     table { border-collapse: collapse; margin: 1em 0; } \
+                     th, td { border: 1px solid #ccc; padding: 0.3em 0.6em; text-align: left; } \
+                     code { font-size: 0.95em; }";
+
+/// Writes `docs` as paragraphs, which are separated by blank lines.
+fn html_docs(html: &mut String, docs: Option<&str>) {
+    let docs = match docs {
+        Some(docs) => docs,
+        None => return,
+    };
+    for paragraph in docs.split("\n\n") {
+        if !paragraph.trim().is_empty() {
+            writeln!(html, "<p>{}</p>", escape(paragraph.trim())).unwrap();
+        }
+    }
+}
+
+fn html_inputs(html: &mut String, inputs: &[Field]) {
+    if inputs.is_empty() {
+        writeln!(html, "<p>Takes no inputs.</p>").unwrap();
+    } else {
+        html_fields(html, "Input", inputs);
+    }
+}
+
+fn html_fields(html: &mut String, heading: &str, fields: &[Field]) {
+    writeln!(
+        html,
+        "<table>\n<tr><th>{}</th><th>Type</th><th>Description</th></tr>",
+        heading
+    )
+    .unwrap();
+    for field in fields.iter() {
+        writeln!(
+            html,
+            "<tr><td><code>{}</code></td><td>{}</td><td>{}</td></tr>",
+            escape(&field.name),
+            fmt_ty(&field.ty, Format::Html),
+            escape(field.docs.as_ref().map(String::as_str).unwrap_or(""))
+        )
+        .unwrap();
+    }
+    writeln!(html, "</table>").unwrap();
+}
+
+fn function_note(func: &Function) -> String {
+    let mutability = match func.mutability {
+        StateMutability::Immutable => "Immutable: does not modify the state of the service.",
+        StateMutability::Mutable => "Mutable: may modify the state of the service.",
+    };
+    let mut note = mutability.to_string();
+    if func.payable {
+        note.push_str(" Payable: accepts value.");
+    }
+    if !func.only.is_empty() {
+        note.push_str(&format!(
+            " Callable only by {}.",
+            super::fmt_principals(&func.only)
+        ));
+    }
+    if func.when_not_paused {
+        note.push_str(" Not callable while the service is paused.");
+    }
+    note
+}
+
+fn variant_payload(variant: &Variant, fmt: Format) -> String {
+    match variant {
+        Variant::Unit(_) => String::new(),
+        Variant::Tuple { items, .. } => format!(
+            "({})",
+            items
+                .iter()
+                .map(|ty| fmt_ty(ty, fmt))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        Variant::Struct { fields, .. } => {
+            let fields: Vec<_> = fields
+                .iter()
+                .map(|f| format!("{}: {}", escape(&f.name), fmt_ty(&f.ty, fmt)))
+                .collect();
+            format!("{{ {} }}", fields.join(", "))
+        }
+    }
+}
+
+/// Formats a type with links to the definitions of the types it contains.
+/// Types are written as in the textual IDL, e.g., `Vec<Option<string>>`.
+fn fmt_ty(ty: &Type, fmt: Format) -> String {
+    let generic = |name: &str, params: &[&Type]| {
+        let params: Vec<_> = params.iter().map(|ty| fmt_ty(ty, fmt)).collect();
+        format!("{}&lt;{}&gt;", name, params.join(", "))
+    };
+    match ty {
+        Type::Bool => "bool".to_string(),
+        Type::U8 => "u8".to_string(),
+        Type::I8 => "i8".to_string(),
+        Type::U16 => "u16".to_string(),
+        Type::I16 => "i16".to_string(),
+        Type::U32 => "u32".to_string(),
+        Type::I32 => "i32".to_string(),
+        Type::U64 => "u64".to_string(),
+        Type::I64 => "i64".to_string(),
+        Type::U128 => "u128".to_string(),
+        Type::I128 => "i128".to_string(),
+        Type::U256 => "u256".to_string(),
+        Type::F32 => "f32".to_string(),
+        Type::F64 => "f64".to_string(),
+        Type::Bytes => "bytes".to_string(),
+        Type::String => "string".to_string(),
+        Type::Address => "address".to_string(),
+        Type::Defined { namespace, ty } => {
+            fmt.type_link(namespace.as_ref().map(String::as_str), ty)
+        }
+        Type::Tuple(tys) => {
+            let tys: Vec<_> = tys.iter().map(|ty| fmt_ty(ty, fmt)).collect();
+            match tys.as_slice() {
+                [ty] => format!("({},)", ty),
+                _ => format!("({})", tys.join(", ")),
+            }
+        }
+        Type::Array(ty, count) => format!("[{}; {}]", fmt_ty(ty, fmt), count),
+        Type::List(ty) => generic("Vec", &[ty]),
+        Type::Set(ty) => generic("Set", &[ty]),
+        Type::Map(k_ty, v_ty) => generic("Map", &[k_ty, v_ty]),
+        Type::Optional(ty) => generic("Option", &[ty]),
+        Type::Result(ok_ty, err_ty) => generic("Result", &[ok_ty, err_ty]),
+    }
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fmt_ty() {
+        let ty = Type::Result(
+            Box::new(Type::List(Box::new(Type::Defined {
+                namespace: None,
+                ty: "DefTy".to_string(),
+            }))),
+            Box::new(Type::Defined {
+                namespace: Some("other".to_string()),
+                ty: "Error".to_string(),
+            }),
+        );
+        assert_eq!(
+            fmt_ty(&ty, Format::Markdown),
+            "Result&lt;Vec&lt;[DefTy](#defty)&gt;, [other::Error](other.md#error)&gt;"
+        );
+        assert_eq!(
+            fmt_ty(&ty, Format::Html),
+            "Result&lt;Vec&lt;<a href=\"#type.DefTy\">DefTy</a>&gt;, \
+             <a href=\"other.html#type.Error\">other::Error</a>&gt;"
+        );
+    }
+
+    #[test]
+    fn test_fixture() {
+        let iface = crate::testing::test_service();
+
+        let md = markdown(&iface);
+        assert!(md.starts_with("# TestService\n\nA service that exercises IDL generation.\n"));
+        assert!(md.contains(
+            "| `to` | address | the recipient |\n| `amount` | u64 | the number of tokens |"
+        ));
+        assert!(md.contains("| `indexed1` | u32 | no |  |"));
+
+        let html = html(&iface);
+        assert!(html.contains("<h3 id=\"type.DefTy\"><code>DefTy</code></h3>"));
+        assert!(html.contains("<p>The errors returned by `transfer`.</p>"));
+    }
+}
@@ -0,0 +1,332 @@
+//! Exports the types and functions of a service as a [JSON Schema](https://json-schema.org).
+//!
+//! The schema describes the JSON representation of values used by `oasis_rpc::codec`.
+//! Type definitions are placed in `definitions`. The inputs, outputs, and errors of the
+//! constructor and RPC functions are placed in `constructor` and `functions`, respectively,
+//! from which they can be referenced using, for instance, `#/functions/transfer/input`.
+//! Types defined by an imported service are referenced from its schema, `<namespace>.json`.
+
+use serde_json::{json, Map, Value};
+
+use crate::{Field, Interface, Type, TypeDef, Variant};
+
+const BYTES_PATTERN: &str = "^(0x)?([0-9a-fA-F]{2})*$";
+const ADDRESS_PATTERN: &str = "^(0x)?[0-9a-fA-F]{40}$";
+const U256_PATTERN: &str = "^([0-9]+|0x[0-9a-fA-F]{1,64})$";
+
+pub fn generate(iface: &Interface) -> Value {
+    let definitions: Map<String, Value> = iface
+        .type_defs
+        .iter()
+        .map(|def| {
+            let def_schema = with_docs(type_def_schema(def), def.docs());
+            (def.name().to_string(), def_schema)
+        })
+        .collect();
+
+    let mut constructor = Map::new();
+    constructor.insert(
+        "input".to_string(),
+        inputs_schema(&iface.constructor.inputs),
+    );
+    if let Some(err_ty) = &iface.constructor.error {
+        constructor.insert("error".to_string(), schema(err_ty));
+    }
+
+    let functions: Map<String, Value> = iface
+        .functions
+        .iter()
+        .map(|func| {
+            let mut func_schema = Map::new();
+            if let Some(docs) = &func.docs {
+                func_schema.insert("description".to_string(), json!(docs));
+            }
+            func_schema.insert("input".to_string(), inputs_schema(&func.inputs));
+            let (output_ty, err_ty) = super::split_output(&func.output);
+            func_schema.insert(
+                "output".to_string(),
+                output_ty
+                    .map(schema)
+                    .unwrap_or_else(|| json!({ "type": "null" })),
+            );
+            if let Some(err_ty) = err_ty {
+                func_schema.insert("error".to_string(), schema(err_ty));
+            }
+            (func.name.clone(), Value::Object(func_schema))
+        })
+        .collect();
+
+    let mut root = json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "$id": format!("{}.json", iface.namespace),
+        "title": iface.name,
+        "description": iface.docs.clone().unwrap_or_else(|| {
+            format!("Types and functions of {} v{}", iface.name, iface.version)
+        }),
+        "definitions": definitions,
+        "constructor": constructor,
+        "functions": functions,
+    });
+    if let Some(migration) = &iface.migration {
+        let mut migration_schema = Map::new();
+        migration_schema.insert("input".to_string(), inputs_schema(&migration.inputs));
+        if let Some(err_ty) = &migration.error {
+            migration_schema.insert("error".to_string(), schema(err_ty));
+        }
+        root["migration"] = Value::Object(migration_schema);
+    }
+    root
+}
+
+/// Returns the schema of the arguments of a function, which are passed as an array.
+fn inputs_schema(inputs: &[Field]) -> Value {
+    let items: Vec<Value> = inputs
+        .iter()
+        .map(|inp| {
+            let mut item = with_docs(schema(&inp.ty), inp.docs.as_ref().map(String::as_str));
+            item["title"] = json!(inp.name);
+            item
+        })
+        .collect();
+    tuple_schema(items)
+}
+
+fn type_def_schema(def: &TypeDef) -> Value {
+    match def {
+        TypeDef::Enum { variants, .. } => {
+            let (units, payloads): (Vec<_>, Vec<_>) = variants.iter().partition(|v| match v {
+                Variant::Unit(_) => true,
+                _ => false,
+            });
+            let unit_schema = json!({
+                "type": "string",
+                "enum": units.iter().map(|v| v.name()).collect::<Vec<_>>(),
+            });
+            if payloads.is_empty() {
+                return unit_schema;
+            }
+            let mut schemas: Vec<Value> = payloads
+                .into_iter()
+                .map(|v| object_schema(std::iter::once((v.name(), variant_schema(v)))))
+                .collect();
+            if !units.is_empty() {
+                schemas.insert(0, unit_schema);
+            }
+            json!({ "oneOf": schemas })
+        }
+        TypeDef::Struct { fields, .. }
+            if crate::is_tuple_struct(fields.iter().map(|f| f.name.as_str())) =>
+        {
+            if let [field] = fields.as_slice() {
+                return schema(&field.ty); // newtypes are transparent
+            }
+            tuple_schema(fields.iter().map(|f| schema(&f.ty)).collect())
+        }
+        TypeDef::Struct { fields, .. } => object_schema(fields.iter().map(|f| {
+            let field_schema = with_docs(schema(&f.ty), f.docs.as_ref().map(String::as_str));
+            (f.name.as_str(), field_schema)
+        })),
+        TypeDef::Event { fields, .. } => object_schema(fields.iter().map(|f| {
+            let docs = match (&f.docs, f.indexed) {
+                (Some(docs), true) => Some(format!("{} (indexed)", docs)),
+                (Some(docs), false) => Some(docs.to_string()),
+                (None, true) => Some("indexed".to_string()),
+                (None, false) => None,
+            };
+            (
+                f.name.as_str(),
+                with_docs(schema(&f.ty), docs.as_ref().map(String::as_str)),
+            )
+        })),
+    }
+}
+
+/// Sets the `description` of `schema` to `docs`, if any.
+fn with_docs(mut schema: Value, docs: Option<&str>) -> Value {
+    if let Some(docs) = docs {
+        schema["description"] = json!(docs);
+    }
+    schema
+}
+
+/// Returns the schema of the payload of a data-carrying enum variant.
+fn variant_schema(variant: &Variant) -> Value {
+    match variant {
+        Variant::Unit(_) => json!({ "type": "null" }),
+        Variant::Tuple { items, .. } => match items.as_slice() {
+            [item] => schema(item),
+            _ => tuple_schema(items.iter().map(schema).collect()),
+        },
+        Variant::Struct { fields, .. } => {
+            object_schema(fields.iter().map(|f| (f.name.as_str(), schema(&f.ty))))
+        }
+    }
+}
+
+fn object_schema<'a>(fields: impl Iterator<Item = (&'a str, Value)>) -> Value {
+    let (names, props): (Vec<_>, Map<_, _>) = fields
+        .map(|(name, field_schema)| (name, (name.to_string(), field_schema)))
+        .unzip();
+    json!({
+        "type": "object",
+        "properties": props,
+        "required": names,
+        "additionalProperties": false,
+    })
+}
+
+fn tuple_schema(items: Vec<Value>) -> Value {
+    json!({
+        "type": "array",
+        "minItems": items.len(),
+        "maxItems": items.len(),
+        "items": items,
+        "additionalItems": false,
+    })
+}
+
+/// Returns the schema of the JSON representation of `ty`.
+pub fn schema(ty: &Type) -> Value {
+    let int = |min: i64, max: u64| json!({ "type": "integer", "minimum": min, "maximum": max });
+    match ty {
+        Type::Bool => json!({ "type": "boolean" }),
+        Type::U8 => int(0, u8::max_value().into()),
+        Type::I8 => int(i8::min_value().into(), i8::max_value() as u64),
+        Type::U16 => int(0, u16::max_value().into()),
+        Type::I16 => int(i16::min_value().into(), i16::max_value() as u64),
+        Type::U32 => int(0, u32::max_value().into()),
+        Type::I32 => int(i32::min_value().into(), i32::max_value() as u64),
+        Type::U64 => int(0, u64::max_value()),
+        Type::I64 => int(i64::min_value(), i64::max_value() as u64),
+        // A 128-bit integer too large for a JSON number is a decimal string.
+        Type::U128 => json!({
+            "anyOf": [int(0, u64::max_value()), { "type": "string", "pattern": "^[0-9]{1,39}$" }]
+        }),
+        Type::I128 => json!({
+            "anyOf": [
+                int(i64::min_value(), u64::max_value()),
+                { "type": "string", "pattern": "^-?[0-9]{1,39}$" },
+            ]
+        }),
+        Type::U256 => json!({
+            "anyOf": [
+                { "type": "integer", "minimum": 0 },
+                { "type": "string", "pattern": U256_PATTERN },
+            ]
+        }),
+        Type::F32 | Type::F64 => json!({ "type": "number" }),
+        Type::String => json!({ "type": "string" }),
+        Type::Bytes => json!({
+            "type": "string",
+            "contentEncoding": "base16",
+            "pattern": BYTES_PATTERN,
+        }),
+        Type::Address => json!({
+            "type": "string",
+            "contentEncoding": "base16",
+            "pattern": ADDRESS_PATTERN,
+        }),
+        Type::Defined { namespace, ty } => match namespace {
+            Some(namespace) => json!({ "$ref": format!("{}.json#/definitions/{}", namespace, ty) }),
+            None => json!({ "$ref": format!("#/definitions/{}", ty) }),
+        },
+        Type::Tuple(tys) if tys.is_empty() => json!({ "type": "null" }),
+        Type::Tuple(tys) => tuple_schema(tys.iter().map(schema).collect()),
+        Type::Array(ty, len) => json!({
+            "type": "array",
+            "items": schema(ty),
+            "minItems": len,
+            "maxItems": len,
+        }),
+        Type::List(ty) => json!({ "type": "array", "items": schema(ty) }),
+        Type::Set(ty) => json!({ "type": "array", "items": schema(ty), "uniqueItems": true }),
+        Type::Map(k_ty, v_ty) if **k_ty == Type::String => json!({
+            "type": "object",
+            "additionalProperties": schema(v_ty),
+        }),
+        Type::Map(k_ty, v_ty) => json!({
+            "type": "array",
+            "items": tuple_schema(vec![schema(k_ty), schema(v_ty)]),
+        }),
+        Type::Optional(ty) => json!({ "anyOf": [{ "type": "null" }, schema(ty)] }),
+        Type::Result(ok_ty, err_ty) => json!({
+            "oneOf": [
+                object_schema(std::iter::once(("Ok", schema(ok_ty)))),
+                object_schema(std::iter::once(("Err", schema(err_ty)))),
+            ]
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schema() {
+        assert_eq!(
+            schema(&Type::Map(Box::new(Type::U8), Box::new(Type::Bool))),
+            json!({
+                "type": "array",
+                "items": {
+                    "type": "array",
+                    "minItems": 2,
+                    "maxItems": 2,
+                    "items": [
+                        { "type": "integer", "minimum": 0, "maximum": 255 },
+                        { "type": "boolean" },
+                    ],
+                    "additionalItems": false,
+                },
+            })
+        );
+        assert_eq!(
+            schema(&Type::Optional(Box::new(Type::Defined {
+                namespace: Some("other".to_string()),
+                ty: "Thing".to_string(),
+            }))),
+            json!({ "anyOf": [{ "type": "null" }, { "$ref": "other.json#/definitions/Thing" }] })
+        );
+    }
+
+    #[test]
+    fn test_generate() {
+        let iface = crate::testing::test_service();
+        let schema = generate(&iface);
+
+        assert_eq!(
+            schema["definitions"]["InnerTy"],
+            json!({ "type": "string", "enum": ["Field1", "Field2"] })
+        );
+        assert_eq!(
+            schema["definitions"]["DefTy"]["required"],
+            json!(["f1", "f2", "f3", "f4"])
+        );
+        assert_eq!(schema["definitions"]["TupleStruct"]["maxItems"], json!(3));
+        assert_eq!(
+            schema["definitions"]["Error"]["oneOf"][0],
+            json!({ "type": "string", "enum": ["Unknown"] })
+        );
+        assert_eq!(
+            schema["definitions"]["Error"]["oneOf"][2]["properties"]["Rejected"],
+            json!({ "type": "string" })
+        );
+        assert_eq!(
+            schema["constructor"]["input"]["items"][0]["$ref"],
+            json!("#/definitions/TupleStruct")
+        );
+        assert_eq!(schema["functions"]["void"]["input"]["maxItems"], json!(0));
+        assert_eq!(
+            schema["functions"]["void"]["output"],
+            json!({ "type": "null" })
+        );
+        assert_eq!(
+            schema["functions"]["the"]["error"],
+            json!({ "type": "null" })
+        );
+        assert_eq!(
+            schema["functions"]["the"]["output"]["items"]["pattern"],
+            json!(ADDRESS_PATTERN)
+        );
+    }
+}
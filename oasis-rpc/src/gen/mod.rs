@@ -0,0 +1,38 @@
+//! Generators of clients and types for services written in other languages
+//! and of their reference documentation.
+
+pub mod docs;
+pub mod json_schema;
+pub mod python;
+pub mod typescript;
+
+use crate::{Principal, Type};
+
+/// Returns the hex-encoded topic that identifies events of type `event_name`.
+/// This is the same topic that is emitted by the `Event` derive.
+pub fn event_topic(event_name: &str) -> String {
+    tiny_keccak::keccak256(event_name.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Returns the type of the successful output of an RPC and the type of its error, if any.
+pub(crate) fn split_output(output: &Option<Type>) -> (Option<&Type>, Option<&Type>) {
+    match output {
+        Some(Type::Result(ok_ty, err_ty)) => (Some(ok_ty), Some(err_ty)),
+        Some(ty) => (Some(ty), None),
+        None => (None, None),
+    }
+}
+
+/// Returns the English list of the callers permitted by a function's `only`.
+pub(crate) fn fmt_principals(only: &[Principal]) -> String {
+    only.iter()
+        .map(|principal| match principal {
+            Principal::Owner => "the owner".to_string(),
+            Principal::Role(role) => format!("accounts with the {} role", role),
+        })
+        .collect::<Vec<_>>()
+        .join(" or ")
+}
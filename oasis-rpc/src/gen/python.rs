@@ -0,0 +1,599 @@
+//! Generates a Python module containing the types and client of a service.
+//!
+//! The generated module embeds the service's interface, which drives the conversion of
+//! values to and from the CBOR produced by `serde_cbor` in the service. Apart from the
+//! modules of imported services, the generated module depends only on the standard library.
+
+use std::fmt::Write as _;
+
+use heck::CamelCase;
+
+use crate::{Field, Interface, StateMutability, Type, TypeDef, Variant};
+
+const PRELUDE: &str = include_str!("python_prelude.py");
+
+const KEYWORDS: &[&str] = &[
+    "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class", "continue",
+    "def", "del", "elif", "else", "except", "finally", "for", "from", "global", "if", "import",
+    "in", "is", "lambda", "nonlocal", "not", "or", "pass", "raise", "return", "try", "while",
+    "with", "yield",
+];
+
+pub fn generate(iface: &Interface) -> Result<String, serde_json::Error> {
+    let mut py = String::new();
+    writeln!(
+        py,
+        "# Generated by oasis-rpc from {} v{}. Do not edit.",
+        iface.name, iface.version
+    )
+    .unwrap();
+    writeln!(
+        py,
+        "\"\"\"Types and client of the `{}` service.\"\"\"",
+        iface.name
+    )
+    .unwrap();
+    writeln!(py).unwrap();
+    writeln!(py, "from __future__ import annotations").unwrap();
+    writeln!(py).unwrap();
+    for import in iface.imports.iter() {
+        writeln!(py, "import {}", import.name).unwrap();
+    }
+    py.push_str(PRELUDE);
+
+    writeln!(py).unwrap();
+    writeln!(py).unwrap();
+    writeln!(
+        py,
+        "INTERFACE = json.loads(r\"\"\"{}\"\"\")",
+        serde_json::to_string(iface)?
+    )
+    .unwrap();
+
+    for def in iface.type_defs.iter() {
+        writeln!(py).unwrap();
+        writeln!(py).unwrap();
+        gen_type_def(&mut py, def);
+    }
+
+    let classes: Vec<_> = iface
+        .type_defs
+        .iter()
+        .map(|def| format!("\"{0}\": {0}", def.name()))
+        .collect();
+    let imports: Vec<_> = iface
+        .imports
+        .iter()
+        .map(|import| format!("\"{0}\": {0}._MODULE", import.name))
+        .collect();
+    writeln!(py).unwrap();
+    writeln!(py).unwrap();
+    writeln!(
+        py,
+        "_MODULE = _Module(INTERFACE, {{{}}}, {{{}}})",
+        classes.join(", "),
+        imports.join(", ")
+    )
+    .unwrap();
+
+    gen_event_decoder(&mut py, &iface.type_defs);
+    gen_client(&mut py, iface);
+    Ok(py)
+}
+
+fn gen_type_def(py: &mut String, def: &TypeDef) {
+    let name = def.name();
+    match def {
+        TypeDef::Struct { fields, .. } => {
+            writeln!(py, "@dataclass").unwrap();
+            writeln!(py, "class {}:", name).unwrap();
+            let attrs = attr_docs(fields.iter().map(|f| (f.name.as_str(), &f.docs)));
+            gen_docstring(py, "    ", def.docs(), &attrs);
+            gen_fields(py, fields.iter().map(|f| (f.name.as_str(), &f.ty)));
+        }
+        TypeDef::Event { fields, .. } => {
+            writeln!(py, "@dataclass").unwrap();
+            writeln!(py, "class {}:", name).unwrap();
+            let attrs = attr_docs(fields.iter().map(|f| (f.name.as_str(), &f.docs)));
+            gen_docstring(py, "    ", def.docs(), &attrs);
+            writeln!(
+                py,
+                "    TOPIC: ClassVar[bytes] = bytes.fromhex(\"{}\")",
+                super::event_topic(name)
+            )
+            .unwrap();
+            writeln!(py).unwrap();
+            gen_fields(py, fields.iter().map(|f| (f.name.as_str(), &f.ty)));
+            writeln!(py).unwrap();
+            writeln!(py, "    @classmethod").unwrap();
+            writeln!(py, "    def decode(cls, data: bytes) -> {}:", name).unwrap();
+            writeln!(
+                py,
+                "        return _MODULE.decode_event(\"{}\", data)",
+                name
+            )
+            .unwrap();
+        }
+        TypeDef::Enum { variants, .. } => {
+            let unit_variants: Option<Vec<_>> = variants
+                .iter()
+                .map(|v| match v {
+                    Variant::Unit(name) => Some(name),
+                    _ => None,
+                })
+                .collect();
+            if let Some(unit_variants) = unit_variants {
+                writeln!(py, "class {}(enum.Enum):", name).unwrap();
+                gen_docstring(py, "    ", def.docs(), &[]);
+                for variant in unit_variants {
+                    writeln!(py, "    {} = \"{}\"", escape(variant, &[]), variant).unwrap();
+                }
+                return;
+            }
+            // Enums with payloads are represented by a dataclass per variant.
+            writeln!(py, "class {}:", name).unwrap();
+            gen_docstring(
+                py,
+                "    ",
+                def.docs(),
+                &[format!(
+                    "The variants of `{}` are its nested classes.",
+                    name
+                )],
+            );
+            for variant in variants.iter() {
+                let mut fields = String::new();
+                match variant {
+                    Variant::Unit(_) => gen_fields(&mut fields, std::iter::empty()),
+                    Variant::Tuple { items, .. } => {
+                        let names: Vec<_> = (0..items.len()).map(|i| i.to_string()).collect();
+                        gen_fields(
+                            &mut fields,
+                            names.iter().map(String::as_str).zip(items.iter()),
+                        )
+                    }
+                    Variant::Struct {
+                        fields: var_fields, ..
+                    } => gen_fields(
+                        &mut fields,
+                        var_fields.iter().map(|f| (f.name.as_str(), &f.ty)),
+                    ),
+                }
+                writeln!(py).unwrap();
+                writeln!(py, "    @dataclass").unwrap();
+                writeln!(py, "    class {}:", escape(variant.name(), &[])).unwrap();
+                for line in fields.lines() {
+                    writeln!(py, "    {}", line).unwrap();
+                }
+            }
+        }
+    }
+}
+
+fn gen_fields<'a>(py: &mut String, fields: impl Iterator<Item = (&'a str, &'a Type)>) {
+    let mut has_fields = false;
+    for (name, ty) in fields {
+        has_fields = true;
+        writeln!(py, "    {}: {}", attr_name(name), py_ty(ty)).unwrap();
+    }
+    if !has_fields {
+        writeln!(py, "    pass").unwrap();
+    }
+}
+
+fn gen_event_decoder(py: &mut String, defs: &[TypeDef]) {
+    let events: Vec<_> = defs
+        .iter()
+        .filter_map(|def| match def {
+            TypeDef::Event { name, .. } => Some(format!("{0}.TOPIC: {0}", name)),
+            _ => None,
+        })
+        .collect();
+    writeln!(py).unwrap();
+    writeln!(py, "EVENTS = {{{}}}", events.join(", ")).unwrap();
+    writeln!(py).unwrap();
+    writeln!(py).unwrap();
+    writeln!(py, "def decode_event(topic: bytes, data: bytes) -> Any:").unwrap();
+    writeln!(
+        py,
+        "    \"\"\"Decodes an event emitted by the service given its first topic.\"\"\""
+    )
+    .unwrap();
+    writeln!(py, "    return EVENTS[topic].decode(data)").unwrap();
+}
+
+fn gen_client(py: &mut String, iface: &Interface) {
+    let client_name = format!("{}Client", iface.name.to_camel_case());
+    writeln!(py).unwrap();
+    writeln!(py).unwrap();
+    writeln!(py, "class {}:", client_name).unwrap();
+    gen_docstring(py, "    ", iface.docs.as_ref().map(String::as_str), &[]);
+    writeln!(
+        py,
+        "    def __init__(self, transport: Transport, address: bytes):"
+    )
+    .unwrap();
+    writeln!(py, "        self.transport = transport").unwrap();
+    writeln!(py, "        self.address = address").unwrap();
+
+    let ctor_inputs = &iface.constructor.inputs;
+    writeln!(py).unwrap();
+    writeln!(py, "    @classmethod").unwrap();
+    writeln!(
+        py,
+        "    def deploy(cls, transport: Transport, bytecode: bytes{}, **options: Any) -> {}:",
+        params(ctor_inputs),
+        client_name
+    )
+    .unwrap();
+    let mut extra =
+        vec!["Deploys `bytecode` and returns a client for the new service.".to_string()];
+    extra.extend(arg_docs(ctor_inputs));
+    gen_docstring(
+        py,
+        "        ",
+        iface.constructor.docs.as_ref().map(String::as_str),
+        &extra,
+    );
+    writeln!(
+        py,
+        "        outcome = transport.deploy(bytecode, _MODULE.encode_deploy({}), **options)",
+        args_tuple(ctor_inputs)
+    )
+    .unwrap();
+    writeln!(
+        py,
+        "        if not outcome.success or outcome.address is None:"
+    )
+    .unwrap();
+    writeln!(py, "            raise RpcError(outcome.output)").unwrap();
+    writeln!(py, "        return cls(transport, outcome.address)").unwrap();
+
+    for func in iface.functions.iter() {
+        let (output_ty, _) = super::split_output(&func.output);
+        writeln!(py).unwrap();
+        writeln!(
+            py,
+            "    def {}(self{}, **options: Any) -> {}:",
+            method_name(&func.name),
+            params(&func.inputs),
+            output_ty.map(py_ty).unwrap_or_else(|| "None".to_string())
+        )
+        .unwrap();
+        let mut extra = Vec::new();
+        if let StateMutability::Immutable = func.mutability {
+            extra.push("Does not modify the state of the service.".to_string());
+        }
+        if func.payable {
+            extra.push("Accepts the value given as the `value` option.".to_string());
+        }
+        if !func.only.is_empty() {
+            extra.push(format!(
+                "Callable only by {}.",
+                super::fmt_principals(&func.only)
+            ));
+        }
+        if func.when_not_paused {
+            extra.push("Not callable while the service is paused.".to_string());
+        }
+        extra.extend(arg_docs(&func.inputs));
+        gen_docstring(
+            py,
+            "        ",
+            func.docs.as_ref().map(String::as_str),
+            &extra,
+        );
+        writeln!(
+            py,
+            "        return self._call(\"{}\", {}, options)",
+            func.name,
+            args_tuple(&func.inputs)
+        )
+        .unwrap();
+    }
+
+    writeln!(py).unwrap();
+    writeln!(
+        py,
+        "    def _call(self, method: str, args: Tuple[Any, ...], options: Dict[str, Any]) -> Any:"
+    )
+    .unwrap();
+    writeln!(py, "        input = _MODULE.encode_call(method, args)").unwrap();
+    writeln!(
+        py,
+        "        outcome = self.transport.transact(self.address, input, **options)"
+    )
+    .unwrap();
+    writeln!(py, "        return _MODULE.decode_output(method, outcome)").unwrap();
+}
+
+/// Writes a docstring containing `docs` followed by `extra` lines, if there are any.
+fn gen_docstring(py: &mut String, indent: &str, docs: Option<&str>, extra: &[String]) {
+    let separator = match (docs, extra.first()) {
+        (Some(_), Some(line)) if !line.is_empty() => Some(""),
+        _ => None,
+    };
+    let lines: Vec<_> = docs
+        .into_iter()
+        .flat_map(str::lines)
+        .chain(separator)
+        .chain(extra.iter().map(String::as_str))
+        .map(|line| line.replace('\\', "\\\\").replace('"', "\\\""))
+        .collect();
+    match lines.as_slice() {
+        [] => (),
+        [line] => writeln!(py, "{}\"\"\"{}\"\"\"", indent, line).unwrap(),
+        _ => {
+            writeln!(py, "{}\"\"\"{}", indent, lines[0]).unwrap();
+            for line in lines[1..].iter() {
+                if line.is_empty() {
+                    writeln!(py).unwrap();
+                } else {
+                    writeln!(py, "{}{}", indent, line).unwrap();
+                }
+            }
+            writeln!(py, "{}\"\"\"", indent).unwrap();
+        }
+    }
+}
+
+/// Returns the `Attributes:` section of the docstring of a class having the given fields.
+fn attr_docs<'a>(fields: impl Iterator<Item = (&'a str, &'a Option<String>)>) -> Vec<String> {
+    docs_section(
+        "Attributes:",
+        fields.map(|(name, docs)| (attr_name(name), docs)),
+    )
+}
+
+/// Returns the `Args:` section of the docstring of a method having the given inputs.
+fn arg_docs(inputs: &[Field]) -> Vec<String> {
+    docs_section(
+        "Args:",
+        inputs.iter().map(|inp| (param_name(&inp.name), &inp.docs)),
+    )
+}
+
+fn docs_section<'a>(
+    heading: &str,
+    items: impl Iterator<Item = (String, &'a Option<String>)>,
+) -> Vec<String> {
+    let items: Vec<_> = items
+        .filter_map(|(name, docs)| {
+            let docs = docs.as_ref()?;
+            Some(format!("    {}: {}", name, docs.replace('\n', " ")))
+        })
+        .collect();
+    if items.is_empty() {
+        return items;
+    }
+    let mut section = vec![String::new(), heading.to_string()];
+    section.extend(items);
+    section
+}
+
+fn params(inputs: &[Field]) -> String {
+    inputs
+        .iter()
+        .map(|inp| format!(", {}: {}", param_name(&inp.name), py_ty(&inp.ty)))
+        .collect()
+}
+
+fn args_tuple(inputs: &[Field]) -> String {
+    let args: Vec<_> = inputs.iter().map(|inp| param_name(&inp.name)).collect();
+    match args.as_slice() {
+        [] => "()".to_string(),
+        [arg] => format!("({},)", arg),
+        _ => format!("({})", args.join(", ")),
+    }
+}
+
+fn py_ty(ty: &Type) -> String {
+    match ty {
+        Type::Bool => "bool".to_string(),
+        Type::U8
+        | Type::I8
+        | Type::U16
+        | Type::I16
+        | Type::U32
+        | Type::I32
+        | Type::U64
+        | Type::I64
+        | Type::U128
+        | Type::I128
+        | Type::U256 => "int".to_string(),
+        Type::F32 | Type::F64 => "float".to_string(),
+        Type::Bytes | Type::Address => "bytes".to_string(),
+        Type::String => "str".to_string(),
+        Type::Defined { namespace, ty } => match namespace {
+            Some(namespace) => format!("{}.{}", namespace, ty),
+            None => ty.to_string(),
+        },
+        Type::Tuple(tys) if tys.is_empty() => "None".to_string(),
+        Type::Tuple(tys) => format!(
+            "Tuple[{}]",
+            tys.iter().map(py_ty).collect::<Vec<_>>().join(", ")
+        ),
+        Type::Array(ty, _) | Type::List(ty) => format!("List[{}]", py_ty(ty)),
+        Type::Set(ty) => format!("Set[{}]", py_ty(ty)),
+        Type::Map(k_ty, v_ty) => format!("Dict[{}, {}]", py_ty(k_ty), py_ty(v_ty)),
+        Type::Optional(ty) => format!("Optional[{}]", py_ty(ty)),
+        Type::Result(ok_ty, err_ty) => format!("Result[{}, {}]", py_ty(ok_ty), py_ty(err_ty)),
+    }
+}
+
+/// Returns the name of the dataclass attribute that holds the field `name`.
+/// Must agree with `_attr` in the prelude.
+fn attr_name(name: &str) -> String {
+    if name.chars().all(|ch| ch.is_ascii_digit()) {
+        format!("_{}", name)
+    } else {
+        escape(name, &[])
+    }
+}
+
+/// Returns the name of the client method that calls the RPC `name`.
+fn method_name(name: &str) -> String {
+    escape(name, &["address", "deploy", "transport", "_call"])
+}
+
+fn param_name(name: &str) -> String {
+    escape(name, &["bytecode", "cls", "options", "self", "transport"])
+}
+
+/// Appends an underscore to Python keywords and to the `reserved` names.
+fn escape(ident: &str, reserved: &[&str]) -> String {
+    if KEYWORDS.contains(&ident) || reserved.contains(&ident) {
+        format!("{}_", ident)
+    } else {
+        ident.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Generates a module for each interface in `tests/idl-gen/res` and checks, using Python,
+    /// that the module's interface matches the fixture and that values of every type
+    /// round-trip through the module's CBOR codec.
+    #[test]
+    fn test_fixtures() {
+        if std::process::Command::new("python3")
+            .arg("--version")
+            .output()
+            .is_err()
+        {
+            eprintln!("skipping `test_fixtures`: python3 not found");
+            return;
+        }
+
+        let res_dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/idl-gen/res");
+        let temp_dir = crate::testing::TempDir::new("gen-py");
+        let out_dir = temp_dir.path();
+
+        for entry in std::fs::read_dir(res_dir).unwrap() {
+            let fixture_path = entry.unwrap().path();
+            let iface: Interface =
+                serde_json::from_slice(&std::fs::read(&fixture_path).unwrap()).unwrap();
+            let mod_name = format!("gen_{}", iface.namespace);
+            std::fs::write(
+                out_dir.join(format!("{}.py", mod_name)),
+                generate(&iface).unwrap(),
+            )
+            .unwrap();
+
+            let output = std::process::Command::new("python3")
+                .arg("-c")
+                .arg(ROUND_TRIP_SCRIPT)
+                .arg(&out_dir)
+                .arg(&mod_name)
+                .arg(&fixture_path)
+                .output()
+                .unwrap();
+            assert!(
+                output.status.success(),
+                "{}: {}",
+                fixture_path.display(),
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+    }
+
+    const ROUND_TRIP_SCRIPT: &str = r#"
+import importlib, json, sys
+
+sys.path.insert(0, sys.argv[1])
+m = importlib.import_module(sys.argv[2])
+with open(sys.argv[3]) as f:
+    assert m.INTERFACE == json.load(f)
+
+SAMPLES = {
+    "bool": True, "u8": 7, "i8": -7, "u16": 7, "i16": -7, "u32": 7, "i32": -7,
+    "u64": 2**64 - 1, "i64": -2**63, "u128": 2**128 - 1, "i128": -2**127, "u256": 2**256 - 1,
+    "f32": 1.5, "f64": 0.1, "string": "s",
+    "bytes": b"\x01\x02", "address": bytes(range(20)),
+}
+
+# 128-bit integers are 16 big-endian bytes, in two's complement if signed.
+assert m._MODULE.encode({"type": "u128"}, 2**64) == (1).to_bytes(8, "big") + bytes(8)
+assert m._MODULE.encode({"type": "i128"}, -1) == b"\xff" * 16
+
+def sample(ty, depth=0):
+    kind, params = ty["type"], ty.get("params")
+    if kind in SAMPLES:
+        return SAMPLES[kind]
+    if kind == "defined":
+        mod, name = m._MODULE._resolve(params)
+        type_def, cls = mod.type_defs[name], mod.classes[name]
+        if type_def["type"] == "enum":
+            if isinstance(cls, m.enum.EnumMeta):
+                return list(cls)[-1]
+            return sample_variant(cls, type_def["variants"][0], depth)
+        return cls(*(sample(f["type"], depth + 1) for f in type_def["fields"]))
+    if kind == "tuple":
+        return tuple(sample(t, depth) for t in params) if params else None
+    if kind == "array":
+        return [sample(params[0], depth) for _ in range(params[1])]
+    if kind in ("list", "set"):
+        items = [sample(params, depth + 1)] if depth < 3 else []
+        return items if kind == "list" else set(items)
+    if kind == "map":
+        return {sample(params[0], depth + 1): sample(params[1], depth + 1)} if depth < 3 else {}
+    if kind == "optional":
+        return sample(params, depth + 1) if depth < 3 else None
+    if kind == "result":
+        return m.Ok(sample(params[0], depth))
+    raise ValueError(kind)
+
+def sample_variant(cls, variant, depth):
+    if isinstance(variant, str):
+        return getattr(cls, m._attr(variant))()
+    var_cls = getattr(cls, m._attr(variant["name"]))
+    if "items" in variant:
+        return var_cls(*(sample(t, depth + 1) for t in variant["items"]))
+    return var_cls(*(sample(f["type"], depth + 1) for f in variant["fields"]))
+
+def round_trip(ty, value):
+    encoded = m.cbor_encode(m._MODULE.encode(ty, value))
+    assert m._MODULE.decode(ty, m.cbor_decode(encoded)) == value, (ty, value)
+    return encoded
+
+for name, type_def in m._MODULE.type_defs.items():
+    ty = {"type": "defined", "params": {"type": name}}
+    round_trip(ty, sample(ty))
+    if type_def["type"] == "enum" and not isinstance(m._MODULE.classes[name], m.enum.EnumMeta):
+        for variant in type_def["variants"]:
+            round_trip(ty, sample_variant(m._MODULE.classes[name], variant, 0))
+
+for function in m.INTERFACE.get("functions", []):
+    inputs = function.get("inputs", [])
+    args = tuple(sample(inp["type"]) for inp in inputs)
+    call = m.cbor_decode(m._MODULE.encode_call(function["name"], args))
+    assert call["method"] == function["name"]
+    assert [m._MODULE.decode(inp["type"], raw) for inp, raw in zip(inputs, call["payload"])] == list(args)
+
+    output = function.get("output")
+    if output is None:
+        continue
+    if output["type"] == "result":
+        ok_ty, err_ty = output["params"]
+        err = sample(err_ty)
+        try:
+            m._MODULE.decode_output(function["name"], m.Outcome(False, round_trip(err_ty, err)))
+            raise AssertionError("expected RpcError")
+        except m.RpcError as e:
+            assert e.error == err
+        output = ok_ty
+    value = sample(output)
+    assert m._MODULE.decode_output(function["name"], m.Outcome(True, round_trip(output, value))) == value
+"#;
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(attr_name("0"), "_0");
+        assert_eq!(attr_name("from"), "from_");
+        assert_eq!(method_name("deploy"), "deploy_");
+        assert_eq!(param_name("self"), "self_");
+        assert_eq!(param_name("value"), "value");
+    }
+}
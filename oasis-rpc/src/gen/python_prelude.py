@@ -0,0 +1,341 @@
+import enum
+import json
+import keyword
+import struct
+from dataclasses import dataclass
+from typing import Any, ClassVar, Dict, Generic, List, Optional, Set, Tuple, TypeVar, Union
+
+T = TypeVar("T")
+E = TypeVar("E")
+
+
+@dataclass
+class Ok(Generic[T]):
+    value: T
+
+
+@dataclass
+class Err(Generic[E]):
+    error: E
+
+
+Result = Union[Ok[T], Err[E]]
+
+
+@dataclass
+class Outcome:
+    """The result of a transaction. `output` holds the encoded error if `success` is false."""
+
+    success: bool
+    output: bytes
+    address: Optional[bytes] = None  # set when a service is deployed
+
+
+class Transport:
+    """The connection to a gateway or node through which transactions are sent."""
+
+    def transact(self, address: bytes, input: bytes, **options: Any) -> Outcome:
+        raise NotImplementedError
+
+    def deploy(self, bytecode: bytes, input: bytes, **options: Any) -> Outcome:
+        raise NotImplementedError
+
+
+class RpcError(Exception):
+    """Raised when a service returns an error."""
+
+    def __init__(self, error: Any):
+        super().__init__(error)
+        self.error = error
+
+
+def cbor_encode(value: Any) -> bytes:
+    out = bytearray()
+    _cbor_write(out, value)
+    return bytes(out)
+
+
+def cbor_decode(data: bytes) -> Any:
+    value, offset = _cbor_read(data, 0)
+    if offset != len(data):
+        raise ValueError("trailing bytes after CBOR value")
+    return value
+
+
+def _cbor_write_head(out: bytearray, major: int, arg: int) -> None:
+    if arg < 24:
+        out.append(major << 5 | arg)
+    elif arg < 1 << 8:
+        out += struct.pack(">BB", major << 5 | 24, arg)
+    elif arg < 1 << 16:
+        out += struct.pack(">BH", major << 5 | 25, arg)
+    elif arg < 1 << 32:
+        out += struct.pack(">BI", major << 5 | 26, arg)
+    else:
+        out += struct.pack(">BQ", major << 5 | 27, arg)
+
+
+def _cbor_write(out: bytearray, value: Any) -> None:
+    if value is None:
+        out.append(0xF6)
+    elif value is True:
+        out.append(0xF5)
+    elif value is False:
+        out.append(0xF4)
+    elif isinstance(value, int):
+        if value >= 0:
+            _cbor_write_head(out, 0, value)
+        else:
+            _cbor_write_head(out, 1, -1 - value)
+    elif isinstance(value, float):
+        out += struct.pack(">Bd", 0xFB, value)
+    elif isinstance(value, (bytes, bytearray)):
+        _cbor_write_head(out, 2, len(value))
+        out += value
+    elif isinstance(value, str):
+        encoded = value.encode("utf-8")
+        _cbor_write_head(out, 3, len(encoded))
+        out += encoded
+    elif isinstance(value, (list, tuple)):
+        _cbor_write_head(out, 4, len(value))
+        for item in value:
+            _cbor_write(out, item)
+    elif isinstance(value, dict):
+        _cbor_write_head(out, 5, len(value))
+        for k, v in value.items():
+            _cbor_write(out, k)
+            _cbor_write(out, v)
+    else:
+        raise TypeError(f"cannot CBOR-encode {type(value).__name__}")
+
+
+def _cbor_read(data: bytes, offset: int) -> Tuple[Any, int]:
+    initial = data[offset]
+    major, info = initial >> 5, initial & 0x1F
+    offset += 1
+    if major == 7:
+        if info == 20:
+            return False, offset
+        if info == 21:
+            return True, offset
+        if info in (22, 23):
+            return None, offset
+        fmt = {25: ">e", 26: ">f", 27: ">d"}.get(info)
+        if fmt is None:
+            raise ValueError(f"unsupported CBOR simple value {info}")
+        size = struct.calcsize(fmt)
+        return struct.unpack_from(fmt, data, offset)[0], offset + size
+    if info < 24:
+        arg = info
+    elif info <= 27:
+        size = 1 << (info - 24)
+        arg = int.from_bytes(data[offset : offset + size], "big")
+        offset += size
+    else:
+        raise ValueError("indefinite-length CBOR items are not supported")
+    if major == 0:
+        return arg, offset
+    if major == 1:
+        return -1 - arg, offset
+    if major == 2:
+        return bytes(data[offset : offset + arg]), offset + arg
+    if major == 3:
+        return data[offset : offset + arg].decode("utf-8"), offset + arg
+    if major == 4:
+        items = []
+        for _ in range(arg):
+            item, offset = _cbor_read(data, offset)
+            items.append(item)
+        return items, offset
+    if major == 5:
+        entries = {}
+        for _ in range(arg):
+            k, offset = _cbor_read(data, offset)
+            v, offset = _cbor_read(data, offset)
+            entries[_hashable(k)] = v
+        return entries, offset
+    return _cbor_read(data, offset)  # major 6: ignore the tag
+
+
+def _hashable(value: Any) -> Any:
+    if isinstance(value, list):
+        return tuple(_hashable(v) for v in value)
+    if isinstance(value, dict):
+        return tuple((k, _hashable(v)) for k, v in value.items())
+    return value
+
+
+def _attr(field_name: str) -> str:
+    """Returns the name of the dataclass attribute that holds the field `field_name`."""
+    if field_name.isdigit():
+        return "_" + field_name
+    if keyword.iskeyword(field_name):
+        return field_name + "_"
+    return field_name
+
+
+def _is_tuple_struct(fields: List[Dict[str, Any]]) -> bool:
+    return len(fields) > 0 and all(f["name"] == str(i) for i, f in enumerate(fields))
+
+
+class _Module:
+    """The type definitions of a service interface and the classes that represent them."""
+
+    def __init__(self, interface: Dict[str, Any], classes: Dict[str, type], imports: Dict[str, "_Module"]):
+        self.interface = interface
+        self.type_defs = {d["name"]: d for d in interface.get("type_defs", [])}
+        self.classes = classes
+        self.imports = imports
+
+    def _resolve(self, params: Dict[str, Any]) -> Tuple["_Module", str]:
+        namespace = params.get("namespace")
+        module = self if namespace is None else self.imports[namespace]
+        return module, params["type"]
+
+    def encode(self, ty: Dict[str, Any], value: Any) -> Any:
+        kind, params = ty["type"], ty.get("params")
+        if kind in ("bytes", "address"):
+            return list(value)
+        if kind in ("u128", "i128"):
+            return value.to_bytes(16, "big", signed=kind == "i128")
+        if kind == "u256":
+            return value.to_bytes(32, "big")
+        if kind == "defined":
+            module, name = self._resolve(params)
+            return module._encode_defined(name, value)
+        if kind == "optional":
+            return None if value is None else self.encode(params, value)
+        if kind in ("list", "set"):
+            return [self.encode(params, v) for v in value]
+        if kind == "array":
+            return [self.encode(params[0], v) for v in value]
+        if kind == "tuple":
+            return [self.encode(t, v) for t, v in zip(params, value)] if params else None
+        if kind == "map":
+            return {_hashable(self.encode(params[0], k)): self.encode(params[1], v) for k, v in value.items()}
+        if kind == "result":
+            if isinstance(value, Ok):
+                return {"Ok": self.encode(params[0], value.value)}
+            return {"Err": self.encode(params[1], value.error)}
+        return value
+
+    def decode(self, ty: Dict[str, Any], raw: Any) -> Any:
+        kind, params = ty["type"], ty.get("params")
+        if kind in ("bytes", "address"):
+            return bytes(raw)
+        if kind in ("u128", "i128"):
+            return int.from_bytes(raw, "big", signed=kind == "i128")
+        if kind == "u256":
+            return int.from_bytes(raw, "big")
+        if kind == "defined":
+            module, name = self._resolve(params)
+            return module._decode_defined(name, raw)
+        if kind == "optional":
+            return None if raw is None else self.decode(params, raw)
+        if kind == "list":
+            return [self.decode(params, r) for r in raw]
+        if kind == "set":
+            return {self.decode(params, r) for r in raw}
+        if kind == "array":
+            return [self.decode(params[0], r) for r in raw]
+        if kind == "tuple":
+            return tuple(self.decode(t, r) for t, r in zip(params, raw)) if params else None
+        if kind == "map":
+            return {self.decode(params[0], k): self.decode(params[1], v) for k, v in raw.items()}
+        if kind == "result":
+            if "Ok" in raw:
+                return Ok(self.decode(params[0], raw["Ok"]))
+            return Err(self.decode(params[1], raw["Err"]))
+        if kind in ("f32", "f64"):
+            return float(raw)
+        return raw
+
+    def _encode_defined(self, name: str, value: Any) -> Any:
+        type_def = self.type_defs[name]
+        if type_def["type"] == "enum":
+            if isinstance(value, enum.Enum):
+                return value.value
+            return self._encode_variant(name, value)
+        fields = type_def["fields"]
+        encoded = [self.encode(f["type"], getattr(value, _attr(f["name"]))) for f in fields]
+        if not _is_tuple_struct(fields):
+            return {f["name"]: v for f, v in zip(fields, encoded)}
+        return encoded[0] if len(encoded) == 1 else encoded  # newtypes are transparent
+
+    def _decode_defined(self, name: str, raw: Any) -> Any:
+        type_def, cls = self.type_defs[name], self.classes[name]
+        if type_def["type"] == "enum":
+            if isinstance(cls, enum.EnumMeta):
+                return cls(raw)
+            return self._decode_variant(name, raw)
+        fields = type_def["fields"]
+        if not _is_tuple_struct(fields):
+            raw = dict(raw)  # map keys are decoded as tuples of entries
+            raw = [raw[f["name"]] for f in fields]
+        elif len(fields) == 1:
+            raw = [raw]
+        return cls(*(self.decode(f["type"], r) for f, r in zip(fields, raw)))
+
+    def _variant(self, name: str, variant_name: str) -> Tuple[Dict[str, Any], type]:
+        """Returns the definition of a variant of the enum `name` and the class that represents it."""
+        for variant in self.type_defs[name]["variants"]:
+            if isinstance(variant, str):
+                variant = {"name": variant}
+            if variant["name"] == variant_name:
+                return variant, getattr(self.classes[name], _attr(variant_name))
+        raise ValueError(f"`{name}` has no variant `{variant_name}`")
+
+    def _encode_variant(self, name: str, value: Any) -> Any:
+        for variant in self.type_defs[name]["variants"]:
+            if isinstance(variant, str):
+                variant = {"name": variant}
+            if type(value) is getattr(self.classes[name], _attr(variant["name"])):
+                break
+        else:
+            raise TypeError(f"{value!r} is not a variant of `{name}`")
+        if "items" in variant:
+            items = variant["items"]
+            encoded = [self.encode(t, getattr(value, f"_{i}")) for i, t in enumerate(items)]
+            payload = encoded[0] if len(encoded) == 1 else encoded
+        elif "fields" in variant:
+            payload = {f["name"]: self.encode(f["type"], getattr(value, _attr(f["name"]))) for f in variant["fields"]}
+        else:
+            return variant["name"]  # unit variants are encoded as their names
+        return {variant["name"]: payload}
+
+    def _decode_variant(self, name: str, raw: Any) -> Any:
+        if isinstance(raw, str):
+            return self._variant(name, raw)[1]()
+        ((variant_name, payload),) = raw.items()
+        variant, cls = self._variant(name, variant_name)
+        if "items" in variant:
+            items = variant["items"]
+            payload = [payload] if len(items) == 1 else payload
+            return cls(*(self.decode(t, r) for t, r in zip(items, payload)))
+        payload = dict(payload)  # map keys are decoded as tuples of entries
+        return cls(*(self.decode(f["type"], payload[f["name"]]) for f in variant["fields"]))
+
+    def encode_call(self, method: str, args: Tuple[Any, ...]) -> bytes:
+        function = next(f for f in self.interface.get("functions", []) if f["name"] == method)
+        inputs = function.get("inputs", [])
+        payload = [self.encode(inp["type"], arg) for inp, arg in zip(inputs, args)]
+        return cbor_encode({"method": method, "payload": payload})
+
+    def decode_output(self, method: str, outcome: Outcome) -> Any:
+        function = next(f for f in self.interface.get("functions", []) if f["name"] == method)
+        output = function.get("output")
+        is_result = output is not None and output["type"] == "result"
+        if not outcome.success:
+            if is_result:
+                raise RpcError(self.decode(output["params"][1], cbor_decode(outcome.output)))
+            raise RpcError(outcome.output)
+        if output is None:
+            return None
+        return self.decode(output["params"][0] if is_result else output, cbor_decode(outcome.output))
+
+    def encode_deploy(self, args: Tuple[Any, ...]) -> bytes:
+        inputs = self.interface["constructor"]["inputs"]
+        return cbor_encode([self.encode(inp["type"], arg) for inp, arg in zip(inputs, args)])
+
+    def decode_event(self, name: str, data: bytes) -> Any:
+        return self._decode_defined(name, cbor_decode(data))
@@ -0,0 +1,156 @@
+// Generated by oasis-rpc from NonDefaultFnService v0.1.0. Do not edit.
+
+import { Decoder, Encoder } from 'cbor-x';
+
+export type Address = Uint8Array;
+
+/** An unsigned or signed 64-bit integer. Decoded as a `bigint` only when it exceeds 2^53. */
+export type Int64 = number | bigint;
+
+/** An unsigned or signed 128-bit integer. Decoded as a `bigint` only when it exceeds 2^53. */
+export type Int128 = number | bigint;
+
+export interface CallOptions {
+  value?: Int64;
+  gas?: Int64;
+}
+
+/** The result of a transaction. `output` holds the encoded error if `success` is false. */
+export interface Outcome {
+  success: boolean;
+  output: Uint8Array;
+}
+
+/** The connection to a gateway or node through which transactions are sent. */
+export interface Transport {
+  transact(address: Address, input: Uint8Array, options?: CallOptions): Promise<Outcome>;
+  deploy(
+    bytecode: Uint8Array,
+    input: Uint8Array,
+    options?: CallOptions,
+  ): Promise<Outcome & { address?: Address }>;
+}
+
+/** Thrown when a service returns an error. */
+export class RpcError<E = Uint8Array> extends Error {
+  constructor(public readonly error: E) {
+    super(`RPC returned error: ${String(error)}`);
+  }
+}
+
+const encoder = new Encoder({ useRecords: false, mapsAsObjects: false });
+const decoder = new Decoder({ useRecords: false, mapsAsObjects: false });
+
+export function encode(value: any): Uint8Array {
+  return encoder.encode(value);
+}
+
+export function decode(bytes: Uint8Array): any {
+  return decoder.decode(bytes);
+}
+
+function encodeBytes(bytes: Uint8Array): number[] {
+  return Array.from(bytes);
+}
+
+function decodeBytes(raw: number[]): Uint8Array {
+  return Uint8Array.from(raw);
+}
+
+function encodeOptional<T>(value: T | null, encodeValue: (v: T) => any): any {
+  return value === null ? null : encodeValue(value);
+}
+
+function decodeOptional<T>(raw: any, decodeValue: (r: any) => T): T | null {
+  return raw === null || raw === undefined ? null : decodeValue(raw);
+}
+
+function encodeMap<K, V>(map: Map<K, V>, encodeKey: (k: K) => any, encodeValue: (v: V) => any) {
+  return new Map(Array.from(map, ([k, v]) => [encodeKey(k), encodeValue(v)]));
+}
+
+function decodeMap<K, V>(raw: Map<any, any>, decodeKey: (r: any) => K, decodeValue: (r: any) => V) {
+  return new Map(Array.from(raw, ([k, v]) => [decodeKey(k), decodeValue(v)]));
+}
+
+/** Encodes an integer as `size` big-endian bytes, in two's complement if it is negative. */
+function encodeBigInt(value: Int128, size: number): Uint8Array {
+  const bytes = new Uint8Array(size);
+  let v = BigInt.asUintN(size * 8, BigInt(value));
+  for (let i = size - 1; i >= 0; i--, v >>= BigInt(8)) {
+    bytes[i] = Number(v & BigInt(0xff));
+  }
+  return bytes;
+}
+
+function decodeBigUint(raw: Uint8Array): bigint {
+  return raw.reduce((v, byte) => (v << BigInt(8)) | BigInt(byte), BigInt(0));
+}
+
+function decodeInt128(raw: Uint8Array, signed: boolean): Int128 {
+  const v = signed ? BigInt.asIntN(128, decodeBigUint(raw)) : decodeBigUint(raw);
+  return Number.isSafeInteger(Number(v)) ? Number(v) : v;
+}
+
+function hexToBytes(hex: string): Uint8Array {
+  return Uint8Array.from(hex.match(/../g)!.map((byte) => parseInt(byte, 16)));
+}
+
+export class NonDefaultFnServiceClient {
+  constructor(public readonly transport: Transport, public readonly address: Address) {}
+
+  /** Deploys `bytecode` and returns a client for the new service. */
+  static async deploy(transport: Transport, bytecode: Uint8Array, options?: CallOptions): Promise<NonDefaultFnServiceClient> {
+    const input = encode([]);
+    const outcome = await transport.deploy(bytecode, input, options);
+    if (!outcome.success || !outcome.address) {
+      throw new RpcError(outcome.output);
+    }
+    return new NonDefaultFnServiceClient(transport, outcome.address);
+  }
+
+  /** Returns a builder of a batch of calls to this service. */
+  batch(): NonDefaultFnServiceBatch {
+    return new NonDefaultFnServiceBatch(this);
+  }
+
+  /** Does not modify the state of the service. */
+  async default(options?: CallOptions): Promise<void> {
+    const input = encode({ method: 'default', payload: [] });
+    const outcome = await this.transport.transact(this.address, input, options);
+    if (!outcome.success) {
+      throw new RpcError(outcome.output);
+    }
+  }
+}
+
+/**
+ * Collects calls to the service that are sent in one transaction.
+ * The calls take effect in order and together or, if any of them fails, not at all.
+ */
+export class NonDefaultFnServiceBatch {
+  private readonly calls: Uint8Array[] = [];
+  private readonly decoders: [(raw: any) => any, (raw: any) => any][] = [];
+
+  constructor(public readonly client: NonDefaultFnServiceClient) {}
+
+  default(): this {
+    this.calls.push(encode({ method: 'default', payload: [] }));
+    this.decoders.push([(raw) => undefined, (raw) => raw]);
+    return this;
+  }
+
+  /**
+   * Sends the batched calls and returns their outputs in order.
+   * If a call fails, the `RpcError` holds its `index` and its `error`.
+   */
+  async send(options?: CallOptions): Promise<any[]> {
+    const input = encode([0, this.calls]);
+    const outcome = await this.client.transport.transact(this.client.address, input, options);
+    if (!outcome.success) {
+      throw new RpcError(outcome.output);
+    }
+    const outputs: Uint8Array[] = decode(outcome.output);
+    return outputs.map((output, i) => this.decoders[i][0](decode(output)));
+  }
+}
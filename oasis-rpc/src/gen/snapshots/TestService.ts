@@ -0,0 +1,389 @@
+// Generated by oasis-rpc from TestService v0.1.0. Do not edit.
+
+import { Decoder, Encoder } from 'cbor-x';
+
+export type Address = Uint8Array;
+
+/** An unsigned or signed 64-bit integer. Decoded as a `bigint` only when it exceeds 2^53. */
+export type Int64 = number | bigint;
+
+/** An unsigned or signed 128-bit integer. Decoded as a `bigint` only when it exceeds 2^53. */
+export type Int128 = number | bigint;
+
+export interface CallOptions {
+  value?: Int64;
+  gas?: Int64;
+}
+
+/** The result of a transaction. `output` holds the encoded error if `success` is false. */
+export interface Outcome {
+  success: boolean;
+  output: Uint8Array;
+}
+
+/** The connection to a gateway or node through which transactions are sent. */
+export interface Transport {
+  transact(address: Address, input: Uint8Array, options?: CallOptions): Promise<Outcome>;
+  deploy(
+    bytecode: Uint8Array,
+    input: Uint8Array,
+    options?: CallOptions,
+  ): Promise<Outcome & { address?: Address }>;
+}
+
+/** Thrown when a service returns an error. */
+export class RpcError<E = Uint8Array> extends Error {
+  constructor(public readonly error: E) {
+    super(`RPC returned error: ${String(error)}`);
+  }
+}
+
+const encoder = new Encoder({ useRecords: false, mapsAsObjects: false });
+const decoder = new Decoder({ useRecords: false, mapsAsObjects: false });
+
+export function encode(value: any): Uint8Array {
+  return encoder.encode(value);
+}
+
+export function decode(bytes: Uint8Array): any {
+  return decoder.decode(bytes);
+}
+
+function encodeBytes(bytes: Uint8Array): number[] {
+  return Array.from(bytes);
+}
+
+function decodeBytes(raw: number[]): Uint8Array {
+  return Uint8Array.from(raw);
+}
+
+function encodeOptional<T>(value: T | null, encodeValue: (v: T) => any): any {
+  return value === null ? null : encodeValue(value);
+}
+
+function decodeOptional<T>(raw: any, decodeValue: (r: any) => T): T | null {
+  return raw === null || raw === undefined ? null : decodeValue(raw);
+}
+
+function encodeMap<K, V>(map: Map<K, V>, encodeKey: (k: K) => any, encodeValue: (v: V) => any) {
+  return new Map(Array.from(map, ([k, v]) => [encodeKey(k), encodeValue(v)]));
+}
+
+function decodeMap<K, V>(raw: Map<any, any>, decodeKey: (r: any) => K, decodeValue: (r: any) => V) {
+  return new Map(Array.from(raw, ([k, v]) => [decodeKey(k), decodeValue(v)]));
+}
+
+/** Encodes an integer as `size` big-endian bytes, in two's complement if it is negative. */
+function encodeBigInt(value: Int128, size: number): Uint8Array {
+  const bytes = new Uint8Array(size);
+  let v = BigInt.asUintN(size * 8, BigInt(value));
+  for (let i = size - 1; i >= 0; i--, v >>= BigInt(8)) {
+    bytes[i] = Number(v & BigInt(0xff));
+  }
+  return bytes;
+}
+
+function decodeBigUint(raw: Uint8Array): bigint {
+  return raw.reduce((v, byte) => (v << BigInt(8)) | BigInt(byte), BigInt(0));
+}
+
+function decodeInt128(raw: Uint8Array, signed: boolean): Int128 {
+  const v = signed ? BigInt.asIntN(128, decodeBigUint(raw)) : decodeBigUint(raw);
+  return Number.isSafeInteger(Number(v)) ? Number(v) : v;
+}
+
+function hexToBytes(hex: string): Uint8Array {
+  return Uint8Array.from(hex.match(/../g)!.map((byte) => parseInt(byte, 16)));
+}
+
+export type InnerTy = 'Field1' | 'Field2';
+
+export function encodeInnerTy(value: InnerTy): any {
+  return value;
+}
+
+export function decodeInnerTy(raw: any): InnerTy {
+  return raw;
+}
+
+export interface TestEvent {
+  indexed: DefTy;
+  non_indexed: [number, number];
+}
+
+export function encodeTestEvent(value: TestEvent): any {
+  return {
+    indexed: encodeDefTy(value.indexed),
+    non_indexed: value.non_indexed,
+  };
+}
+
+export function decodeTestEvent(raw: any): TestEvent {
+  return {
+    indexed: decodeDefTy(raw.get('indexed')),
+    non_indexed: raw.get('non_indexed'),
+  };
+}
+
+export const TestEventTopic = hexToBytes('e8f134cb39cbc51b2dd43af32059dcd508e608e59c8050a30ca3bdd623bd84c5');
+
+/** Decodes the data of a `TestEvent` event. */
+export function parseTestEvent(data: Uint8Array): TestEvent {
+  return decodeTestEvent(decode(data));
+}
+
+/** A user-defined type. */
+export interface DefTy {
+  /** An optional integer. */
+  f1: Int64 | null;
+  f2: Array<DefTy | null>;
+  f3: Map<string, InnerTy>;
+  f4: [Address, Int64, Address];
+}
+
+export function encodeDefTy(value: DefTy): any {
+  return {
+    f1: value.f1,
+    f2: value.f2.map((v0) => encodeOptional(v0, (v1) => encodeDefTy(v1))),
+    f3: encodeMap(value.f3, (k0) => k0, (v0) => encodeInnerTy(v0)),
+    f4: ((v0) => [encodeBytes(v0[0]), v0[1], encodeBytes(v0[2])])(value.f4),
+  };
+}
+
+export function decodeDefTy(raw: any): DefTy {
+  return {
+    f1: raw.get('f1'),
+    f2: raw.get('f2').map((v0: any) => decodeOptional(v0, (v1: any) => decodeDefTy(v1))),
+    f3: decodeMap(raw.get('f3'), (k0: any) => k0, (v0: any) => decodeInnerTy(v0)),
+    f4: ((v0: any) => [decodeBytes(v0[0]), v0[1], decodeBytes(v0[2])])(raw.get('f4')),
+  };
+}
+
+export type TupleStruct = [string, string, number];
+
+export function encodeTupleStruct(value: TupleStruct): any {
+  return [value[0], value[1], value[2]];
+}
+
+export function decodeTupleStruct(raw: any): TupleStruct {
+  return [raw[0], raw[1], raw[2]];
+}
+
+export interface TestEvent2 {
+  indexed1: number;
+  indexed2: number;
+}
+
+export function encodeTestEvent2(value: TestEvent2): any {
+  return {
+    indexed1: value.indexed1,
+    indexed2: value.indexed2,
+  };
+}
+
+export function decodeTestEvent2(raw: any): TestEvent2 {
+  return {
+    indexed1: raw.get('indexed1'),
+    indexed2: raw.get('indexed2'),
+  };
+}
+
+export const TestEvent2Topic = hexToBytes('c365927ee4bfa11e6d773dd7e7c14323645e05ddba3d36abacdfb69af568751f');
+
+/** Decodes the data of a `TestEvent2` event. */
+export function parseTestEvent2(data: Uint8Array): TestEvent2 {
+  return decodeTestEvent2(decode(data));
+}
+
+/** The errors returned by `transfer`. */
+export type Error = { InsufficientFunds: { address: Address } } | { Rejected: string } | { Clamped: [Int64, InnerTy | null] } | 'Unknown';
+
+export function encodeError(value: Error): any {
+  if (typeof value === 'string') {
+    return value;
+  }
+  if ('InsufficientFunds' in value) {
+    const payload = value.InsufficientFunds;
+    return { InsufficientFunds: { address: encodeBytes(payload.address) } };
+  }
+  if ('Rejected' in value) {
+    const payload = value.Rejected;
+    return { Rejected: payload };
+  }
+  if ('Clamped' in value) {
+    const payload = value.Clamped;
+    return { Clamped: [payload[0], encodeOptional(payload[1], (v0) => encodeInnerTy(v0))] };
+  }
+  throw new TypeError(`Invalid Error: ${String(value)}`);
+}
+
+export function decodeError(raw: any): Error {
+  if (typeof raw === 'string') {
+    return raw as Error;
+  }
+  if (raw.has('InsufficientFunds')) {
+    const payload = raw.get('InsufficientFunds');
+    return { InsufficientFunds: { address: decodeBytes(payload.get('address')) } };
+  }
+  if (raw.has('Rejected')) {
+    const payload = raw.get('Rejected');
+    return { Rejected: payload };
+  }
+  if (raw.has('Clamped')) {
+    const payload = raw.get('Clamped');
+    return { Clamped: [payload[0], decodeOptional(payload[1], (v0: any) => decodeInnerTy(v0))] };
+  }
+  throw new TypeError('Invalid Error');
+}
+
+/** A service that exercises IDL generation. */
+export class TestServiceClient {
+  constructor(public readonly transport: Transport, public readonly address: Address) {}
+
+  /** Deploys `bytecode` and returns a client for the new service. */
+  static async deploy(transport: Transport, bytecode: Uint8Array, tupleStruct: TupleStruct, options?: CallOptions): Promise<TestServiceClient> {
+    const input = encode([encodeTupleStruct(tupleStruct)]);
+    const outcome = await transport.deploy(bytecode, input, options);
+    if (!outcome.success || !outcome.address) {
+      throw new RpcError(outcome.output);
+    }
+    return new TestServiceClient(transport, outcome.address);
+  }
+
+  /** Returns a builder of a batch of calls to this service. */
+  batch(): TestServiceBatch {
+    return new TestServiceBatch(this);
+  }
+
+  /** Does not modify the state of the service. */
+  async the(arg1: Array<DefTy>, arg2: Uint8Array, options?: CallOptions): Promise<Set<Address>> {
+    const input = encode({ method: 'the', payload: [arg1.map((v0) => encodeDefTy(v0)), encodeBytes(arg2)] });
+    const outcome = await this.transport.transact(this.address, input, options);
+    if (!outcome.success) {
+      throw new RpcError(decode(outcome.output));
+    }
+    return new Set(decode(outcome.output).map((v0: any) => decodeBytes(v0)));
+  }
+
+  async it(a1: Map<boolean, Array<number>>, a3: Set<Int64>, options?: CallOptions): Promise<Uint8Array> {
+    const input = encode({ method: 'it', payload: [a1, Array.from(a3)] });
+    const outcome = await this.transport.transact(this.address, input, options);
+    if (!outcome.success) {
+      throw new RpcError(decode(outcome.output));
+    }
+    return decodeBytes(decode(outcome.output));
+  }
+
+  /** Does not modify the state of the service. */
+  async void(options?: CallOptions): Promise<void> {
+    const input = encode({ method: 'void', payload: [] });
+    const outcome = await this.transport.transact(this.address, input, options);
+    if (!outcome.success) {
+      throw new RpcError(outcome.output);
+    }
+  }
+
+  /** Accepts the value sent in `options.value`. */
+  async theDefaultFn(options?: CallOptions): Promise<Int64 | null> {
+    const input = encode({ method: 'the_default_fn', payload: [] });
+    const outcome = await this.transport.transact(this.address, input, options);
+    if (!outcome.success) {
+      throw new RpcError(new Set(decode(outcome.output)));
+    }
+    return decode(outcome.output);
+  }
+
+  /**
+   * Transfers tokens to another account.
+   * @param to the recipient
+   * @param amount the number of tokens
+   */
+  async transfer(to: Address, amount: Int64, options?: CallOptions): Promise<null> {
+    const input = encode({ method: 'transfer', payload: [encodeBytes(to), amount] });
+    const outcome = await this.transport.transact(this.address, input, options);
+    if (!outcome.success) {
+      throw new RpcError(decodeError(decode(outcome.output)));
+    }
+    return decode(outcome.output);
+  }
+
+  /**
+   * Callable only by the owner or accounts with the minter role.
+   * Not callable while the service is paused.
+   */
+  async mint(amount: Int128, cap: bigint, options?: CallOptions): Promise<Int128> {
+    const input = encode({ method: 'mint', payload: [encodeBigInt(amount, 16), encodeBigInt(cap, 32)] });
+    const outcome = await this.transport.transact(this.address, input, options);
+    if (!outcome.success) {
+      throw new RpcError(outcome.output);
+    }
+    return decodeInt128(decode(outcome.output), true);
+  }
+}
+
+/**
+ * Collects calls to the service that are sent in one transaction.
+ * The calls take effect in order and together or, if any of them fails, not at all.
+ */
+export class TestServiceBatch {
+  private readonly calls: Uint8Array[] = [];
+  private readonly decoders: [(raw: any) => any, (raw: any) => any][] = [];
+
+  constructor(public readonly client: TestServiceClient) {}
+
+  the(arg1: Array<DefTy>, arg2: Uint8Array): this {
+    this.calls.push(encode({ method: 'the', payload: [arg1.map((v0) => encodeDefTy(v0)), encodeBytes(arg2)] }));
+    this.decoders.push([(raw) => new Set(raw.map((v0: any) => decodeBytes(v0))), (raw) => raw]);
+    return this;
+  }
+
+  it(a1: Map<boolean, Array<number>>, a3: Set<Int64>): this {
+    this.calls.push(encode({ method: 'it', payload: [a1, Array.from(a3)] }));
+    this.decoders.push([(raw) => decodeBytes(raw), (raw) => raw]);
+    return this;
+  }
+
+  void(): this {
+    this.calls.push(encode({ method: 'void', payload: [] }));
+    this.decoders.push([(raw) => undefined, (raw) => raw]);
+    return this;
+  }
+
+  theDefaultFn(): this {
+    this.calls.push(encode({ method: 'the_default_fn', payload: [] }));
+    this.decoders.push([(raw) => raw, (raw) => new Set(raw)]);
+    return this;
+  }
+
+  /**
+   * Transfers tokens to another account.
+   * @param to the recipient
+   * @param amount the number of tokens
+   */
+  transfer(to: Address, amount: Int64): this {
+    this.calls.push(encode({ method: 'transfer', payload: [encodeBytes(to), amount] }));
+    this.decoders.push([(raw) => raw, (raw) => decodeError(raw)]);
+    return this;
+  }
+
+  mint(amount: Int128, cap: bigint): this {
+    this.calls.push(encode({ method: 'mint', payload: [encodeBigInt(amount, 16), encodeBigInt(cap, 32)] }));
+    this.decoders.push([(raw) => decodeInt128(raw, true), (raw) => raw]);
+    return this;
+  }
+
+  /**
+   * Sends the batched calls and returns their outputs in order.
+   * If a call fails, the `RpcError` holds its `index` and its `error`.
+   */
+  async send(options?: CallOptions): Promise<any[]> {
+    const input = encode([0, this.calls]);
+    const outcome = await this.client.transport.transact(this.client.address, input, options);
+    if (!outcome.success) {
+      const [index, error] = decode(outcome.output);
+      throw new RpcError({ index, error: this.decoders[index][1](decode(error)) });
+    }
+    const outputs: Uint8Array[] = decode(outcome.output);
+    return outputs.map((output, i) => this.decoders[i][0](decode(output)));
+  }
+}
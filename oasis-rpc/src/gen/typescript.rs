@@ -0,0 +1,1038 @@
+//! Generates a TypeScript module containing the types and client of a service.
+//!
+//! Values are converted to and from the shapes produced by `serde_cbor` in the service.
+//! The generated module depends on the [`cbor-x`](https://github.com/kriszyp/cbor-x) codec.
+
+use std::fmt::Write as _;
+
+use heck::{CamelCase, MixedCase};
+
+use crate::{Constructor, Field, Function, Interface, StateMutability, Type, TypeDef, Variant};
+
+/// The definitions shared by every generated module.
+const PRELUDE: &str = r#"
+export type Address = Uint8Array;
+
+/** An unsigned or signed 64-bit integer. Decoded as a `bigint` only when it exceeds 2^53. */
+export type Int64 = number | bigint;
+
+/** An unsigned or signed 128-bit integer. Decoded as a `bigint` only when it exceeds 2^53. */
+export type Int128 = number | bigint;
+
+export interface CallOptions {
+  value?: Int64;
+  gas?: Int64;
+}
+
+/** The result of a transaction. `output` holds the encoded error if `success` is false. */
+export interface Outcome {
+  success: boolean;
+  output: Uint8Array;
+}
+
+/** The connection to a gateway or node through which transactions are sent. */
+export interface Transport {
+  transact(address: Address, input: Uint8Array, options?: CallOptions): Promise<Outcome>;
+  deploy(
+    bytecode: Uint8Array,
+    input: Uint8Array,
+    options?: CallOptions,
+  ): Promise<Outcome & { address?: Address }>;
+}
+
+/** Thrown when a service returns an error. */
+export class RpcError<E = Uint8Array> extends Error {
+  constructor(public readonly error: E) {
+    super(`RPC returned error: ${String(error)}`);
+  }
+}
+
+const encoder = new Encoder({ useRecords: false, mapsAsObjects: false });
+const decoder = new Decoder({ useRecords: false, mapsAsObjects: false });
+
+export function encode(value: any): Uint8Array {
+  return encoder.encode(value);
+}
+
+export function decode(bytes: Uint8Array): any {
+  return decoder.decode(bytes);
+}
+
+function encodeBytes(bytes: Uint8Array): number[] {
+  return Array.from(bytes);
+}
+
+function decodeBytes(raw: number[]): Uint8Array {
+  return Uint8Array.from(raw);
+}
+
+function encodeOptional<T>(value: T | null, encodeValue: (v: T) => any): any {
+  return value === null ? null : encodeValue(value);
+}
+
+function decodeOptional<T>(raw: any, decodeValue: (r: any) => T): T | null {
+  return raw === null || raw === undefined ? null : decodeValue(raw);
+}
+
+function encodeMap<K, V>(map: Map<K, V>, encodeKey: (k: K) => any, encodeValue: (v: V) => any) {
+  return new Map(Array.from(map, ([k, v]) => [encodeKey(k), encodeValue(v)]));
+}
+
+function decodeMap<K, V>(raw: Map<any, any>, decodeKey: (r: any) => K, decodeValue: (r: any) => V) {
+  return new Map(Array.from(raw, ([k, v]) => [decodeKey(k), decodeValue(v)]));
+}
+
+/** Encodes an integer as `size` big-endian bytes, in two's complement if it is negative. */
+function encodeBigInt(value: Int128, size: number): Uint8Array {
+  const bytes = new Uint8Array(size);
+  let v = BigInt.asUintN(size * 8, BigInt(value));
+  for (let i = size - 1; i >= 0; i--, v >>= BigInt(8)) {
+    bytes[i] = Number(v & BigInt(0xff));
+  }
+  return bytes;
+}
+
+function decodeBigUint(raw: Uint8Array): bigint {
+  return raw.reduce((v, byte) => (v << BigInt(8)) | BigInt(byte), BigInt(0));
+}
+
+function decodeInt128(raw: Uint8Array, signed: boolean): Int128 {
+  const v = signed ? BigInt.asIntN(128, decodeBigUint(raw)) : decodeBigUint(raw);
+  return Number.isSafeInteger(Number(v)) ? Number(v) : v;
+}
+
+function hexToBytes(hex: string): Uint8Array {
+  return Uint8Array.from(hex.match(/../g)!.map((byte) => parseInt(byte, 16)));
+}
+"#;
+
+pub fn generate(iface: &Interface) -> String {
+    let mut ts = String::new();
+    writeln!(
+        ts,
+        "// Generated by oasis-rpc from {} v{}. Do not edit.",
+        iface.name, iface.version
+    )
+    .unwrap();
+    ts.push('\n');
+    writeln!(ts, "import {{ Decoder, Encoder }} from 'cbor-x';").unwrap();
+    for import in iface.imports.iter() {
+        writeln!(ts, "import * as {0} from './{0}';", import.name).unwrap();
+    }
+    ts.push_str(PRELUDE);
+
+    for def in iface.type_defs.iter() {
+        ts.push('\n');
+        gen_type_def(&mut ts, def);
+    }
+
+    ts.push('\n');
+    gen_client(&mut ts, iface);
+    ts
+}
+
+fn gen_type_def(ts: &mut String, def: &TypeDef) {
+    let name = def.name();
+    gen_doc(ts, "", def.docs(), &[]);
+    match def {
+        TypeDef::Struct { fields, .. }
+            if crate::is_tuple_struct(fields.iter().map(|f| f.name.as_str())) =>
+        {
+            if let [field] = fields.as_slice() {
+                // Newtype structs are serialized as their inner value.
+                writeln!(ts, "export type {} = {};", name, ts_ty(&field.ty)).unwrap();
+                gen_converters(
+                    ts,
+                    name,
+                    &encode_expr(&field.ty, "value", 0),
+                    &decode_expr(&field.ty, "raw", 0),
+                );
+                return;
+            }
+            let tys: Vec<_> = fields.iter().map(|f| ts_ty(&f.ty)).collect();
+            writeln!(ts, "export type {} = [{}];", name, tys.join(", ")).unwrap();
+            let encoded: Vec<_> = fields
+                .iter()
+                .enumerate()
+                .map(|(i, f)| encode_expr(&f.ty, &format!("value[{}]", i), 0))
+                .collect();
+            let decoded: Vec<_> = fields
+                .iter()
+                .enumerate()
+                .map(|(i, f)| decode_expr(&f.ty, &format!("raw[{}]", i), 0))
+                .collect();
+            gen_converters(
+                ts,
+                name,
+                &format!("[{}]", encoded.join(", ")),
+                &format!("[{}]", decoded.join(", ")),
+            );
+        }
+        TypeDef::Struct { fields, .. } => {
+            let fields: Vec<_> = fields
+                .iter()
+                .map(|f| (f.name.as_str(), &f.ty, f.docs.as_ref().map(String::as_str)))
+                .collect();
+            gen_struct(ts, name, &fields);
+        }
+        TypeDef::Event { fields, .. } => {
+            let indexed: Vec<_> = fields
+                .iter()
+                .map(|f| (f.name.as_str(), &f.ty, f.docs.as_ref().map(String::as_str)))
+                .collect();
+            gen_struct(ts, name, &indexed);
+            writeln!(ts).unwrap();
+            writeln!(
+                ts,
+                "export const {}Topic = hexToBytes('{}');",
+                name,
+                super::event_topic(name)
+            )
+            .unwrap();
+            writeln!(ts).unwrap();
+            writeln!(ts, "/** Decodes the data of a `{}` event. */", name).unwrap();
+            writeln!(
+                ts,
+                "export function parse{0}(data: Uint8Array): {0} {{",
+                name
+            )
+            .unwrap();
+            writeln!(ts, "  return decode{}(decode(data));", name).unwrap();
+            writeln!(ts, "}}").unwrap();
+        }
+        TypeDef::Enum { variants, .. } => {
+            let variant_tys: Vec<_> = variants
+                .iter()
+                .map(|v| match v {
+                    Variant::Unit(name) => format!("'{}'", name),
+                    _ => format!("{{ {}: {} }}", v.name(), variant_ty(v)),
+                })
+                .collect();
+            writeln!(ts, "export type {} = {};", name, variant_tys.join(" | ")).unwrap();
+            if variants.iter().all(|v| match v {
+                Variant::Unit(_) => true,
+                _ => false,
+            }) {
+                gen_converters(ts, name, "value", "raw");
+            } else {
+                gen_variant_converters(ts, name, variants);
+            }
+        }
+    }
+}
+
+/// Returns the TypeScript type of the payload of a data-carrying enum variant.
+fn variant_ty(variant: &Variant) -> String {
+    match variant {
+        Variant::Unit(_) => "null".to_string(),
+        Variant::Tuple { items, .. } => match items.as_slice() {
+            [item] => ts_ty(item),
+            _ => format!(
+                "[{}]",
+                items.iter().map(ts_ty).collect::<Vec<_>>().join(", ")
+            ),
+        },
+        Variant::Struct { fields, .. } => {
+            let fields: Vec<_> = fields
+                .iter()
+                .map(|f| format!("{}: {}", f.name, ts_ty(&f.ty)))
+                .collect();
+            format!("{{ {} }}", fields.join("; "))
+        }
+    }
+}
+
+/// Generates the converters of an enum having variants with payloads.
+/// Unit variants are represented as strings and the others as `{ Variant: payload }`.
+fn gen_variant_converters(ts: &mut String, name: &str, variants: &[Variant]) {
+    let payload_variants = || {
+        variants.iter().filter(|v| match v {
+            Variant::Unit(_) => false,
+            _ => true,
+        })
+    };
+
+    writeln!(ts).unwrap();
+    writeln!(ts, "export function encode{0}(value: {0}): any {{", name).unwrap();
+    writeln!(ts, "  if (typeof value === 'string') {{").unwrap();
+    writeln!(ts, "    return value;").unwrap();
+    writeln!(ts, "  }}").unwrap();
+    for variant in payload_variants() {
+        let var_name = variant.name();
+        let encoded = match variant {
+            Variant::Tuple { items, .. } => match items.as_slice() {
+                [item] => encode_expr(item, "payload", 0),
+                _ => {
+                    let elems: Vec<_> = items
+                        .iter()
+                        .enumerate()
+                        .map(|(i, ty)| encode_expr(ty, &format!("payload[{}]", i), 0))
+                        .collect();
+                    format!("[{}]", elems.join(", "))
+                }
+            },
+            Variant::Struct { fields, .. } => {
+                let fields: Vec<_> = fields
+                    .iter()
+                    .map(|f| {
+                        let expr = encode_expr(&f.ty, &format!("payload.{}", f.name), 0);
+                        format!("{}: {}", f.name, expr)
+                    })
+                    .collect();
+                format!("{{ {} }}", fields.join(", "))
+            }
+            Variant::Unit(_) => unreachable!("unit variants are encoded as strings"),
+        };
+        writeln!(ts, "  if ('{}' in value) {{", var_name).unwrap();
+        writeln!(ts, "    const payload = value.{};", var_name).unwrap();
+        writeln!(ts, "    return {{ {}: {} }};", var_name, encoded).unwrap();
+        writeln!(ts, "  }}").unwrap();
+    }
+    writeln!(
+        ts,
+        "  throw new TypeError(`Invalid {}: ${{String(value)}}`);",
+        name
+    )
+    .unwrap();
+    writeln!(ts, "}}").unwrap();
+
+    writeln!(ts).unwrap();
+    writeln!(ts, "export function decode{0}(raw: any): {0} {{", name).unwrap();
+    writeln!(ts, "  if (typeof raw === 'string') {{").unwrap();
+    writeln!(ts, "    return raw as {};", name).unwrap();
+    writeln!(ts, "  }}").unwrap();
+    for variant in payload_variants() {
+        let var_name = variant.name();
+        let decoded = match variant {
+            Variant::Tuple { items, .. } => match items.as_slice() {
+                [item] => decode_expr(item, "payload", 0),
+                _ => {
+                    let elems: Vec<_> = items
+                        .iter()
+                        .enumerate()
+                        .map(|(i, ty)| decode_expr(ty, &format!("payload[{}]", i), 0))
+                        .collect();
+                    format!("[{}]", elems.join(", "))
+                }
+            },
+            Variant::Struct { fields, .. } => {
+                let fields: Vec<_> = fields
+                    .iter()
+                    .map(|f| {
+                        let expr = decode_expr(&f.ty, &format!("payload.get('{}')", f.name), 0);
+                        format!("{}: {}", f.name, expr)
+                    })
+                    .collect();
+                format!("{{ {} }}", fields.join(", "))
+            }
+            Variant::Unit(_) => unreachable!("unit variants are decoded from strings"),
+        };
+        writeln!(ts, "  if (raw.has('{}')) {{", var_name).unwrap();
+        writeln!(ts, "    const payload = raw.get('{}');", var_name).unwrap();
+        writeln!(ts, "    return {{ {}: {} }};", var_name, decoded).unwrap();
+        writeln!(ts, "  }}").unwrap();
+    }
+    writeln!(ts, "  throw new TypeError('Invalid {}');", name).unwrap();
+    writeln!(ts, "}}").unwrap();
+}
+
+fn gen_struct(ts: &mut String, name: &str, fields: &[(&str, &Type, Option<&str>)]) {
+    writeln!(ts, "export interface {} {{", name).unwrap();
+    for (field_name, ty, docs) in fields.iter() {
+        gen_doc(ts, "  ", *docs, &[]);
+        writeln!(ts, "  {}: {};", field_name, ts_ty(ty)).unwrap();
+    }
+    writeln!(ts, "}}").unwrap();
+
+    let encoded: Vec<_> = fields
+        .iter()
+        .map(|(field_name, ty, _)| {
+            format!(
+                "    {}: {},",
+                field_name,
+                encode_expr(ty, &format!("value.{}", field_name), 0)
+            )
+        })
+        .collect();
+    let decoded: Vec<_> = fields
+        .iter()
+        .map(|(field_name, ty, _)| {
+            format!(
+                "    {}: {},",
+                field_name,
+                decode_expr(ty, &format!("raw.get('{}')", field_name), 0)
+            )
+        })
+        .collect();
+    gen_converters(
+        ts,
+        name,
+        &format!("{{\n{}\n  }}", encoded.join("\n")),
+        &format!("{{\n{}\n  }}", decoded.join("\n")),
+    );
+}
+
+/// Generates the functions that convert a defined type to and from its CBOR representation.
+fn gen_converters(ts: &mut String, name: &str, encoded: &str, decoded: &str) {
+    writeln!(ts).unwrap();
+    writeln!(ts, "export function encode{0}(value: {0}): any {{", name).unwrap();
+    writeln!(ts, "  return {};", encoded).unwrap();
+    writeln!(ts, "}}").unwrap();
+    writeln!(ts).unwrap();
+    writeln!(ts, "export function decode{0}(raw: any): {0} {{", name).unwrap();
+    writeln!(ts, "  return {};", decoded).unwrap();
+    writeln!(ts, "}}").unwrap();
+}
+
+fn gen_client(ts: &mut String, iface: &Interface) {
+    let client_name = format!("{}Client", iface.name.to_camel_case());
+    gen_doc(ts, "", iface.docs.as_ref().map(String::as_str), &[]);
+    writeln!(ts, "export class {} {{", client_name).unwrap();
+    writeln!(
+        ts,
+        "  constructor(public readonly transport: Transport, public readonly address: Address) {{}}"
+    )
+    .unwrap();
+    gen_deploy(ts, &client_name, &iface.constructor);
+    // An interface having a function without a selector was exported by a dispatcher
+    // that predates batches.
+    let batch_name = if iface.functions.iter().all(|func| func.selector.is_some()) {
+        Some(format!("{}Batch", iface.name.to_camel_case()))
+    } else {
+        None
+    };
+    if let Some(batch_name) = &batch_name {
+        writeln!(ts).unwrap();
+        gen_doc(
+            ts,
+            "  ",
+            Some("Returns a builder of a batch of calls to this service."),
+            &[],
+        );
+        writeln!(ts, "  batch(): {} {{", batch_name).unwrap();
+        writeln!(ts, "    return new {}(this);", batch_name).unwrap();
+        writeln!(ts, "  }}").unwrap();
+    }
+    for func in iface.functions.iter() {
+        gen_rpc(ts, func);
+    }
+    writeln!(ts, "}}").unwrap();
+
+    if let Some(batch_name) = &batch_name {
+        ts.push('\n');
+        gen_batch(ts, iface, &client_name, batch_name);
+    }
+}
+
+/// Writes the builder of batches of calls, which are sent to the service in one transaction.
+fn gen_batch(ts: &mut String, iface: &Interface, client_name: &str, batch_name: &str) {
+    gen_doc(
+        ts,
+        "",
+        Some(
+            "Collects calls to the service that are sent in one transaction.\n\
+             The calls take effect in order and together or, if any of them fails, not at all.",
+        ),
+        &[],
+    );
+    writeln!(ts, "export class {} {{", batch_name).unwrap();
+    writeln!(ts, "  private readonly calls: Uint8Array[] = [];").unwrap();
+    writeln!(
+        ts,
+        "  private readonly decoders: [(raw: any) => any, (raw: any) => any][] = [];"
+    )
+    .unwrap();
+    writeln!(ts).unwrap();
+    writeln!(
+        ts,
+        "  constructor(public readonly client: {}) {{}}",
+        client_name
+    )
+    .unwrap();
+
+    for func in iface.functions.iter() {
+        let (output_ty, err_ty) = super::split_output(&func.output);
+        let params: Vec<_> = func
+            .inputs
+            .iter()
+            .map(|inp| format!("{}: {}", param_name(&inp.name), ts_ty(&inp.ty)))
+            .collect();
+        let args: Vec<_> = func
+            .inputs
+            .iter()
+            .map(|inp| encode_expr(&inp.ty, &param_name(&inp.name), 0))
+            .collect();
+        writeln!(ts).unwrap();
+        gen_doc(
+            ts,
+            "  ",
+            func.docs.as_ref().map(String::as_str),
+            &param_tags(&func.inputs),
+        );
+        writeln!(
+            ts,
+            "  {}({}): this {{",
+            batch_method_name(&func.name),
+            params.join(", ")
+        )
+        .unwrap();
+        writeln!(
+            ts,
+            "    this.calls.push(encode({{ method: '{}', payload: [{}] }}));",
+            func.name,
+            args.join(", ")
+        )
+        .unwrap();
+        writeln!(
+            ts,
+            "    this.decoders.push([(raw) => {}, (raw) => {}]);",
+            output_ty
+                .map(|ty| decode_expr(ty, "raw", 0))
+                .unwrap_or_else(|| "undefined".to_string()),
+            err_ty
+                .map(|ty| decode_expr(ty, "raw", 0))
+                .unwrap_or_else(|| "raw".to_string()),
+        )
+        .unwrap();
+        writeln!(ts, "    return this;").unwrap();
+        writeln!(ts, "  }}").unwrap();
+    }
+
+    writeln!(ts).unwrap();
+    gen_doc(
+        ts,
+        "  ",
+        Some(
+            "Sends the batched calls and returns their outputs in order.\n\
+             If a call fails, the `RpcError` holds its `index` and its `error`.",
+        ),
+        &[],
+    );
+    writeln!(ts, "  async send(options?: CallOptions): Promise<any[]> {{").unwrap();
+    writeln!(
+        ts,
+        "    const input = encode([{}, this.calls]);",
+        crate::BATCH_SELECTOR
+    )
+    .unwrap();
+    writeln!(
+        ts,
+        "    const outcome = await this.client.transport.transact(this.client.address, input, options);"
+    )
+    .unwrap();
+    writeln!(ts, "    if (!outcome.success) {{").unwrap();
+    if iface
+        .functions
+        .iter()
+        .any(|func| super::split_output(&func.output).1.is_some())
+    {
+        writeln!(ts, "      const [index, error] = decode(outcome.output);").unwrap();
+        writeln!(
+            ts,
+            "      throw new RpcError({{ index, error: this.decoders[index][1](decode(error)) }});"
+        )
+        .unwrap();
+    } else {
+        writeln!(ts, "      throw new RpcError(outcome.output);").unwrap();
+    }
+    writeln!(ts, "    }}").unwrap();
+    writeln!(
+        ts,
+        "    const outputs: Uint8Array[] = decode(outcome.output);"
+    )
+    .unwrap();
+    writeln!(
+        ts,
+        "    return outputs.map((output, i) => this.decoders[i][0](decode(output)));"
+    )
+    .unwrap();
+    writeln!(ts, "  }}").unwrap();
+    writeln!(ts, "}}").unwrap();
+}
+
+fn gen_deploy(ts: &mut String, client_name: &str, ctor: &Constructor) {
+    let params: Vec<_> = ctor
+        .inputs
+        .iter()
+        .map(|inp| format!("{}: {}, ", param_name(&inp.name), ts_ty(&inp.ty)))
+        .collect();
+    let args: Vec<_> = ctor
+        .inputs
+        .iter()
+        .map(|inp| encode_expr(&inp.ty, &param_name(&inp.name), 0))
+        .collect();
+    writeln!(ts).unwrap();
+    let mut tags = vec!["Deploys `bytecode` and returns a client for the new service.".to_string()];
+    tags.extend(param_tags(&ctor.inputs));
+    gen_doc(ts, "  ", ctor.docs.as_ref().map(String::as_str), &tags);
+    writeln!(
+        ts,
+        "  static async deploy(transport: Transport, bytecode: Uint8Array, {}options?: CallOptions): Promise<{}> {{",
+        params.concat(),
+        client_name
+    )
+    .unwrap();
+    writeln!(ts, "    const input = encode([{}]);", args.join(", ")).unwrap();
+    writeln!(
+        ts,
+        "    const outcome = await transport.deploy(bytecode, input, options);"
+    )
+    .unwrap();
+    writeln!(ts, "    if (!outcome.success || !outcome.address) {{").unwrap();
+    writeln!(ts, "      throw new RpcError(outcome.output);").unwrap();
+    writeln!(ts, "    }}").unwrap();
+    writeln!(
+        ts,
+        "    return new {}(transport, outcome.address);",
+        client_name
+    )
+    .unwrap();
+    writeln!(ts, "  }}").unwrap();
+}
+
+fn gen_rpc(ts: &mut String, func: &Function) {
+    let (output_ty, err_ty) = super::split_output(&func.output);
+    let params: Vec<_> = func
+        .inputs
+        .iter()
+        .map(|inp| format!("{}: {}, ", param_name(&inp.name), ts_ty(&inp.ty)))
+        .collect();
+    let args: Vec<_> = func
+        .inputs
+        .iter()
+        .map(|inp| encode_expr(&inp.ty, &param_name(&inp.name), 0))
+        .collect();
+    let ret_ty = output_ty.map(ts_ty).unwrap_or_else(|| "void".to_string());
+
+    writeln!(ts).unwrap();
+    let mut tags = Vec::new();
+    if let StateMutability::Immutable = func.mutability {
+        tags.push("Does not modify the state of the service.".to_string());
+    }
+    if func.payable {
+        tags.push("Accepts the value sent in `options.value`.".to_string());
+    }
+    if !func.only.is_empty() {
+        tags.push(format!(
+            "Callable only by {}.",
+            super::fmt_principals(&func.only)
+        ));
+    }
+    if func.when_not_paused {
+        tags.push("Not callable while the service is paused.".to_string());
+    }
+    tags.extend(param_tags(&func.inputs));
+    gen_doc(ts, "  ", func.docs.as_ref().map(String::as_str), &tags);
+    writeln!(
+        ts,
+        "  async {}({}options?: CallOptions): Promise<{}> {{",
+        method_name(&func.name),
+        params.concat(),
+        ret_ty
+    )
+    .unwrap();
+    writeln!(
+        ts,
+        "    const input = encode({{ method: '{}', payload: [{}] }});",
+        func.name,
+        args.join(", ")
+    )
+    .unwrap();
+    writeln!(
+        ts,
+        "    const outcome = await this.transport.transact(this.address, input, options);"
+    )
+    .unwrap();
+    writeln!(ts, "    if (!outcome.success) {{").unwrap();
+    match err_ty {
+        Some(err_ty) => writeln!(
+            ts,
+            "      throw new RpcError({});",
+            decode_expr(err_ty, "decode(outcome.output)", 0)
+        ),
+        None => writeln!(ts, "      throw new RpcError(outcome.output);"),
+    }
+    .unwrap();
+    writeln!(ts, "    }}").unwrap();
+    match output_ty {
+        Some(output_ty) => writeln!(
+            ts,
+            "    return {};",
+            decode_expr(output_ty, "decode(outcome.output)", 0)
+        )
+        .unwrap(),
+        None => (),
+    }
+    writeln!(ts, "  }}").unwrap();
+}
+
+/// Writes a JSDoc comment containing `docs` followed by `extra` lines, if there are any.
+fn gen_doc(ts: &mut String, indent: &str, docs: Option<&str>, extra: &[String]) {
+    let lines: Vec<_> = docs
+        .into_iter()
+        .flat_map(str::lines)
+        .chain(extra.iter().map(String::as_str))
+        .map(|line| line.replace("*/", "*\\/"))
+        .collect();
+    match lines.as_slice() {
+        [] => (),
+        [line] => writeln!(ts, "{}/** {} */", indent, line).unwrap(),
+        _ => {
+            writeln!(ts, "{}/**", indent).unwrap();
+            for line in lines.iter() {
+                writeln!(ts, "{} * {}", indent, line).unwrap();
+            }
+            writeln!(ts, "{} */", indent).unwrap();
+        }
+    }
+}
+
+/// Returns the `@param` tags of the documented `inputs`.
+fn param_tags(inputs: &[Field]) -> Vec<String> {
+    inputs
+        .iter()
+        .filter_map(|inp| {
+            let docs = inp.docs.as_ref()?;
+            Some(format!(
+                "@param {} {}",
+                param_name(&inp.name),
+                docs.replace('\n', " ")
+            ))
+        })
+        .collect()
+}
+
+fn ts_ty(ty: &Type) -> String {
+    match ty {
+        Type::Bool => "boolean".to_string(),
+        Type::U8 | Type::I8 | Type::U16 | Type::I16 | Type::U32 | Type::I32 => "number".to_string(),
+        Type::U64 | Type::I64 => "Int64".to_string(),
+        Type::U128 | Type::I128 => "Int128".to_string(),
+        Type::U256 => "bigint".to_string(),
+        Type::F32 | Type::F64 => "number".to_string(),
+        Type::Bytes => "Uint8Array".to_string(),
+        Type::String => "string".to_string(),
+        Type::Address => "Address".to_string(),
+        Type::Defined { namespace, ty } => match namespace {
+            Some(namespace) => format!("{}.{}", namespace, ty),
+            None => ty.to_string(),
+        },
+        Type::Tuple(tys) if tys.is_empty() => "null".to_string(),
+        Type::Tuple(tys) => format!("[{}]", tys.iter().map(ts_ty).collect::<Vec<_>>().join(", ")),
+        Type::Array(ty, _) | Type::List(ty) => format!("Array<{}>", ts_ty(ty)),
+        Type::Set(ty) => format!("Set<{}>", ts_ty(ty)),
+        Type::Map(k_ty, v_ty) => format!("Map<{}, {}>", ts_ty(k_ty), ts_ty(v_ty)),
+        Type::Optional(ty) => format!("{} | null", ts_ty(ty)),
+        Type::Result(ok_ty, err_ty) => {
+            format!("{{ Ok: {} }} | {{ Err: {} }}", ts_ty(ok_ty), ts_ty(err_ty))
+        }
+    }
+}
+
+/// Returns whether a value of type `ty` has the same representation in TypeScript and CBOR.
+fn is_plain(ty: &Type) -> bool {
+    match ty {
+        Type::Bool
+        | Type::U8
+        | Type::I8
+        | Type::U16
+        | Type::I16
+        | Type::U32
+        | Type::I32
+        | Type::U64
+        | Type::I64
+        | Type::F32
+        | Type::F64
+        | Type::String => true,
+        Type::Tuple(tys) => tys.iter().all(is_plain),
+        Type::Array(ty, _) | Type::List(ty) | Type::Optional(ty) => is_plain(ty),
+        // `Map`s are encoded as CBOR maps, and CBOR maps are always decoded into `Map`s.
+        Type::Map(k_ty, v_ty) => is_plain(k_ty) && is_plain(v_ty),
+        _ => false,
+    }
+}
+
+/// Returns an expression that converts `expr` of type `ty` into its CBOR representation.
+/// `depth` is used to generate unique names for closure arguments.
+fn encode_expr(ty: &Type, expr: &str, depth: usize) -> String {
+    if is_plain(ty) {
+        return expr.to_string();
+    }
+    let v = format!("v{}", depth);
+    let k = format!("k{}", depth);
+    match ty {
+        Type::Bytes | Type::Address => format!("encodeBytes({})", expr),
+        Type::U128 | Type::I128 => format!("encodeBigInt({}, 16)", expr),
+        Type::U256 => format!("encodeBigInt({}, 32)", expr),
+        Type::Defined { namespace, ty } => match namespace {
+            Some(namespace) => format!("{}.encode{}({})", namespace, ty, expr),
+            None => format!("encode{}({})", ty, expr),
+        },
+        Type::Tuple(tys) => {
+            let elems: Vec<_> = tys
+                .iter()
+                .enumerate()
+                .map(|(i, ty)| encode_expr(ty, &format!("{}[{}]", v, i), depth + 1))
+                .collect();
+            format!("(({}) => [{}])({})", v, elems.join(", "), expr)
+        }
+        Type::Array(ty, _) | Type::List(ty) => format!(
+            "{}.map(({}) => {})",
+            expr,
+            v,
+            encode_expr(ty, &v, depth + 1)
+        ),
+        Type::Set(ty) if is_plain(ty) => format!("Array.from({})", expr),
+        Type::Set(ty) => format!(
+            "Array.from({}, ({}) => {})",
+            expr,
+            v,
+            encode_expr(ty, &v, depth + 1)
+        ),
+        Type::Map(k_ty, v_ty) => format!(
+            "encodeMap({}, ({}) => {}, ({}) => {})",
+            expr,
+            k,
+            encode_expr(k_ty, &k, depth + 1),
+            v,
+            encode_expr(v_ty, &v, depth + 1)
+        ),
+        Type::Optional(ty) => format!(
+            "encodeOptional({}, ({}) => {})",
+            expr,
+            v,
+            encode_expr(ty, &v, depth + 1)
+        ),
+        Type::Result(ok_ty, err_ty) => format!(
+            "(({v}) => 'Ok' in {v} ? {{ Ok: {ok} }} : {{ Err: {err} }})({expr})",
+            v = v,
+            ok = encode_expr(ok_ty, &format!("{}.Ok", v), depth + 1),
+            err = encode_expr(err_ty, &format!("{}.Err", v), depth + 1),
+            expr = expr
+        ),
+        _ => unreachable!("plain types are returned early"),
+    }
+}
+
+/// Returns an expression that converts the CBOR representation `expr` into a value of type `ty`.
+fn decode_expr(ty: &Type, expr: &str, depth: usize) -> String {
+    if is_plain(ty) {
+        return expr.to_string();
+    }
+    let v = format!("v{}", depth);
+    let k = format!("k{}", depth);
+    match ty {
+        Type::Bytes | Type::Address => format!("decodeBytes({})", expr),
+        Type::U128 => format!("decodeInt128({}, false)", expr),
+        Type::I128 => format!("decodeInt128({}, true)", expr),
+        Type::U256 => format!("decodeBigUint({})", expr),
+        Type::Defined { namespace, ty } => match namespace {
+            Some(namespace) => format!("{}.decode{}({})", namespace, ty, expr),
+            None => format!("decode{}({})", ty, expr),
+        },
+        Type::Tuple(tys) => {
+            let elems: Vec<_> = tys
+                .iter()
+                .enumerate()
+                .map(|(i, ty)| decode_expr(ty, &format!("{}[{}]", v, i), depth + 1))
+                .collect();
+            format!("(({}: any) => [{}])({})", v, elems.join(", "), expr)
+        }
+        Type::Array(ty, _) | Type::List(ty) => format!(
+            "{}.map(({}: any) => {})",
+            expr,
+            v,
+            decode_expr(ty, &v, depth + 1)
+        ),
+        Type::Set(ty) if is_plain(ty) => format!("new Set({})", expr),
+        Type::Set(ty) => format!(
+            "new Set({}.map(({}: any) => {}))",
+            expr,
+            v,
+            decode_expr(ty, &v, depth + 1)
+        ),
+        Type::Map(k_ty, v_ty) => format!(
+            "decodeMap({}, ({}: any) => {}, ({}: any) => {})",
+            expr,
+            k,
+            decode_expr(k_ty, &k, depth + 1),
+            v,
+            decode_expr(v_ty, &v, depth + 1)
+        ),
+        Type::Optional(ty) => format!(
+            "decodeOptional({}, ({}: any) => {})",
+            expr,
+            v,
+            decode_expr(ty, &v, depth + 1)
+        ),
+        Type::Result(ok_ty, err_ty) => format!(
+            "(({v}: any) => {v}.has('Ok') ? {{ Ok: {ok} }} : {{ Err: {err} }})({expr})",
+            v = v,
+            ok = decode_expr(ok_ty, &format!("{}.get('Ok')", v), depth + 1),
+            err = decode_expr(err_ty, &format!("{}.get('Err')", v), depth + 1),
+            expr = expr
+        ),
+        _ => unreachable!("plain types are returned early"),
+    }
+}
+
+/// Returns the name of the client method that calls the RPC `name`.
+/// Names that would collide with the client's own members have an underscore appended.
+fn method_name(name: &str) -> String {
+    const CLIENT_MEMBERS: &[&str] = &["address", "batch", "constructor", "transport"];
+    escape(name.to_mixed_case(), CLIENT_MEMBERS)
+}
+
+/// Returns the name of the batch builder method that adds a call to the RPC `name`.
+fn batch_method_name(name: &str) -> String {
+    const BATCH_MEMBERS: &[&str] = &["calls", "client", "constructor", "decoders", "send"];
+    escape(name.to_mixed_case(), BATCH_MEMBERS)
+}
+
+/// Returns the name of a parameter of a client method.
+/// Reserved words and the names of the parameters added by the client are escaped.
+fn param_name(name: &str) -> String {
+    const RESERVED: &[&str] = &[
+        "arguments",
+        "await",
+        "break",
+        "case",
+        "catch",
+        "class",
+        "const",
+        "continue",
+        "debugger",
+        "default",
+        "delete",
+        "do",
+        "else",
+        "enum",
+        "eval",
+        "export",
+        "extends",
+        "false",
+        "finally",
+        "for",
+        "function",
+        "if",
+        "implements",
+        "import",
+        "in",
+        "instanceof",
+        "interface",
+        "let",
+        "new",
+        "null",
+        "package",
+        "private",
+        "protected",
+        "public",
+        "return",
+        "static",
+        "super",
+        "switch",
+        "this",
+        "throw",
+        "true",
+        "try",
+        "typeof",
+        "var",
+        "void",
+        "while",
+        "with",
+        "yield",
+        // parameters and locals of generated methods
+        "bytecode",
+        "input",
+        "options",
+        "outcome",
+        "transport",
+    ];
+    escape(name.to_mixed_case(), RESERVED)
+}
+
+fn escape(ident: String, reserved: &[&str]) -> String {
+    if reserved.contains(&ident.as_str()) {
+        ident + "_"
+    } else {
+        ident
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list(ty: Type) -> Type {
+        Type::List(Box::new(ty))
+    }
+
+    #[test]
+    fn test_encode_expr() {
+        assert_eq!(encode_expr(&list(Type::U32), "xs", 0), "xs");
+        assert_eq!(
+            encode_expr(&list(Type::Optional(Box::new(Type::Bytes))), "xs", 0),
+            "xs.map((v0) => encodeOptional(v0, (v1) => encodeBytes(v1)))"
+        );
+        assert_eq!(
+            decode_expr(&Type::Tuple(vec![Type::Address, Type::U64]), "raw", 0),
+            "((v0: any) => [decodeBytes(v0[0]), v0[1]])(raw)"
+        );
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(method_name("the_default_fn"), "theDefaultFn");
+        assert_eq!(method_name("default"), "default");
+        assert_eq!(method_name("address"), "address_");
+        assert_eq!(method_name("batch"), "batch_");
+        assert_eq!(batch_method_name("send"), "send_");
+        assert_eq!(batch_method_name("transfer"), "transfer");
+        assert_eq!(param_name("new"), "new_");
+        assert_eq!(param_name("options"), "options_");
+    }
+
+    /// Compares the code generated for each fixture in `tests/idl-gen/res` with its snapshot
+    /// in `src/gen/snapshots`. Set `UPDATE_SNAPSHOTS` to overwrite the snapshots instead.
+    #[test]
+    fn test_fixtures() {
+        let manifest_dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR"));
+        let snapshot_dir = manifest_dir.join("src/gen/snapshots");
+        for entry in std::fs::read_dir(manifest_dir.join("../tests/idl-gen/res")).unwrap() {
+            let iface: Interface =
+                serde_json::from_slice(&std::fs::read(entry.unwrap().path()).unwrap()).unwrap();
+            let ts = generate(&iface);
+            let snapshot_path = snapshot_dir.join(format!("{}.ts", iface.name));
+            if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+                std::fs::create_dir_all(&snapshot_dir).unwrap();
+                std::fs::write(&snapshot_path, ts).unwrap();
+                continue;
+            }
+            let snapshot = std::fs::read_to_string(&snapshot_path)
+                .unwrap_or_else(|err| panic!("{}: {}", snapshot_path.display(), err));
+            assert!(
+                ts == snapshot,
+                "the code generated for {} differs from {}",
+                iface.name,
+                snapshot_path.display()
+            );
+        }
+    }
+
+    #[test]
+    fn test_batch_and_access_docs() {
+        let mut iface = crate::testing::test_service();
+        let ts = generate(&iface);
+        assert!(ts.contains("export class TestServiceBatch {"));
+        assert!(ts.contains("  batch(): TestServiceBatch {"));
+        assert!(ts.contains("  mint(amount: Int128, cap: bigint): this {"));
+        assert!(ts.contains("Accepts the value sent in `options.value`."));
+        assert!(ts.contains("Callable only by the owner or accounts with the minter role."));
+        assert!(ts.contains("Not callable while the service is paused."));
+
+        // Clients of services that predate selectors cannot batch calls.
+        iface.functions[0].selector = None;
+        let ts = generate(&iface);
+        assert!(!ts.contains("TestServiceBatch"));
+    }
+}
@@ -0,0 +1,1216 @@
+//! A textual format of `Interface`s that is easier to read and review than JSON.
+//!
+//! ```text
+//! import geometry "0.1.0";
+//!
+//! /// A point in the plane.
+//! struct Point {
+//!     x: i32,
+//!     y: i32,
+//! }
+//!
+//! enum Shape {
+//!     Empty,
+//!     Circle(Point, u32),
+//!     Polygon { vertices: Vec<Point> },
+//! }
+//!
+//! event Drawn {
+//!     #[indexed] shape: Shape,
+//! }
+//!
+//! /// A canvas on which shapes are drawn.
+//! #[namespace = "canvas", version = "0.1.0", oasis_build_version = "0.2.2"]
+//! service Canvas {
+//!     constructor(width: u32, height: u32) -> Result<Self, string>;
+//!     fn draw(&mut self, shape: Shape) -> Result<(), geometry::Error>;
+//!     fn shapes(&self) -> Vec<Shape>;
+//! }
+//! ```
+//!
+//! The built-in types are `bool`, the integer types up to `u256`, `f32`, `f64`, `bytes`,
+//! `string`, `address`, tuples, arrays (`[T; N]`), `Vec<T>`, `Set<T>`, `Map<K, V>`,
+//! `Option<T>`, and `Result<T, E>`. Their names may not be used for defined types.
+//!
+//! A function whose calls may identify it by selector is preceded by its selector,
+//! e.g. `#[selector = "0x8a1f3c02"]`, and one that accepts value by `#[payable]`.
+//! The callers permitted to call a function are listed by `#[only(owner, role = "minter")]`,
+//! as in the service's source, and a function that cannot be called while the service
+//! is paused is marked `#[when_not_paused]`.
+//!
+//! `print` and `parse` are lossless: parsing the printed form of an interface yields the
+//! same interface, docs included.
+
+use std::fmt::{self, Write as _};
+
+use crate::{
+    Constructor, Field, Function, Import, IndexedField, Interface, Migration, Principal,
+    StateMutability, Type, TypeDef, Variant,
+};
+
+/// Returns the textual form of `iface`.
+pub fn print(iface: &Interface) -> String {
+    let mut out = String::new();
+    for import in iface.imports.iter() {
+        write!(out, "import {} {:?}", import.name, import.version).unwrap();
+        if let Some(registry) = &import.registry {
+            write!(out, " from {:?}", registry).unwrap();
+        }
+        writeln!(out, ";").unwrap();
+    }
+    if !iface.imports.is_empty() {
+        writeln!(out).unwrap();
+    }
+
+    for def in iface.type_defs.iter() {
+        print_type_def(&mut out, def);
+        writeln!(out).unwrap();
+    }
+
+    print_docs(&mut out, "", iface.docs.as_ref());
+    writeln!(
+        out,
+        "#[namespace = {:?}, version = {:?}, oasis_build_version = {:?}]",
+        iface.namespace, iface.version, iface.oasis_build_version
+    )
+    .unwrap();
+    writeln!(out, "service {} {{", iface.name).unwrap();
+    let ctor = &iface.constructor;
+    print_docs(&mut out, "    ", ctor.docs.as_ref());
+    write!(out, "    constructor").unwrap();
+    print_ctor_sig(&mut out, &ctor.inputs, &ctor.error);
+    if let Some(migration) = &iface.migration {
+        print_docs(&mut out, "    ", migration.docs.as_ref());
+        write!(out, "    migrate").unwrap();
+        print_ctor_sig(&mut out, &migration.inputs, &migration.error);
+    }
+    let mut prev_documented = false;
+    for func in iface.functions.iter() {
+        // Documented functions are set apart by blank lines.
+        if func.docs.is_some() || prev_documented {
+            writeln!(out).unwrap();
+        }
+        prev_documented = func.docs.is_some();
+        print_docs(&mut out, "    ", func.docs.as_ref());
+        if let Some(selector) = func.selector {
+            writeln!(out, "    #[selector = \"{:#010x}\"]", selector).unwrap();
+        }
+        if func.payable {
+            writeln!(out, "    #[payable]").unwrap();
+        }
+        if !func.only.is_empty() {
+            let principals: Vec<String> = func
+                .only
+                .iter()
+                .map(|principal| match principal {
+                    Principal::Owner => "owner".to_string(),
+                    Principal::Role(role) => format!("role = {:?}", role),
+                })
+                .collect();
+            writeln!(out, "    #[only({})]", principals.join(", ")).unwrap();
+        }
+        if func.when_not_paused {
+            writeln!(out, "    #[when_not_paused]").unwrap();
+        }
+        let receiver = match func.mutability {
+            StateMutability::Immutable => "&self",
+            StateMutability::Mutable => "&mut self",
+        };
+        write!(out, "    fn {}", func.name).unwrap();
+        print_inputs(&mut out, Some(receiver), &func.inputs);
+        if let Some(output) = &func.output {
+            write!(out, " -> {}", fmt_ty(output)).unwrap();
+        }
+        writeln!(out, ";").unwrap();
+    }
+    writeln!(out, "}}").unwrap();
+    out
+}
+
+fn print_type_def(out: &mut String, def: &TypeDef) {
+    print_docs(out, "", def.docs());
+    match def {
+        TypeDef::Struct { name, fields, .. }
+            if crate::is_tuple_struct(fields.iter().map(|f| f.name.as_str())) =>
+        {
+            write!(out, "struct {}(", name).unwrap();
+            if fields.iter().any(|f| f.docs.is_some()) {
+                writeln!(out).unwrap();
+                for field in fields.iter() {
+                    print_docs(out, "    ", field.docs.as_ref());
+                    writeln!(out, "    {},", fmt_ty(&field.ty)).unwrap();
+                }
+            } else {
+                let tys: Vec<_> = fields.iter().map(|f| fmt_ty(&f.ty)).collect();
+                write!(out, "{}", tys.join(", ")).unwrap();
+            }
+            writeln!(out, ");").unwrap();
+        }
+        TypeDef::Struct { name, fields, .. } => {
+            writeln!(out, "struct {} {{", name).unwrap();
+            print_fields(out, "    ", fields);
+            writeln!(out, "}}").unwrap();
+        }
+        TypeDef::Enum { name, variants, .. } => {
+            writeln!(out, "enum {} {{", name).unwrap();
+            for variant in variants.iter() {
+                match variant {
+                    Variant::Unit(name) => writeln!(out, "    {},", name).unwrap(),
+                    Variant::Tuple { name, items } => {
+                        let tys: Vec<_> = items.iter().map(fmt_ty).collect();
+                        writeln!(out, "    {}({}),", name, tys.join(", ")).unwrap();
+                    }
+                    Variant::Struct { name, fields } => {
+                        writeln!(out, "    {} {{", name).unwrap();
+                        print_fields(out, "        ", fields);
+                        writeln!(out, "    }},").unwrap();
+                    }
+                }
+            }
+            writeln!(out, "}}").unwrap();
+        }
+        TypeDef::Event { name, fields, .. } => {
+            writeln!(out, "event {} {{", name).unwrap();
+            for field in fields.iter() {
+                print_docs(out, "    ", field.docs.as_ref());
+                let indexed = if field.indexed { "#[indexed] " } else { "" };
+                writeln!(out, "    {}{}: {},", indexed, field.name, fmt_ty(&field.ty)).unwrap();
+            }
+            writeln!(out, "}}").unwrap();
+        }
+    }
+}
+
+fn print_fields(out: &mut String, indent: &str, fields: &[Field]) {
+    for field in fields.iter() {
+        print_docs(out, indent, field.docs.as_ref());
+        writeln!(out, "{}{}: {},", indent, field.name, fmt_ty(&field.ty)).unwrap();
+    }
+}
+
+/// Prints the parenthesized inputs of a function. Inputs are printed on separate lines
+/// if any of them has docs.
+fn print_inputs(out: &mut String, receiver: Option<&str>, inputs: &[Field]) {
+    if inputs.iter().any(|inp| inp.docs.is_some()) {
+        writeln!(out, "(").unwrap();
+        if let Some(receiver) = receiver {
+            writeln!(out, "        {},", receiver).unwrap();
+        }
+        print_fields(out, "        ", inputs);
+        write!(out, "    )").unwrap();
+        return;
+    }
+    let params: Vec<_> = receiver
+        .map(str::to_string)
+        .into_iter()
+        .chain(
+            inputs
+                .iter()
+                .map(|inp| format!("{}: {}", inp.name, fmt_ty(&inp.ty))),
+        )
+        .collect();
+    write!(out, "({})", params.join(", ")).unwrap();
+}
+
+fn print_ctor_sig(out: &mut String, inputs: &[Field], error: &Option<Type>) {
+    print_inputs(out, None, inputs);
+    if let Some(error) = error {
+        write!(out, " -> Result<Self, {}>", fmt_ty(error)).unwrap();
+    }
+    writeln!(out, ";").unwrap();
+}
+
+fn print_docs<S: AsRef<str>>(out: &mut String, indent: &str, docs: Option<S>) {
+    let docs = match &docs {
+        Some(docs) => docs.as_ref(),
+        None => return,
+    };
+    for line in docs.split('\n') {
+        if line.is_empty() {
+            writeln!(out, "{}///", indent).unwrap();
+        } else {
+            writeln!(out, "{}/// {}", indent, line).unwrap();
+        }
+    }
+}
+
+pub(crate) fn fmt_ty(ty: &Type) -> String {
+    match ty {
+        Type::Bool => "bool".to_string(),
+        Type::U8 => "u8".to_string(),
+        Type::I8 => "i8".to_string(),
+        Type::U16 => "u16".to_string(),
+        Type::I16 => "i16".to_string(),
+        Type::U32 => "u32".to_string(),
+        Type::I32 => "i32".to_string(),
+        Type::U64 => "u64".to_string(),
+        Type::I64 => "i64".to_string(),
+        Type::U128 => "u128".to_string(),
+        Type::I128 => "i128".to_string(),
+        Type::U256 => "u256".to_string(),
+        Type::F32 => "f32".to_string(),
+        Type::F64 => "f64".to_string(),
+        Type::Bytes => "bytes".to_string(),
+        Type::String => "string".to_string(),
+        Type::Address => "address".to_string(),
+        Type::Defined { namespace, ty } => match namespace {
+            Some(namespace) => format!("{}::{}", namespace, ty),
+            None => ty.to_string(),
+        },
+        Type::Tuple(tys) => match tys.as_slice() {
+            [ty] => format!("({},)", fmt_ty(ty)),
+            _ => format!(
+                "({})",
+                tys.iter().map(fmt_ty).collect::<Vec<_>>().join(", ")
+            ),
+        },
+        Type::Array(ty, count) => format!("[{}; {}]", fmt_ty(ty), count),
+        Type::List(ty) => format!("Vec<{}>", fmt_ty(ty)),
+        Type::Set(ty) => format!("Set<{}>", fmt_ty(ty)),
+        Type::Map(k_ty, v_ty) => format!("Map<{}, {}>", fmt_ty(k_ty), fmt_ty(v_ty)),
+        Type::Optional(ty) => format!("Option<{}>", fmt_ty(ty)),
+        Type::Result(ok_ty, err_ty) => format!("Result<{}, {}>", fmt_ty(ok_ty), fmt_ty(err_ty)),
+    }
+}
+
+/// Parses the textual form of an interface. The result is not validated.
+pub fn parse(src: &str) -> Result<Interface, ParseError> {
+    let tokens = Lexer::new(src).tokenize()?;
+    Parser::new(tokens).interface()
+}
+
+#[derive(Clone, Debug, PartialEq, failure::Fail)]
+#[fail(display = "{}:{}: {}", line, column, message)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+/// The value of an attribute, e.g., of `#[payable]`, `#[version = "0.1.0"]`, or
+/// `#[only(owner, role = "minter")]`.
+#[derive(Debug)]
+enum AttrValue {
+    Flag,
+    Str(String),
+    List(Vec<(String, AttrValue)>),
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Pos {
+    line: usize,
+    column: usize,
+}
+
+impl Pos {
+    fn error<T>(self, message: String) -> Result<T, ParseError> {
+        Err(ParseError {
+            line: self.line,
+            column: self.column,
+            message,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    /// An identifier, keyword, or integer.
+    Word(String),
+    Str(String),
+    /// A line of a doc comment, without the leading `///` and space.
+    Doc(String),
+    Punct(&'static str),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Token::Word(word) => write!(f, "`{}`", word),
+            Token::Str(_) => write!(f, "string literal"),
+            Token::Doc(_) => write!(f, "doc comment"),
+            Token::Punct(punct) => write!(f, "`{}`", punct),
+        }
+    }
+}
+
+const PUNCTS: &[&str] = &[
+    "->", "::", "{", "}", "(", ")", "[", "]", "<", ">", ",", ";", ":", "=", "#", "&",
+];
+
+struct Lexer {
+    chars: Vec<char>,
+    idx: usize,
+    pos: Pos,
+}
+
+impl Lexer {
+    fn new(src: &str) -> Self {
+        Self {
+            chars: src.chars().collect(),
+            idx: 0,
+            pos: Pos { line: 1, column: 1 },
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.idx).copied()
+    }
+
+    fn starts_with(&self, s: &str) -> bool {
+        s.chars()
+            .enumerate()
+            .all(|(i, ch)| self.chars.get(self.idx + i) == Some(&ch))
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let ch = self.peek()?;
+        self.idx += 1;
+        if ch == '\n' {
+            self.pos.line += 1;
+            self.pos.column = 1;
+        } else {
+            self.pos.column += 1;
+        }
+        Some(ch)
+    }
+
+    fn take_line(&mut self) -> String {
+        let mut line = String::new();
+        while let Some(ch) = self.peek() {
+            if ch == '\n' {
+                break;
+            }
+            line.push(ch);
+            self.bump();
+        }
+        line
+    }
+
+    fn tokenize(mut self) -> Result<Vec<(Token, Pos)>, ParseError> {
+        let mut tokens = Vec::new();
+        while let Some(ch) = self.peek() {
+            let pos = self.pos;
+            if ch.is_whitespace() {
+                self.bump();
+            } else if self.starts_with("///") {
+                self.idx += 3;
+                self.pos.column += 3;
+                let line = self.take_line();
+                let line = if line.starts_with(' ') {
+                    line[1..].to_string()
+                } else {
+                    line
+                };
+                tokens.push((Token::Doc(line), pos));
+            } else if self.starts_with("//") {
+                self.take_line();
+            } else if ch.is_ascii_alphanumeric() || ch == '_' {
+                let mut word = String::new();
+                while let Some(ch) = self.peek() {
+                    if !(ch.is_ascii_alphanumeric() || ch == '_') {
+                        break;
+                    }
+                    word.push(ch);
+                    self.bump();
+                }
+                tokens.push((Token::Word(word), pos));
+            } else if ch == '"' {
+                self.bump();
+                tokens.push((Token::Str(self.string(pos)?), pos));
+            } else {
+                match PUNCTS.iter().find(|punct| self.starts_with(punct)) {
+                    Some(punct) => {
+                        for _ in 0..punct.len() {
+                            self.bump();
+                        }
+                        tokens.push((Token::Punct(punct), pos));
+                    }
+                    None => return pos.error(format!("unexpected character `{}`", ch)),
+                }
+            }
+        }
+        Ok(tokens)
+    }
+
+    /// Lexes the remainder of a string literal that starts at `start`.
+    fn string(&mut self, start: Pos) -> Result<String, ParseError> {
+        let mut s = String::new();
+        loop {
+            let pos = self.pos;
+            match self.bump() {
+                Some('"') => return Ok(s),
+                Some('\\') => s.push(match self.bump() {
+                    Some('n') => '\n',
+                    Some('r') => '\r',
+                    Some('t') => '\t',
+                    Some('0') => '\0',
+                    Some(ch @ '\\') | Some(ch @ '"') | Some(ch @ '\'') => ch,
+                    Some('u') if self.peek() == Some('{') => {
+                        self.bump();
+                        let mut hex = String::new();
+                        while let Some(ch) = self.bump() {
+                            if ch == '}' {
+                                break;
+                            }
+                            hex.push(ch);
+                        }
+                        match u32::from_str_radix(&hex, 16)
+                            .ok()
+                            .and_then(std::char::from_u32)
+                        {
+                            Some(ch) => ch,
+                            None => return pos.error(format!("invalid escape `\\u{{{}}}`", hex)),
+                        }
+                    }
+                    _ => return pos.error("invalid escape in string literal".to_string()),
+                }),
+                Some(ch) => s.push(ch),
+                None => return start.error("unterminated string literal".to_string()),
+            }
+        }
+    }
+}
+
+struct Parser {
+    tokens: Vec<(Token, Pos)>,
+    idx: usize,
+}
+
+impl Parser {
+    fn new(tokens: Vec<(Token, Pos)>) -> Self {
+        Self { tokens, idx: 0 }
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.idx).map(|(token, _)| token)
+    }
+
+    fn pos(&self) -> Pos {
+        match self.tokens.get(self.idx).or_else(|| self.tokens.last()) {
+            Some((_, pos)) => *pos,
+            None => Pos { line: 1, column: 1 },
+        }
+    }
+
+    fn unexpected<T>(&self, expected: &str) -> Result<T, ParseError> {
+        let found = match self.peek() {
+            Some(token) => token.to_string(),
+            None => "end of input".to_string(),
+        };
+        self.pos()
+            .error(format!("expected {}, found {}", expected, found))
+    }
+
+    fn eat(&mut self, punct: &str) -> bool {
+        match self.peek() {
+            Some(Token::Punct(p)) if *p == punct => {
+                self.idx += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn expect(&mut self, punct: &str) -> Result<(), ParseError> {
+        if self.eat(punct) {
+            Ok(())
+        } else {
+            self.unexpected(&format!("`{}`", punct))
+        }
+    }
+
+    fn eat_word(&mut self, word: &str) -> bool {
+        match self.peek() {
+            Some(Token::Word(w)) if w == word => {
+                self.idx += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn expect_word(&mut self, word: &str) -> Result<(), ParseError> {
+        if self.eat_word(word) {
+            Ok(())
+        } else {
+            self.unexpected(&format!("`{}`", word))
+        }
+    }
+
+    fn ident(&mut self) -> Result<String, ParseError> {
+        match self.peek() {
+            Some(Token::Word(word)) => {
+                let word = word.to_string();
+                self.idx += 1;
+                Ok(word)
+            }
+            _ => self.unexpected("identifier"),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, ParseError> {
+        match self.peek() {
+            Some(Token::Str(s)) => {
+                let s = s.to_string();
+                self.idx += 1;
+                Ok(s)
+            }
+            _ => self.unexpected("string literal"),
+        }
+    }
+
+    fn docs(&mut self) -> Option<String> {
+        let mut lines = Vec::new();
+        while let Some(Token::Doc(line)) = self.peek() {
+            lines.push(line.to_string());
+            self.idx += 1;
+        }
+        if lines.is_empty() {
+            None
+        } else {
+            Some(lines.join("\n"))
+        }
+    }
+
+    fn no_docs(&mut self, what: &str) -> Result<(), ParseError> {
+        let pos = self.pos();
+        match self.docs() {
+            Some(_) => pos.error(format!("{} cannot have docs", what)),
+            None => Ok(()),
+        }
+    }
+
+    fn interface(&mut self) -> Result<Interface, ParseError> {
+        let mut imports = Vec::new();
+        let mut type_defs = Vec::new();
+        let mut service: Option<Interface> = None;
+        while self.peek().is_some() {
+            let docs = self.docs();
+            let attrs_pos = self.pos();
+            let attrs = self.attrs()?;
+            let pos = self.pos();
+            let keyword = self.ident()?;
+            if !attrs.is_empty() && keyword != "service" {
+                return attrs_pos.error("only services have attributes".to_string());
+            }
+            match keyword.as_str() {
+                "import" if docs.is_none() => imports.push(self.import()?),
+                "import" => return pos.error("imports cannot have docs".to_string()),
+                "struct" => type_defs.push(self.strukt(docs)?),
+                "enum" => type_defs.push(self.enumm(docs)?),
+                "event" => type_defs.push(self.event(docs)?),
+                "service" if service.is_some() => {
+                    return pos.error("an interface has only one service".to_string())
+                }
+                "service" => service = Some(self.service(docs, attrs, attrs_pos)?),
+                _ => {
+                    return pos.error(format!(
+                        "expected `import`, `struct`, `enum`, `event`, or `service`, found `{}`",
+                        keyword
+                    ))
+                }
+            }
+        }
+        match service {
+            Some(mut iface) => {
+                iface.imports = imports;
+                iface.type_defs = type_defs;
+                Ok(iface)
+            }
+            None => self.pos().error("missing service".to_string()),
+        }
+    }
+
+    /// Parses any number of `#[key = "value", flag, list(...)]` attributes.
+    fn attrs(&mut self) -> Result<Vec<(String, AttrValue)>, ParseError> {
+        let mut attrs = Vec::new();
+        while self.eat("#") {
+            self.expect("[")?;
+            attrs.append(&mut self.attr_list("]")?);
+        }
+        Ok(attrs)
+    }
+
+    /// Parses comma-separated attributes up to and including `close`.
+    fn attr_list(&mut self, close: &str) -> Result<Vec<(String, AttrValue)>, ParseError> {
+        let mut attrs = Vec::new();
+        loop {
+            let key = self.ident()?;
+            let value = if self.eat("=") {
+                AttrValue::Str(self.string()?)
+            } else if self.eat("(") {
+                AttrValue::List(self.attr_list(")")?)
+            } else {
+                AttrValue::Flag
+            };
+            attrs.push((key, value));
+            if !self.eat(",") {
+                break;
+            }
+        }
+        self.expect(close)?;
+        Ok(attrs)
+    }
+
+    fn import(&mut self) -> Result<Import, ParseError> {
+        let name = self.ident()?;
+        let version = self.string()?;
+        let registry = if self.eat_word("from") {
+            Some(self.string()?)
+        } else {
+            None
+        };
+        self.expect(";")?;
+        Ok(Import {
+            name,
+            version,
+            registry,
+        })
+    }
+
+    fn strukt(&mut self, docs: Option<String>) -> Result<TypeDef, ParseError> {
+        let name = self.ident()?;
+        let fields = if self.eat("(") {
+            let mut fields = Vec::new();
+            while !self.eat(")") {
+                fields.push(Field {
+                    docs: self.docs(),
+                    name: fields.len().to_string(),
+                    ty: self.ty()?,
+                });
+                if !self.eat(",") {
+                    self.expect(")")?;
+                    break;
+                }
+            }
+            self.expect(";")?;
+            fields
+        } else {
+            self.expect("{")?;
+            self.fields("}")?
+        };
+        Ok(TypeDef::Struct { name, fields, docs })
+    }
+
+    fn enumm(&mut self, docs: Option<String>) -> Result<TypeDef, ParseError> {
+        let name = self.ident()?;
+        self.expect("{")?;
+        let mut variants = Vec::new();
+        while !self.eat("}") {
+            self.no_docs("enum variants")?;
+            let name = self.ident()?;
+            variants.push(if self.eat("(") {
+                Variant::Tuple {
+                    name,
+                    items: self.tys(")")?,
+                }
+            } else if self.eat("{") {
+                Variant::Struct {
+                    name,
+                    fields: self.fields("}")?,
+                }
+            } else {
+                Variant::Unit(name)
+            });
+            if !self.eat(",") {
+                self.expect("}")?;
+                break;
+            }
+        }
+        Ok(TypeDef::Enum {
+            name,
+            variants,
+            docs,
+        })
+    }
+
+    fn event(&mut self, docs: Option<String>) -> Result<TypeDef, ParseError> {
+        let name = self.ident()?;
+        self.expect("{")?;
+        let mut fields = Vec::new();
+        while !self.eat("}") {
+            let docs = self.docs();
+            let indexed = self.eat("#");
+            if indexed {
+                self.expect("[")?;
+                self.expect_word("indexed")?;
+                self.expect("]")?;
+            }
+            let name = self.ident()?;
+            self.expect(":")?;
+            fields.push(IndexedField {
+                name,
+                ty: self.ty()?,
+                indexed,
+                docs,
+            });
+            if !self.eat(",") {
+                self.expect("}")?;
+                break;
+            }
+        }
+        Ok(TypeDef::Event { name, fields, docs })
+    }
+
+    fn service(
+        &mut self,
+        docs: Option<String>,
+        attrs: Vec<(String, AttrValue)>,
+        attrs_pos: Pos,
+    ) -> Result<Interface, ParseError> {
+        let (mut namespace, mut version, mut oasis_build_version) = (None, None, None);
+        for (key, value) in attrs {
+            let attr = match key.as_str() {
+                "namespace" => &mut namespace,
+                "version" => &mut version,
+                "oasis_build_version" => &mut oasis_build_version,
+                _ => return attrs_pos.error(format!("unknown service attribute `{}`", key)),
+            };
+            let value = match value {
+                AttrValue::Str(value) => value,
+                _ => return attrs_pos.error(format!("service attribute `{}` needs a value", key)),
+            };
+            if attr.replace(value).is_some() {
+                return attrs_pos.error(format!("duplicate service attribute `{}`", key));
+            }
+        }
+        let required = |attr: Option<String>, key: &str| match attr {
+            Some(value) => Ok(value),
+            None => attrs_pos.error(format!("missing service attribute `{}`", key)),
+        };
+        let namespace = required(namespace, "namespace")?;
+        let version = required(version, "version")?;
+        let oasis_build_version = required(oasis_build_version, "oasis_build_version")?;
+
+        let name = self.ident()?;
+        self.expect("{")?;
+        let mut constructor = None;
+        let mut migration = None;
+        let mut functions = Vec::new();
+        while !self.eat("}") {
+            let docs = self.docs();
+            let attrs_pos = self.pos();
+            let attrs = self.attrs()?;
+            let pos = self.pos();
+            let keyword = self.ident()?;
+            if !attrs.is_empty() && keyword != "fn" {
+                return attrs_pos.error("only functions have attributes".to_string());
+            }
+            match keyword.as_str() {
+                "constructor" if constructor.is_some() => {
+                    return pos.error("a service has only one constructor".to_string())
+                }
+                "constructor" => {
+                    let (inputs, error) = self.ctor_sig()?;
+                    constructor = Some(Constructor {
+                        inputs,
+                        error,
+                        docs,
+                    });
+                }
+                "migrate" if migration.is_some() => {
+                    return pos.error("a service has only one migration".to_string())
+                }
+                "migrate" => {
+                    let (inputs, error) = self.ctor_sig()?;
+                    migration = Some(Migration {
+                        inputs,
+                        error,
+                        docs,
+                    });
+                }
+                "fn" => functions.push(self.function(docs, attrs, attrs_pos)?),
+                other => {
+                    return pos.error(format!(
+                        "expected `fn`, `constructor`, or `migrate`, found `{}`",
+                        other
+                    ))
+                }
+            }
+        }
+        let constructor = match constructor {
+            Some(constructor) => constructor,
+            None => return self.pos().error(format!("`{}` has no constructor", name)),
+        };
+        Ok(Interface {
+            name,
+            namespace,
+            version,
+            imports: Vec::new(),
+            type_defs: Vec::new(),
+            constructor,
+            migration,
+            functions,
+            oasis_build_version,
+            docs,
+        })
+    }
+
+    /// Parses `(inputs) [-> Result<Self, E>];`.
+    fn ctor_sig(&mut self) -> Result<(Vec<Field>, Option<Type>), ParseError> {
+        self.expect("(")?;
+        let inputs = self.fields(")")?;
+        let error = if self.eat("->") {
+            self.expect_word("Result")?;
+            self.expect("<")?;
+            self.expect_word("Self")?;
+            self.expect(",")?;
+            let error = self.ty()?;
+            self.expect(">")?;
+            Some(error)
+        } else {
+            None
+        };
+        self.expect(";")?;
+        Ok((inputs, error))
+    }
+
+    fn function(
+        &mut self,
+        docs: Option<String>,
+        attrs: Vec<(String, AttrValue)>,
+        attrs_pos: Pos,
+    ) -> Result<Function, ParseError> {
+        let mut selector = None;
+        let (mut payable, mut when_not_paused) = (false, false);
+        let mut only = Vec::new();
+        for (key, value) in attrs {
+            match (key.as_str(), value) {
+                ("selector", _) if selector.is_some() => {
+                    return attrs_pos.error("duplicate function attribute `selector`".to_string())
+                }
+                ("selector", AttrValue::Str(value)) => {
+                    let parsed = if value.starts_with("0x") {
+                        u32::from_str_radix(&value[2..], 16).ok()
+                    } else {
+                        None
+                    };
+                    match parsed {
+                        Some(parsed) => selector = Some(parsed),
+                        None => {
+                            return attrs_pos.error(format!("invalid selector `{}`", value));
+                        }
+                    }
+                }
+                ("selector", _) => {
+                    return attrs_pos
+                        .error("function attribute `selector` needs a value".to_string())
+                }
+                ("payable", AttrValue::Flag) if !payable => payable = true,
+                ("when_not_paused", AttrValue::Flag) if !when_not_paused => when_not_paused = true,
+                ("only", AttrValue::List(_)) if !only.is_empty() => {
+                    return attrs_pos.error("duplicate function attribute `only`".to_string())
+                }
+                ("only", AttrValue::List(principals)) => {
+                    for principal in principals {
+                        let principal = match principal {
+                            (key, AttrValue::Flag) if key == "owner" => Principal::Owner,
+                            (key, AttrValue::Str(role)) if key == "role" => Principal::Role(role),
+                            (key, _) => {
+                                return attrs_pos.error(format!(
+                                    "expected `owner` or `role = \"name\"`, found `{}`",
+                                    key
+                                ))
+                            }
+                        };
+                        if only.contains(&principal) {
+                            return attrs_pos.error("duplicate principal in `only`".to_string());
+                        }
+                        only.push(principal);
+                    }
+                }
+                ("only", _) => {
+                    return attrs_pos
+                        .error("function attribute `only` needs a list of principals".to_string())
+                }
+                ("payable", AttrValue::Flag) | ("when_not_paused", AttrValue::Flag) => {
+                    return attrs_pos.error(format!("duplicate function attribute `{}`", key))
+                }
+                ("payable", _) | ("when_not_paused", _) => {
+                    return attrs_pos.error(format!("function attribute `{}` has no value", key))
+                }
+                _ => return attrs_pos.error(format!("unknown function attribute `{}`", key)),
+            }
+        }
+        let name = self.ident()?;
+        self.expect("(")?;
+        self.expect("&")?;
+        let mutability = if self.eat_word("mut") {
+            StateMutability::Mutable
+        } else {
+            StateMutability::Immutable
+        };
+        self.expect_word("self")?;
+        let inputs = if self.eat(",") {
+            self.fields(")")?
+        } else {
+            self.expect(")")?;
+            Vec::new()
+        };
+        let output = if self.eat("->") {
+            Some(self.ty()?)
+        } else {
+            None
+        };
+        self.expect(";")?;
+        Ok(Function {
+            name,
+            mutability,
+            inputs,
+            output,
+            docs,
+            selector,
+            payable,
+            only,
+            when_not_paused,
+        })
+    }
+
+    /// Parses comma-separated `name: Type` fields up to and including `close`.
+    fn fields(&mut self, close: &str) -> Result<Vec<Field>, ParseError> {
+        let mut fields = Vec::new();
+        while !self.eat(close) {
+            let docs = self.docs();
+            let name = self.ident()?;
+            self.expect(":")?;
+            fields.push(Field {
+                name,
+                ty: self.ty()?,
+                docs,
+            });
+            if !self.eat(",") {
+                self.expect(close)?;
+                break;
+            }
+        }
+        Ok(fields)
+    }
+
+    /// Parses comma-separated types up to and including `close`.
+    fn tys(&mut self, close: &str) -> Result<Vec<Type>, ParseError> {
+        let mut tys = Vec::new();
+        while !self.eat(close) {
+            tys.push(self.ty()?);
+            if !self.eat(",") {
+                self.expect(close)?;
+                break;
+            }
+        }
+        Ok(tys)
+    }
+
+    /// Parses the `N` type parameters of a generic built-in type.
+    fn ty_params(&mut self, n: usize) -> Result<Vec<Type>, ParseError> {
+        self.expect("<")?;
+        let mut params = Vec::with_capacity(n);
+        for i in 0..n {
+            if i > 0 {
+                self.expect(",")?;
+            }
+            params.push(self.ty()?);
+        }
+        self.expect(">")?;
+        Ok(params)
+    }
+
+    fn ty(&mut self) -> Result<Type, ParseError> {
+        if self.eat("(") {
+            return Ok(Type::Tuple(self.tys(")")?));
+        }
+        if self.eat("[") {
+            let ty = self.ty()?;
+            self.expect(";")?;
+            let pos = self.pos();
+            let count = match self.ident()?.parse() {
+                Ok(count) => count,
+                Err(_) => return pos.error("expected array length".to_string()),
+            };
+            self.expect("]")?;
+            return Ok(Type::Array(Box::new(ty), count));
+        }
+        let name = match self.peek() {
+            Some(Token::Word(_)) => self.ident()?,
+            _ => return self.unexpected("type"),
+        };
+        if self.eat("::") {
+            return Ok(Type::Defined {
+                namespace: Some(name),
+                ty: self.ident()?,
+            });
+        }
+        Ok(match name.as_str() {
+            "bool" => Type::Bool,
+            "u8" => Type::U8,
+            "i8" => Type::I8,
+            "u16" => Type::U16,
+            "i16" => Type::I16,
+            "u32" => Type::U32,
+            "i32" => Type::I32,
+            "u64" => Type::U64,
+            "i64" => Type::I64,
+            "u128" => Type::U128,
+            "i128" => Type::I128,
+            "u256" => Type::U256,
+            "f32" => Type::F32,
+            "f64" => Type::F64,
+            "bytes" => Type::Bytes,
+            "string" => Type::String,
+            "address" => Type::Address,
+            "Vec" => Type::List(Box::new(self.ty_params(1)?.remove(0))),
+            "Set" => Type::Set(Box::new(self.ty_params(1)?.remove(0))),
+            "Option" => Type::Optional(Box::new(self.ty_params(1)?.remove(0))),
+            "Map" | "Result" => {
+                let mut params = self.ty_params(2)?;
+                let (first, second) = (Box::new(params.remove(0)), Box::new(params.remove(0)));
+                if name == "Map" {
+                    Type::Map(first, second)
+                } else {
+                    Type::Result(first, second)
+                }
+            }
+            _ => Type::Defined {
+                namespace: None,
+                ty: name,
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CANVAS: &str = r#"
+        // Comments are ignored.
+        import geometry "0.1.0" from "https://registry.example.com";
+
+        /// A point in the plane.
+        ///
+        ///  Indented.
+        struct Point(i32, i32);
+
+        enum Shape {
+            Empty,
+            Circle(Point, u32),
+            Polygon { vertices: Vec<Point> },
+        }
+
+        event Drawn {
+            #[indexed] shape: Shape,
+            /// When the shape was drawn.
+            at: Option<(u64,)>,
+        }
+
+        #[namespace = "canvas", version = "0.1.0"]
+        #[oasis_build_version = "0.2.2"]
+        service Canvas {
+            constructor(width: u32, height: u32) -> Result<Self, string>;
+            /// Clears the canvas of a previous version.
+            migrate() -> Result<Self, string>;
+            #[selector = "0x00c0ffee", payable]
+            #[only(owner, role = "artist"), when_not_paused]
+            fn draw(
+                &mut self,
+                /// The shape to draw.
+                shape: Shape,
+            ) -> Result<(), geometry::Error>;
+            fn pixels(&self) -> Map<[u8; 2], Set<address>>;
+        }
+    "#;
+
+    #[test]
+    fn test_parse() {
+        let iface = parse(CANVAS).unwrap();
+        assert_eq!(iface.name, "Canvas");
+        assert_eq!(iface.namespace, "canvas");
+        assert_eq!(
+            iface.imports[0].registry.as_ref().map(String::as_str),
+            Some("https://registry.example.com")
+        );
+        assert_eq!(
+            iface.type_defs[0].docs(),
+            Some("A point in the plane.\n\n Indented.")
+        );
+        match &iface.type_defs[2] {
+            TypeDef::Event { fields, .. } => {
+                assert!(fields[0].indexed && !fields[1].indexed);
+                assert_eq!(
+                    fields[1].ty,
+                    Type::Optional(Box::new(Type::Tuple(vec![Type::U64])))
+                );
+            }
+            def => panic!("expected event, found {:?}", def),
+        }
+        assert_eq!(iface.constructor.error, Some(Type::String));
+        assert_eq!(
+            iface.migration.as_ref().and_then(|m| m.docs.as_ref()),
+            Some(&"Clears the canvas of a previous version.".to_string())
+        );
+        assert_eq!(iface.functions[0].mutability, StateMutability::Mutable);
+        assert_eq!(iface.functions[0].selector, Some(0x00c0_ffee));
+        assert_eq!(iface.functions[1].selector, None);
+        assert!(iface.functions[0].payable && !iface.functions[1].payable);
+        assert_eq!(
+            iface.functions[0].only,
+            vec![Principal::Owner, Principal::Role("artist".to_string())]
+        );
+        assert!(iface.functions[0].when_not_paused && iface.functions[1].only.is_empty());
+        assert_eq!(
+            iface.functions[0].inputs[0]
+                .docs
+                .as_ref()
+                .map(String::as_str),
+            Some("The shape to draw.")
+        );
+        assert_eq!(
+            iface.functions[1].output,
+            Some(Type::Map(
+                Box::new(Type::Array(Box::new(Type::U8), 2)),
+                Box::new(Type::Set(Box::new(Type::Address)))
+            ))
+        );
+        assert_eq!(parse(&print(&iface)).unwrap(), iface);
+    }
+
+    #[test]
+    fn test_round_trip_fixtures() {
+        for res_dir in &["../tests/idl-gen/res", "../tests/xcc-a/res"] {
+            let res_dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join(res_dir);
+            for entry in std::fs::read_dir(res_dir).unwrap() {
+                let iface: Interface =
+                    serde_json::from_slice(&std::fs::read(entry.unwrap().path()).unwrap()).unwrap();
+                let printed = print(&iface);
+                let parsed = parse(&printed).unwrap_or_else(|err| panic!("{}\n{}", err, printed));
+                assert_eq!(parsed, iface);
+                assert_eq!(print(&parsed), printed);
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = |src: &str| parse(src).unwrap_err().to_string();
+        assert_eq!(err(""), "1:1: missing service");
+        assert_eq!(
+            err("struct S {\n    x: u8;\n}"),
+            "2:10: expected `}`, found `;`"
+        );
+        assert_eq!(
+            err("#[version = \"0.1.0\"]\nservice S {}"),
+            "1:1: missing service attribute `namespace`"
+        );
+        assert_eq!(
+            err("enum E {\n    /// A variant.\n    V,\n}"),
+            "2:5: enum variants cannot have docs"
+        );
+        assert_eq!(
+            err("#[namespace = \"s\", version = \"0.1.0\", oasis_build_version = \"0.2.2\"]\nservice S {\n    #[selector = \"12\"]\n    fn f(&self);\n}"),
+            "3:5: invalid selector `12`"
+        );
+        assert_eq!(
+            err("#[namespace = \"s\", version = \"0.1.0\", oasis_build_version = \"0.2.2\"]\nservice S {\n    #[only(admin)]\n    fn f(&self);\n}"),
+            "3:5: expected `owner` or `role = \"name\"`, found `admin`"
+        );
+        assert_eq!(err("import a \"0.1.0"), "1:10: unterminated string literal");
+    }
+}
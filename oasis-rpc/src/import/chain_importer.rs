@@ -0,0 +1,132 @@
+use blockchain_traits::{Block, Blockchain as _};
+use memchain::{Memchain, StateDump};
+use oasis_types::Address;
+
+use crate::import::ImportedService;
+
+use super::{ImportError, ImporterBackend};
+
+/// Imports the service deployed at an address of a chain restored from a memchain state dump.
+pub struct ChainImporter {
+    pub chain: Memchain<'static>,
+    pub address: Address,
+}
+
+impl ChainImporter {
+    pub fn new(dump_path: &std::path::Path, address: &str) -> Result<Self, ImportError> {
+        let dump_path_str = dump_path.display().to_string();
+        let dump = std::fs::read_to_string(dump_path)
+            .map_err(|err| ImportError::Io(dump_path_str.clone(), err))?;
+        let importer_err =
+            |err| ImportError::Importer(failure::format_err!("`{}`: {}", dump_path_str, err));
+        let dump = StateDump::from_json(&dump).map_err(importer_err)?;
+        let chain = Memchain::from_dump(dump_path_str.as_str(), &dump, 0).map_err(importer_err)?;
+        let address = address.trim_start_matches("0x").parse().map_err(|_| {
+            ImportError::Importer(failure::format_err!("Invalid address `{}`", address))
+        })?;
+        Ok(Self { chain, address })
+    }
+}
+
+impl ImporterBackend for ChainImporter {
+    fn import(
+        &self,
+        name: &str,
+        version_req: &semver::VersionReq,
+    ) -> Result<ImportedService, ImportError> {
+        let service = import_deployed(self.chain.last_block(), &self.address)?;
+        if !super::is_named(&service.interface, name) {
+            return Err(ImportError::Importer(failure::format_err!(
+                "The service at {} is `{}`, not `{}`",
+                self.address,
+                service.interface.name,
+                name
+            )));
+        }
+        if !version_req.matches(&super::version_of(&service.interface)?) {
+            return Err(ImportError::NoMatchingVersion {
+                name: name.to_string(),
+                version_req: version_req.to_string(),
+                versions: vec![service.interface.version],
+            });
+        }
+        Ok(service)
+    }
+
+    fn import_all(&self) -> Result<Vec<ImportedService>, ImportError> {
+        Ok(vec![import_deployed(
+            self.chain.last_block(),
+            &self.address,
+        )?])
+    }
+}
+
+/// Imports the service whose code is deployed at `address` as of `block`.
+pub fn import_deployed<B>(block: &B, address: &Address) -> Result<ImportedService, ImportError>
+where
+    B: Block<Address = Address> + ?Sized,
+{
+    let code = match block.code_at(address) {
+        Some(code) if !code.is_empty() => code,
+        _ => {
+            return Err(ImportError::Importer(failure::format_err!(
+                "No service is deployed at {}",
+                address
+            )))
+        }
+    };
+    let mut service = ImportedService::from_bytecode(code.to_vec())?;
+    service.address = Some(address.as_ref().to_vec());
+    Ok(service)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::{borrow::Cow, collections::HashMap};
+
+    #[test]
+    fn test_import_deployed() {
+        let iface = crate::testing::test_service();
+        let code = crate::testing::service_wasm(&iface);
+
+        let service_addr = Address([1u8; 20]);
+        let user_addr = Address([2u8; 20]);
+        let mut genesis_state = HashMap::new();
+        genesis_state.insert(
+            service_addr,
+            Cow::Owned(memchain::Account {
+                code: code.clone(),
+                ..Default::default()
+            }),
+        );
+        genesis_state.insert(user_addr, Cow::Owned(memchain::Account::default()));
+        let chain = Memchain::new("devnet", genesis_state, 0);
+
+        let service = import_deployed(chain.last_block(), &service_addr).unwrap();
+        assert_eq!(service.bytecode, code);
+        assert_eq!(service.interface, iface);
+        assert_eq!(service.address, Some(vec![1u8; 20]));
+        assert!(import_deployed(chain.last_block(), &user_addr).is_err());
+        assert!(import_deployed(chain.last_block(), &Address([3u8; 20])).is_err());
+
+        let dir = crate::testing::TempDir::new("chain-importer");
+        let dump_path = dir.path().join("devnet.json");
+        std::fs::write(&dump_path, chain.dump_state().to_json()).unwrap();
+        let url = format!(
+            "file://{}?address={}&version=0.1",
+            dump_path.display(),
+            service_addr
+        );
+        let importer = crate::import::Importer::for_url(&url, Default::default()).unwrap();
+        let imported = importer.import("test-service").unwrap();
+        assert_eq!(imported.bytecode, code);
+        assert_eq!(imported.address, Some(vec![1u8; 20]));
+        assert!(importer.import("OtherService").is_err());
+
+        let url = format!("file://{}?address={}", dump_path.display(), user_addr);
+        let importer = crate::import::Importer::for_url(&url, Default::default()).unwrap();
+        assert!(importer.import_all().is_err());
+    }
+}
@@ -0,0 +1,173 @@
+use std::path::{Path, PathBuf};
+
+use crate::import::ImportedService;
+
+use super::{file_importer::FileImporter, ImportError, ImporterBackend};
+
+/// Imports the services in a directory and its subdirectories, so that many services
+/// (e.g., those of a workspace) can be imported from one location.
+pub struct DirImporter {
+    pub path: PathBuf,
+}
+
+impl DirImporter {
+    /// Returns every service found in the directory, along with the path of its bytecode.
+    /// Wasm modules that have no interface section (i.e. are not services) are skipped.
+    fn index(&self) -> Result<Vec<(PathBuf, ImportedService)>, ImportError> {
+        let mut wasm_paths = Vec::new();
+        find_wasm(&self.path, &mut wasm_paths)?;
+        let mut services = Vec::with_capacity(wasm_paths.len());
+        for path in wasm_paths {
+            match (FileImporter { path: path.clone() }).import_all() {
+                Ok(imported) => services.extend(imported.into_iter().map(|s| (path.clone(), s))),
+                Err(ImportError::MissingInterfaceSection) => continue,
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(services)
+    }
+}
+
+impl ImporterBackend for DirImporter {
+    fn import(
+        &self,
+        name: &str,
+        version_req: &semver::VersionReq,
+    ) -> Result<ImportedService, ImportError> {
+        let mut candidates = Vec::new();
+        for (path, service) in self.index()? {
+            if super::is_named(&service.interface, name) {
+                candidates.push((super::version_of(&service.interface)?, path, service));
+            }
+        }
+        if candidates.is_empty() {
+            return Err(ImportError::NoImport(name.to_string()));
+        }
+        candidates.sort_by(|a, b| a.0.cmp(&b.0));
+        let mut versions: Vec<_> = candidates
+            .iter()
+            .map(|(version, ..)| version.to_string())
+            .collect();
+        versions.dedup();
+
+        let mut matching: Vec<_> = candidates
+            .into_iter()
+            .filter(|(version, ..)| version_req.matches(version))
+            .collect();
+        let (version, path, service) =
+            matching
+                .pop()
+                .ok_or_else(|| ImportError::NoMatchingVersion {
+                    name: name.to_string(),
+                    version_req: version_req.to_string(),
+                    versions,
+                })?;
+        // The same build may be found more than once (e.g., in `target/*/deps`).
+        if let Some((_, other_path, other)) = matching.last() {
+            if other.interface.version == service.interface.version
+                && other.bytecode != service.bytecode
+            {
+                return Err(ImportError::Importer(failure::format_err!(
+                    "Both `{}` and `{}` provide `{}` v{}",
+                    other_path.display(),
+                    path.display(),
+                    name,
+                    version
+                )));
+            }
+        }
+        Ok(service)
+    }
+
+    fn import_all(&self) -> Result<Vec<ImportedService>, ImportError> {
+        Ok(self
+            .index()?
+            .into_iter()
+            .map(|(_, service)| service)
+            .collect())
+    }
+}
+
+/// Appends the paths of the `.wasm` files in `dir` and its subdirectories to `paths`, in order.
+fn find_wasm(dir: &Path, paths: &mut Vec<PathBuf>) -> Result<(), ImportError> {
+    let io_err = |err| ImportError::Io(dir.display().to_string(), err);
+    let mut entries = std::fs::read_dir(dir)
+        .and_then(|entries| entries.collect::<Result<Vec<_>, _>>())
+        .map_err(io_err)?;
+    entries.sort_by_key(|entry| entry.file_name());
+    for entry in entries {
+        let path = entry.path();
+        if entry.file_type().map_err(io_err)?.is_dir() {
+            find_wasm(&path, paths)?;
+        } else if path.extension() == Some("wasm".as_ref()) {
+            paths.push(path);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{import::Importer, Interface};
+
+    fn write_service(path: &Path, iface: Option<&Interface>) {
+        let wasm = match iface {
+            Some(iface) => crate::testing::service_wasm(iface),
+            None => b"\0asm\x01\0\0\0".to_vec(),
+        };
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, wasm).unwrap();
+    }
+
+    #[test]
+    fn test_import() {
+        let iface = crate::testing::test_service();
+        let with_version = |version: &str| {
+            let mut iface = iface.clone();
+            iface.version = version.to_string();
+            iface
+        };
+
+        let temp_dir = crate::testing::TempDir::new("dir-importer");
+        let dir = temp_dir.path().to_path_buf();
+        write_service(&dir.join("a/test-0.1.0.wasm"), Some(&with_version("0.1.0")));
+        write_service(&dir.join("a/test-0.1.2.wasm"), Some(&with_version("0.1.2")));
+        write_service(&dir.join("b/deps/test.wasm"), Some(&with_version("0.2.0")));
+        write_service(&dir.join("b/test.wasm"), Some(&with_version("0.2.0")));
+        write_service(&dir.join("not-a-service.wasm"), None);
+        std::fs::write(dir.join("README"), "not wasm").unwrap();
+
+        let import = |name: &str, query: &str| {
+            let url = format!("file://{}{}", dir.display(), query);
+            Importer::for_url(&url, dir.clone())
+                .unwrap()
+                .import(name)
+                .map(|service| service.interface.version)
+        };
+
+        assert_eq!(
+            Importer::for_url(&format!("file://{}", dir.display()), dir.clone())
+                .unwrap()
+                .import_all()
+                .unwrap()
+                .len(),
+            4
+        );
+        assert_eq!(import("TestService", "").unwrap(), "0.2.0");
+        assert_eq!(import("types", "?version=0.1").unwrap(), "0.1.2");
+        assert_eq!(import("test_service", "?version=~0.1.0").unwrap(), "0.1.2");
+        assert_eq!(import("TestService", "?version==0.1.0").unwrap(), "0.1.0");
+        match import("TestService", "?version=1") {
+            Err(ImportError::NoMatchingVersion { versions, .. }) => {
+                assert_eq!(versions, vec!["0.1.0", "0.1.2", "0.2.0"]);
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+        match import("OtherService", "") {
+            Err(ImportError::NoImport(name)) => assert_eq!(name, "OtherService"),
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+}
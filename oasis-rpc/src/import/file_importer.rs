@@ -1,4 +1,4 @@
-use crate::{import::ImportedService, Interface};
+use crate::import::ImportedService;
 
 use super::{ImportError, ImporterBackend};
 
@@ -7,27 +7,28 @@ pub struct FileImporter {
 }
 
 impl ImporterBackend for FileImporter {
-    fn import(&self, name: &str) -> Result<ImportedService, ImportError> {
-        self.import_all()?
+    fn import(
+        &self,
+        name: &str,
+        version_req: &semver::VersionReq,
+    ) -> Result<ImportedService, ImportError> {
+        let service = self
+            .import_all()?
             .pop()
-            .ok_or_else(|| ImportError::NoImport(name.to_string()))
+            .ok_or_else(|| ImportError::NoImport(name.to_string()))?;
+        if !version_req.matches(&super::version_of(&service.interface)?) {
+            return Err(ImportError::NoMatchingVersion {
+                name: name.to_string(),
+                version_req: version_req.to_string(),
+                versions: vec![service.interface.version],
+            });
+        }
+        Ok(service)
     }
 
     fn import_all(&self) -> Result<Vec<ImportedService>, ImportError> {
         let bytecode = std::fs::read(&self.path)
             .map_err(|err| ImportError::Io(self.path.display().to_string(), err))?;
-        let interface_bytes = match walrus::Module::from_buffer(&bytecode)
-            .map_err(ImportError::Importer)?
-            .customs
-            .remove_raw("oasis-interface")
-        {
-            Some(iface_section) => iface_section.data,
-            None => return Err(ImportError::MissingInterfaceSection),
-        };
-        let interface = Interface::from_slice(&interface_bytes).map_err(ImportError::Importer)?;
-        Ok(vec![ImportedService {
-            bytecode,
-            interface,
-        }])
+        Ok(vec![ImportedService::from_bytecode(bytecode)?])
     }
 }
@@ -0,0 +1,180 @@
+use std::{collections::BTreeMap, path::Path};
+
+use super::ImportedService;
+
+/// The name of the lockfile, which is placed next to the importing crate's `Cargo.toml`.
+pub const LOCKFILE_NAME: &str = "Oasis.lock";
+
+const LOCKFILE_VERSION: u32 = 1;
+
+const HEADER: &str = "# This file is generated by oasis-build and records the services imported \
+                      by this crate.\n# Run `oasis-rpc lock update` to update it.\n";
+
+/// Records the exact services to which the dependencies of a service were resolved,
+/// so that later builds import the same code or fail if that code has changed.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Lockfile {
+    pub version: u32,
+    #[serde(rename = "service", skip_serializing_if = "Vec::is_empty", default)]
+    pub services: Vec<LockedService>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct LockedService {
+    /// The name by which the service is imported.
+    pub name: String,
+    pub version: String,
+    /// The URL from which the service was imported.
+    pub source: String,
+    /// The hex-encoded Keccak-256 hash of the JSON-encoded interface.
+    pub interface_hash: String,
+    /// The hex-encoded Keccak-256 hash of the bytecode.
+    pub bytecode_hash: String,
+}
+
+impl Lockfile {
+    pub fn new(mut services: Vec<LockedService>) -> Self {
+        services.sort_by(|a, b| a.name.cmp(&b.name));
+        Self {
+            version: LOCKFILE_VERSION,
+            services,
+        }
+    }
+
+    /// Reads the lockfile at `path`, if it exists.
+    pub fn load(path: &Path) -> Result<Option<Self>, failure::Error> {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(ref err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => {
+                return Err(failure::format_err!(
+                    "Could not read `{}`: {}",
+                    path.display(),
+                    err
+                ))
+            }
+        };
+        let lockfile: Self = toml::from_str(&contents)
+            .map_err(|err| failure::format_err!("Invalid `{}`: {}", path.display(), err))?;
+        if lockfile.version != LOCKFILE_VERSION {
+            return Err(failure::format_err!(
+                "`{}` has unsupported version {}",
+                path.display(),
+                lockfile.version
+            ));
+        }
+        Ok(Some(lockfile))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), failure::Error> {
+        let contents = format!("{}\n{}", HEADER, toml::to_string(self)?);
+        std::fs::write(path, contents)
+            .map_err(|err| failure::format_err!("Could not write `{}`: {}", path.display(), err))
+    }
+
+    /// Returns the locked service that is imported as `name`.
+    pub fn get(&self, name: &str) -> Option<&LockedService> {
+        self.services
+            .iter()
+            .find(|service| super::same_name(&service.name, name))
+    }
+}
+
+impl LockedService {
+    pub fn new(name: String, source: String, service: &ImportedService) -> Self {
+        Self {
+            name,
+            version: service.interface.version.clone(),
+            source,
+            interface_hash: interface_hash(service),
+            bytecode_hash: hash(&service.bytecode),
+        }
+    }
+
+    /// Returns the part of the service (i.e. its interface or bytecode) that differs from
+    /// the locked service, if any.
+    pub fn changed_part(&self, service: &ImportedService) -> Option<&'static str> {
+        if interface_hash(service) != self.interface_hash {
+            Some("interface")
+        } else if hash(&service.bytecode) != self.bytecode_hash {
+            Some("bytecode")
+        } else {
+            None
+        }
+    }
+}
+
+/// Returns the top-level dependencies of the crate whose manifest is at `manifest_path`.
+/// Specified in the manifest as `[package.metadata.oasis-dependencies] <name> = "<url>"`.
+pub fn manifest_deps(manifest_path: &Path) -> Result<BTreeMap<String, String>, failure::Error> {
+    let manifest = std::fs::read_to_string(manifest_path).map_err(|err| {
+        failure::format_err!("Could not read `{}`: {}", manifest_path.display(), err)
+    })?;
+    let cargo_toml: toml::Value = toml::from_str(&manifest)?;
+    cargo_toml
+        .get("package")
+        .and_then(|p| p.get("metadata"))
+        .and_then(|m| m.get("oasis-dependencies"))
+        .cloned()
+        .map(|d| d.try_into::<BTreeMap<String, String>>())
+        .unwrap_or_else(|| Ok(BTreeMap::new()))
+        .map_err(|err| failure::format_err!("Could not parse Oasis dependencies: {}", err))
+}
+
+fn interface_hash(service: &ImportedService) -> String {
+    hash(&serde_json::to_vec(&service.interface).unwrap())
+}
+
+fn hash(bytes: &[u8]) -> String {
+    tiny_keccak::keccak256(bytes)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let service = ImportedService {
+            bytecode: b"\0asm\x01\0\0\0".to_vec(),
+            interface: crate::testing::test_service(),
+            registry: None,
+            address: None,
+        };
+        let lockfile = Lockfile::new(vec![
+            LockedService::new("types".to_string(), "file:///b".to_string(), &service),
+            LockedService::new(
+                "a".to_string(),
+                "registry://localhost".to_string(),
+                &service,
+            ),
+        ]);
+        assert_eq!(lockfile.services[0].name, "a");
+        assert_eq!(lockfile.get("Types").unwrap().source, "file:///b");
+        assert_eq!(lockfile.services[0].changed_part(&service), None);
+
+        let dir = crate::testing::TempDir::new("lockfile");
+        let path = dir.path().join("Oasis.lock");
+        assert_eq!(Lockfile::load(&path).unwrap(), None);
+        lockfile.save(&path).unwrap();
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert!(contents.starts_with(HEADER));
+        assert!(contents.contains("[[service]]\nname = \"a\"\nversion = \"0.1.0\"\n"));
+        assert_eq!(Lockfile::load(&path).unwrap(), Some(lockfile.clone()));
+
+        let mut changed = service;
+        changed.bytecode.push(0);
+        assert_eq!(
+            lockfile.services[0].changed_part(&changed),
+            Some("bytecode")
+        );
+        changed.interface.version = "0.1.1".to_string();
+        assert_eq!(
+            lockfile.services[0].changed_part(&changed),
+            Some("interface")
+        );
+    }
+}
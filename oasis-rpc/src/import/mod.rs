@@ -1,12 +1,30 @@
+#[cfg(feature = "chain")]
+mod chain_importer;
+mod dir_importer;
 mod file_importer;
+#[cfg(feature = "registry")]
+mod registry_importer;
+#[cfg(feature = "resolve")]
+mod lock;
 #[cfg(feature = "resolve")]
 mod resolve;
 
+#[cfg(feature = "chain")]
+pub use chain_importer::import_deployed;
+#[cfg(feature = "resolve")]
+pub use lock::{manifest_deps, LockedService, Lockfile, LOCKFILE_NAME};
 #[cfg(feature = "resolve")]
 pub use resolve::{ResolveError, Resolver};
 
+/// Imports services from the location given by a URL. A URL may restrict the versions of the
+/// services that it provides using a `version` query parameter, e.g., `?version=0.1`.
+///
+/// A `file` URL that has an `address` query parameter refers to a memchain state dump,
+/// from which the service deployed at that address is imported,
+/// e.g., `file:///./devnet.json?address=0x0101010101010101010101010101010101010101`.
 pub struct Importer {
     backend: Box<dyn ImporterBackend>,
+    version_req: semver::VersionReq,
 }
 
 impl Importer {
@@ -15,6 +33,11 @@ impl Importer {
             Ok(url) => url,
             Err(err) => return Err(ImportError::InvalidUrl(err)),
         };
+        let version_req = match url.query_pairs().find(|(key, _)| key == "version") {
+            Some((_, req)) => semver::VersionReq::parse(&req)
+                .map_err(|err| ImportError::InvalidVersionReq(req.to_string(), err))?,
+            None => semver::VersionReq::any(),
+        };
         Ok(Self {
             backend: match url.scheme() {
                 "file" => {
@@ -40,15 +63,44 @@ impl Importer {
                             .canonicalize()
                             .map_err(|err| ImportError::Io(relpath_str.to_string(), err))?
                     };
-                    box file_importer::FileImporter { path }
+                    let address = url.query_pairs().find(|(key, _)| key == "address");
+                    if let Some((_, address)) = address {
+                        chain_importer(&path, &address)?
+                    } else if path.is_dir() {
+                        box dir_importer::DirImporter { path }
+                    } else {
+                        box file_importer::FileImporter { path }
+                    }
+                }
+                #[cfg(feature = "registry")]
+                "registry" | "http" => {
+                    let client = crate::registry::Client::new(&url)
+                        .map_err(|err| ImportError::Importer(err.into()))?;
+                    box registry_importer::RegistryImporter { client }
                 }
                 _ => return Err(ImportError::NoImporter(url.scheme().to_string())),
             },
+            version_req,
         })
     }
 
     pub fn import(&self, name: &str) -> Result<ImportedService, ImportError> {
-        self.backend.import(name)
+        self.backend.import(name, &self.version_req)
+    }
+
+    /// Imports the greatest version of the service that matches `version_req`,
+    /// which replaces the version requirement of the URL.
+    pub fn import_matching(
+        &self,
+        name: &str,
+        version_req: &semver::VersionReq,
+    ) -> Result<ImportedService, ImportError> {
+        self.backend.import(name, version_req)
+    }
+
+    /// Returns the version requirement given by the URL, which matches any version if none was.
+    pub fn version_req(&self) -> &semver::VersionReq {
+        &self.version_req
     }
 
     pub fn import_all(&self) -> Result<Vec<ImportedService>, ImportError> {
@@ -56,17 +108,96 @@ impl Importer {
     }
 }
 
+#[cfg(feature = "chain")]
+fn chain_importer(
+    dump_path: &std::path::Path,
+    address: &str,
+) -> Result<Box<dyn ImporterBackend>, ImportError> {
+    let importer = chain_importer::ChainImporter::new(dump_path, address)?;
+    Ok(box importer)
+}
+
+#[cfg(not(feature = "chain"))]
+fn chain_importer(
+    _dump_path: &std::path::Path,
+    address: &str,
+) -> Result<Box<dyn ImporterBackend>, ImportError> {
+    Err(ImportError::Importer(failure::format_err!(
+        "Cannot import the service at {} without the `chain` feature",
+        address
+    )))
+}
+
 pub struct ImportedService {
     pub bytecode: Vec<u8>,
     pub interface: crate::Interface,
+    /// The URL of the registry from which the service was imported, if any.
+    pub registry: Option<String>,
+    /// The address at which the service is deployed, if it was imported from a chain.
+    pub address: Option<Vec<u8>>,
+}
+
+impl ImportedService {
+    /// Returns the service whose interface is stored in the interface section of `bytecode`.
+    pub fn from_bytecode(bytecode: Vec<u8>) -> Result<Self, ImportError> {
+        let interface_bytes = match crate::wasm::interface_section(&bytecode)
+            .map_err(|err| ImportError::Importer(err.into()))?
+        {
+            Some(iface_section) => iface_section,
+            None => return Err(ImportError::MissingInterfaceSection),
+        };
+        let interface = crate::Interface::from_slice(interface_bytes).map_err(ImportError::Importer)?;
+        interface
+            .validate()
+            .map_err(ImportError::InvalidInterface)?;
+        Ok(Self {
+            bytecode,
+            interface,
+            registry: None,
+            address: None,
+        })
+    }
 }
 
 trait ImporterBackend {
-    fn import(&self, name: &str) -> Result<ImportedService, ImportError>;
+    /// Returns the service named `name` whose version matches `version_req`.
+    /// If several versions match, the greatest is returned.
+    fn import(
+        &self,
+        name: &str,
+        version_req: &semver::VersionReq,
+    ) -> Result<ImportedService, ImportError>;
 
     fn import_all(&self) -> Result<Vec<ImportedService>, ImportError>;
 }
 
+/// Returns whether `name` refers to the service, either by its name or by its namespace
+/// (i.e. crate name). Case, `-`, and `_` are ignored, so `my-service` refers to `MyService`.
+pub(crate) fn is_named(iface: &crate::Interface, name: &str) -> bool {
+    same_name(&iface.name, name) || same_name(&iface.namespace, name)
+}
+
+pub(crate) fn same_name(a: &str, b: &str) -> bool {
+    let normalize = |s: &str| {
+        s.chars()
+            .filter(|ch| *ch != '-' && *ch != '_')
+            .flat_map(char::to_lowercase)
+            .collect::<String>()
+    };
+    normalize(a) == normalize(b)
+}
+
+pub(crate) fn version_of(iface: &crate::Interface) -> Result<semver::Version, ImportError> {
+    semver::Version::parse(&iface.version).map_err(|err| {
+        ImportError::Importer(failure::format_err!(
+            "`{}` has invalid version `{}`: {}",
+            iface.name,
+            iface.version,
+            err
+        ))
+    })
+}
+
 #[derive(Debug, failure::Fail)]
 pub enum ImportError {
     #[fail(display = "Invalid URL: {}", _0)]
@@ -81,9 +212,25 @@ pub enum ImportError {
     #[fail(display = "Wasm module missing oasis-interface section")]
     MissingInterfaceSection,
 
+    #[fail(display = "{}", _0)]
+    InvalidInterface(#[fail(cause)] crate::InvalidInterface),
+
     #[fail(display = "Could not locate `{}`", _0)]
     NoImport(String),
 
+    #[fail(display = "Invalid version requirement `{}`: {}", _0, _1)]
+    InvalidVersionReq(String, #[fail(cause)] semver::ReqParseError),
+
+    #[fail(
+        display = "No version of `{}` matches `{}`. Found: {:?}",
+        name, version_req, versions
+    )]
+    NoMatchingVersion {
+        name: String,
+        version_req: String,
+        versions: Vec<String>,
+    },
+
     #[fail(display = "{}", _0)]
     Importer(#[fail(cause)] failure::Error),
 }
@@ -0,0 +1,71 @@
+use crate::{
+    import::ImportedService,
+    registry::{Client, RegistryError},
+};
+
+use super::{ImportError, ImporterBackend};
+
+pub struct RegistryImporter {
+    pub client: Client,
+}
+
+impl ImporterBackend for RegistryImporter {
+    fn import(
+        &self,
+        name: &str,
+        version_req: &semver::VersionReq,
+    ) -> Result<ImportedService, ImportError> {
+        let published = match self.client.versions(name) {
+            Ok(published) => published,
+            Err(RegistryError::NotFound(_)) => return Err(ImportError::NoImport(name.to_string())),
+            Err(err) => return Err(ImportError::Importer(err.into())),
+        };
+        let version = published
+            .versions
+            .iter()
+            .filter_map(|version| semver::Version::parse(version).ok())
+            .filter(|version| version_req.matches(version))
+            .max()
+            .ok_or_else(|| ImportError::NoMatchingVersion {
+                name: name.to_string(),
+                version_req: version_req.to_string(),
+                versions: published.versions.clone(),
+            })?
+            .to_string();
+
+        let bytecode = self
+            .client
+            .wasm(&published.name, &version)
+            .map_err(|err| ImportError::Importer(err.into()))?;
+        let interface = crate::wasm::interface(&bytecode)
+            .map_err(ImportError::Importer)?
+            .ok_or(ImportError::MissingInterfaceSection)?;
+        interface
+            .validate()
+            .map_err(ImportError::InvalidInterface)?;
+        if !super::is_named(&interface, name) || interface.version != version {
+            return Err(ImportError::Importer(failure::format_err!(
+                "Registry returned `{}` v{} instead of `{}` v{}",
+                interface.name,
+                interface.version,
+                name,
+                version
+            )));
+        }
+        Ok(ImportedService {
+            bytecode,
+            interface,
+            registry: Some(self.client.url()),
+            address: None,
+        })
+    }
+
+    fn import_all(&self) -> Result<Vec<ImportedService>, ImportError> {
+        self.client
+            .services()
+            .map_err(|err| ImportError::Importer(err.into()))?
+            .iter()
+            .map(|name| self.import(name, &semver::VersionReq::any()))
+            .collect()
+    }
+}
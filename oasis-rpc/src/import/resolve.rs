@@ -1,6 +1,45 @@
+use std::collections::BTreeMap;
+
+use super::{same_name, ImportError, ImportedService, Importer, LockedService, Lockfile};
+
+/// Resolves the services imported by a service and, transitively, the services that they import.
+///
+/// Each service is imported once, at the greatest version that satisfies every requirement
+/// placed on it: the `version` of its top-level URL, if any, and the versions recorded
+/// in the interfaces of the services that import it, which are treated as caret requirements.
+/// A transitive import is located using the top-level dependency of the same name or,
+/// failing that, the registry from which its importer imported it.
+///
+/// Given a lockfile, a service is instead imported at its locked version if that version
+/// satisfies every requirement and is still imported from the same location. The imported
+/// service must then be identical to the locked one.
 pub struct Resolver {
     deps: Vec<(String, String)>,
     base_dir: std::path::PathBuf,
+    lockfile: Option<Lockfile>,
+}
+
+/// A requirement placed on the version of a service by the service at the end of `chain`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Requirement {
+    /// The services that led to the requirement, starting from a top-level dependency.
+    /// Empty if the requirement was made by the top-level dependency itself.
+    pub chain: Vec<String>,
+    pub version_req: String,
+}
+
+impl std::fmt::Display for Requirement {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self.chain.last() {
+            Some(_) => write!(
+                f,
+                "{} requires {}",
+                self.chain.join(" -> "),
+                self.version_req
+            ),
+            None => write!(f, "the top-level dependency requires {}", self.version_req),
+        }
+    }
 }
 
 impl Resolver {
@@ -8,25 +47,283 @@ impl Resolver {
         Self {
             deps: initial_deps,
             base_dir,
+            lockfile: None,
         }
     }
 
-    pub fn resolve(&self) -> Result<Vec<super::ImportedService>, ResolveError> {
-        self.deps
+    pub fn with_lockfile(mut self, lockfile: Lockfile) -> Self {
+        self.lockfile = Some(lockfile);
+        self
+    }
+
+    /// Returns the imported services such that every service follows the services it imports.
+    pub fn resolve(&self) -> Result<Vec<ImportedService>, ResolveError> {
+        self.resolve_locked().map(|(services, _)| services)
+    }
+
+    /// Resolves the imported services, as does `resolve`, and also returns the lockfile that
+    /// records them.
+    pub fn resolve_locked(&self) -> Result<(Vec<ImportedService>, Lockfile), ResolveError> {
+        let mut importers = BTreeMap::new();
+        for (name, url) in self.deps.iter() {
+            let importer =
+                Importer::for_url(url, self.base_dir.clone()).map_err(ResolveError::Import)?;
+            importers.insert(name.clone(), importer);
+        }
+
+        let mut resolved: BTreeMap<String, ImportedService> = BTreeMap::new();
+        let mut sources: BTreeMap<String, String> = BTreeMap::new();
+        // Choosing a newer version of a service can change the requirements of the services
+        // it imports, so iterate until every requirement is satisfied.
+        loop {
+            let graph = self.walk(&importers, &resolved)?;
+            let mut changed = false;
+            for (name, reqs) in graph.requirements.iter() {
+                let satisfied = resolved.get(name).map(|service| {
+                    let version = super::version_of(&service.interface);
+                    version.map(|version| reqs.iter().all(|(req, _)| req.matches(&version)))
+                });
+                if let Some(true) = satisfied.transpose().map_err(ResolveError::Import)? {
+                    continue;
+                }
+                let (service, source) = self.import(name, reqs, &importers, &graph.registries)?;
+                resolved.insert(name.clone(), service);
+                sources.insert(name.clone(), source);
+                changed = true;
+            }
+            if !changed {
+                resolved.retain(|name, _| graph.requirements.contains_key(name));
+                let services: Vec<_> = graph
+                    .order
+                    .iter()
+                    .filter_map(|name| resolved.remove(name).map(|service| (name, service)))
+                    .collect();
+                let lockfile = Lockfile::new(
+                    services
+                        .iter()
+                        .map(|(name, service)| {
+                            LockedService::new(name.to_string(), sources[*name].clone(), service)
+                        })
+                        .collect(),
+                );
+                return Ok((
+                    services.into_iter().map(|(_, service)| service).collect(),
+                    lockfile,
+                ));
+            }
+        }
+    }
+
+    /// Collects the requirements placed on each service by the top-level dependencies and
+    /// the services resolved so far. Also returns the order in which the resolved services
+    /// must be built and fails if they import each other.
+    fn walk(
+        &self,
+        importers: &BTreeMap<String, Importer>,
+        resolved: &BTreeMap<String, ImportedService>,
+    ) -> Result<Graph, ResolveError> {
+        let mut graph = Graph::default();
+        for (name, importer) in importers.iter() {
+            graph.require(name, importer.version_req().clone(), Vec::new());
+        }
+        let mut visiting = Vec::new();
+        for name in importers.keys() {
+            self.visit(name, resolved, &mut visiting, &mut graph)?;
+        }
+        Ok(graph)
+    }
+
+    fn visit(
+        &self,
+        name: &str,
+        resolved: &BTreeMap<String, ImportedService>,
+        visiting: &mut Vec<String>,
+        graph: &mut Graph,
+    ) -> Result<(), ResolveError> {
+        if let Some(pos) = visiting.iter().position(|n| n == name) {
+            let mut cycle = visiting[pos..].to_vec();
+            cycle.push(name.to_string());
+            return Err(ResolveError::Cycle(cycle));
+        }
+        if graph.order.iter().any(|n| n == name) {
+            return Ok(());
+        }
+        let service = match resolved.get(name) {
+            Some(service) => service,
+            None => return Ok(()), // not yet imported
+        };
+        visiting.push(name.to_string());
+        let chain: Vec<_> = visiting
             .iter()
-            .map(|(name, url)| {
-                super::Importer::for_url(url, self.base_dir.clone())
-                    .and_then(|importer| importer.import(name))
-                    .map_err(ResolveError::Import)
-            })
-            .collect()
+            .map(|n| format!("{} v{}", n, resolved[n].interface.version))
+            .collect();
+        for import in service.interface.imports.iter() {
+            let version_req =
+                semver::VersionReq::parse(&format!("^{}", import.version)).map_err(|err| {
+                    ResolveError::Import(ImportError::InvalidVersionReq(
+                        import.version.clone(),
+                        err,
+                    ))
+                })?;
+            let dep_name = graph.require(&import.name, version_req, chain.clone());
+            if let Some(registry) = &import.registry {
+                graph
+                    .registries
+                    .entry(dep_name.clone())
+                    .or_insert_with(|| registry.clone());
+            }
+            self.visit(&dep_name, resolved, visiting, graph)?;
+        }
+        visiting.pop();
+        graph.order.push(name.to_string());
+        Ok(())
+    }
+
+    /// Imports the locked version of the service or, if it has none, the greatest version
+    /// that satisfies every requirement. Also returns the URL from which it was imported.
+    fn import(
+        &self,
+        name: &str,
+        reqs: &[(semver::VersionReq, Requirement)],
+        importers: &BTreeMap<String, Importer>,
+        registries: &BTreeMap<String, String>,
+    ) -> Result<(ImportedService, String), ResolveError> {
+        let transitive_importer;
+        let (importer, source) = match (importers.get(name), registries.get(name)) {
+            (Some(importer), _) => {
+                let url = self.deps.iter().find(|(dep, _)| dep == name).unwrap();
+                (importer, &url.1)
+            }
+            (None, Some(registry)) => {
+                transitive_importer = Importer::for_url(registry, self.base_dir.clone())
+                    .map_err(ResolveError::Import)?;
+                (&transitive_importer, registry)
+            }
+            (None, None) => {
+                return Err(ResolveError::Unlocatable {
+                    name: name.to_string(),
+                    requirements: reqs.iter().map(|(_, req)| req.clone()).collect(),
+                });
+            }
+        };
+
+        if let Some((locked, version)) = self.locked(name, source, reqs) {
+            let version_req = semver::VersionReq::exact(&version);
+            let service = importer
+                .import_matching(name, &version_req)
+                .map_err(ResolveError::Import)?;
+            if let Some(part) = locked.changed_part(&service) {
+                return Err(ResolveError::LockMismatch {
+                    name: name.to_string(),
+                    version: locked.version.clone(),
+                    source: source.to_string(),
+                    part,
+                });
+            }
+            return Ok((service, source.to_string()));
+        }
+
+        let combined = reqs
+            .iter()
+            .map(|(req, _)| req.to_string())
+            .filter(|req| req != "*")
+            .collect::<Vec<_>>()
+            .join(", ");
+        let version_req = if combined.is_empty() {
+            semver::VersionReq::any()
+        } else {
+            semver::VersionReq::parse(&combined).map_err(|err| {
+                ResolveError::Import(ImportError::InvalidVersionReq(combined.clone(), err))
+            })?
+        };
+        match importer.import_matching(name, &version_req) {
+            Ok(service) => Ok((service, source.to_string())),
+            Err(ImportError::NoMatchingVersion { .. }) if reqs.len() > 1 => {
+                Err(ResolveError::DependencyMismatch {
+                    name: name.to_string(),
+                    requirements: reqs.iter().map(|(_, req)| req.clone()).collect(),
+                })
+            }
+            Err(err) => Err(ResolveError::Import(err)),
+        }
+    }
+
+    /// Returns the locked service that can be imported to satisfy the requirements, if any.
+    fn locked(
+        &self,
+        name: &str,
+        source: &str,
+        reqs: &[(semver::VersionReq, Requirement)],
+    ) -> Option<(&LockedService, semver::Version)> {
+        let locked = self.lockfile.as_ref()?.get(name)?;
+        let version = semver::Version::parse(&locked.version).ok()?;
+        if locked.source == source && reqs.iter().all(|(req, _)| req.matches(&version)) {
+            Some((locked, version))
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Default)]
+struct Graph {
+    /// The requirements placed on each service, keyed by the name of its top-level dependency,
+    /// if any, or by the name under which it was first imported.
+    requirements: BTreeMap<String, Vec<(semver::VersionReq, Requirement)>>,
+    /// The registries from which transitive imports were imported.
+    registries: BTreeMap<String, String>,
+    /// The services in the order in which they must be built.
+    order: Vec<String>,
+}
+
+impl Graph {
+    /// Adds a requirement and returns the name by which the required service is known.
+    fn require(
+        &mut self,
+        name: &str,
+        version_req: semver::VersionReq,
+        chain: Vec<String>,
+    ) -> String {
+        let name = self
+            .requirements
+            .keys()
+            .find(|n| same_name(n, name))
+            .cloned()
+            .unwrap_or_else(|| name.to_string());
+        let req = Requirement {
+            chain,
+            version_req: version_req.to_string(),
+        };
+        self.requirements
+            .entry(name.clone())
+            .or_default()
+            .push((version_req, req));
+        name
     }
 }
 
 #[derive(Debug, failure::Fail)]
 pub enum ResolveError {
     Import(#[fail(cause)] super::ImportError),
-    DependencyMismatch { name: String, versions: Vec<String> },
+    DependencyMismatch {
+        name: String,
+        requirements: Vec<Requirement>,
+    },
+    /// A transitive import has neither a top-level dependency nor a registry.
+    Unlocatable {
+        name: String,
+        requirements: Vec<Requirement>,
+    },
+    /// The services import each other. The first and last services are the same.
+    Cycle(Vec<String>),
+    /// The locked version of a service is no longer the service that was locked.
+    LockMismatch {
+        name: String,
+        version: String,
+        source: String,
+        /// The part of the service that changed: its `interface` or `bytecode`.
+        part: &'static str,
+    },
 }
 
 impl std::fmt::Display for ResolveError {
@@ -34,12 +331,248 @@ impl std::fmt::Display for ResolveError {
         use ResolveError::*;
         match self {
             Import(err) => write!(f, "{}", err),
-            DependencyMismatch { name, versions } => write!(
+            DependencyMismatch { name, requirements } => {
+                write!(f, "Could not reconcile versions for `{}`:", name)?;
+                for req in requirements.iter() {
+                    write!(f, "\n        {}", req)?;
+                }
+                Ok(())
+            }
+            Unlocatable { name, requirements } => {
+                write!(
+                    f,
+                    "Could not locate `{}`. Add it to `[package.metadata.oasis-dependencies]`:",
+                    name
+                )?;
+                for req in requirements.iter() {
+                    write!(f, "\n        {}", req)?;
+                }
+                Ok(())
+            }
+            Cycle(cycle) => write!(f, "Services import each other: {}", cycle.join(" -> ")),
+            LockMismatch {
+                name,
+                version,
+                source,
+                part,
+            } => write!(
                 f,
-                "Could not reconcile versions for `{}`: ({})",
+                "The {} of `{}` v{} imported from `{}` differs from the one recorded in `{}`. \
+                 Run `oasis-rpc lock update` if the change is expected.",
+                part,
+                name,
+                version,
+                source,
+                super::LOCKFILE_NAME
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{testing::TempDir, Import};
+
+    /// A service's `(name, version, imports)`, where each import is `(name, version)`.
+    type TestService<'a> = (&'a str, &'a str, &'a [(&'a str, &'a str)]);
+
+    /// Creates a directory for the services of the test named `test_name`.
+    fn services_dir(test_name: &str) -> (TempDir, String) {
+        let temp_dir = TempDir::new(&format!("resolve-{}", test_name));
+        let dir = temp_dir.path().display().to_string();
+        (temp_dir, dir)
+    }
+
+    /// Writes the services into `dir`.
+    fn write_services(dir: &str, services: &[TestService]) {
+        let base = crate::testing::test_service();
+        for (name, version, imports) in services {
+            let mut iface = base.clone();
+            iface.name = name.to_string();
+            iface.namespace = name.to_lowercase();
+            iface.version = version.to_string();
+            iface.imports = imports
+                .iter()
+                .map(|(name, version)| Import {
+                    name: name.to_string(),
+                    version: version.to_string(),
+                    registry: None,
+                })
+                .collect();
+            let wasm = crate::testing::service_wasm(&iface);
+            let path = std::path::Path::new(dir).join(format!("{}-{}.wasm", name, version));
+            std::fs::write(path, wasm).unwrap();
+        }
+    }
+
+    /// Returns a resolver of `deps`, each of which is a name optionally followed by a URL query.
+    fn resolver(dir: &str, deps: &[&str]) -> Resolver {
+        let deps = deps
+            .iter()
+            .map(|dep| {
+                let mut parts = dep.splitn(2, '?');
+                let name = parts.next().unwrap().to_string();
+                let query = parts.next().map(|q| format!("?{}", q)).unwrap_or_default();
+                (name, format!("file://{}{}", dir, query))
+            })
+            .collect();
+        Resolver::new(deps, dir.into())
+    }
+
+    fn names(services: Vec<ImportedService>) -> Vec<String> {
+        services
+            .into_iter()
+            .map(|s| format!("{} v{}", s.interface.name, s.interface.version))
+            .collect()
+    }
+
+    fn resolve(dir: &str, deps: &[&str]) -> Result<Vec<String>, ResolveError> {
+        Ok(names(resolver(dir, deps).resolve()?))
+    }
+
+    #[test]
+    fn test_transitive() {
+        let (_temp_dir, dir) = services_dir("transitive");
+        write_services(
+            &dir,
+            &[
+                ("A", "0.1.0", &[("b", "0.1.0"), ("c", "0.1.1")]),
+                ("B", "0.1.0", &[("c", "0.1.0")]),
+                ("B", "0.1.5", &[("c", "0.1.2")]),
+                ("B", "0.2.0", &[]),
+                ("C", "0.1.0", &[]),
+                ("C", "0.1.3", &[]),
+                ("C", "0.2.0", &[]),
+                ("D", "1.0.0", &[("c", "0.2.0")]),
+                ("E", "0.1.0", &[("b", "0.1.5")]),
+            ],
+        );
+
+        assert_eq!(
+            resolve(&dir, &["a", "b", "c"]).unwrap(),
+            vec!["C v0.1.3", "B v0.1.5", "A v0.1.0"]
+        );
+        assert_eq!(
+            resolve(&dir, &["b", "c"]).unwrap(),
+            vec!["B v0.2.0", "C v0.2.0"]
+        );
+
+        match resolve(&dir, &["a", "b", "c", "d"]) {
+            Err(ResolveError::DependencyMismatch { name, requirements }) => {
+                assert_eq!(name, "c");
+                let reqs: Vec<_> = requirements.iter().map(ToString::to_string).collect();
+                assert_eq!(
+                    reqs,
+                    vec![
+                        "the top-level dependency requires *",
+                        "a v0.1.0 requires ^0.1.1",
+                        "d v1.0.0 requires ^0.2.0",
+                    ]
+                );
+            }
+            result => panic!("unexpected result: {:?}", result.map_err(|e| e.to_string())),
+        }
+
+        match resolve(&dir, &["b", "c?version=0.2", "e"]) {
+            Err(err @ ResolveError::DependencyMismatch { .. }) => assert_eq!(
+                err.to_string(),
+                "Could not reconcile versions for `c`:\n        \
+                 the top-level dependency requires ^0.2\n        \
+                 b v0.1.5 requires ^0.1.2"
+            ),
+            result => panic!("unexpected result: {:?}", result.map_err(|e| e.to_string())),
+        }
+
+        match resolve(&dir, &["a"]) {
+            Err(ResolveError::Unlocatable { name, requirements }) => {
+                assert_eq!(name, "b");
+                assert_eq!(requirements[0].chain, vec!["a v0.1.0"]);
+            }
+            result => panic!("unexpected result: {:?}", result.map_err(|e| e.to_string())),
+        }
+    }
+
+    #[test]
+    fn test_cycle() {
+        let (_temp_dir, dir) = services_dir("cycle");
+        write_services(
+            &dir,
+            &[
+                ("X", "0.1.0", &[("y", "0.1.0")]),
+                ("Y", "0.1.0", &[("z", "0.1.0")]),
+                ("Z", "0.1.0", &[("x", "0.1.0")]),
+            ],
+        );
+        match resolve(&dir, &["x", "y", "z"]) {
+            Err(ResolveError::Cycle(cycle)) => assert_eq!(cycle, vec!["x", "y", "z", "x"]),
+            result => panic!("unexpected result: {:?}", result.map_err(|e| e.to_string())),
+        }
+    }
+
+    #[test]
+    fn test_lockfile() {
+        let (_temp_dir, dir) = services_dir("lockfile");
+        write_services(
+            &dir,
+            &[("A", "0.1.0", &[("b", "0.1.0")]), ("B", "0.1.0", &[])],
+        );
+        let (services, lockfile) = resolver(&dir, &["a", "b"]).resolve_locked().unwrap();
+        assert_eq!(names(services), vec!["B v0.1.0", "A v0.1.0"]);
+        let locked: Vec<_> = lockfile
+            .services
+            .iter()
+            .map(|s| (s.name.as_str(), s.version.as_str(), s.source.as_str()))
+            .collect();
+        let source = format!("file://{}", dir);
+        assert_eq!(
+            locked,
+            vec![
+                ("a", "0.1.0", source.as_str()),
+                ("b", "0.1.0", source.as_str())
+            ]
+        );
+
+        write_services(&dir, &[("B", "0.1.1", &[])]);
+        assert_eq!(
+            resolve(&dir, &["a", "b"]).unwrap(),
+            vec!["B v0.1.1", "A v0.1.0"]
+        );
+        let (services, relocked) = resolver(&dir, &["a", "b"])
+            .with_lockfile(lockfile.clone())
+            .resolve_locked()
+            .unwrap();
+        assert_eq!(names(services), vec!["B v0.1.0", "A v0.1.0"]);
+        assert_eq!(relocked, lockfile);
+
+        // A locked version that no longer satisfies the requirements is replaced.
+        let (services, relocked) = resolver(&dir, &["a", "b?version=0.1.1"])
+            .with_lockfile(lockfile.clone())
+            .resolve_locked()
+            .unwrap();
+        assert_eq!(names(services), vec!["B v0.1.1", "A v0.1.0"]);
+        assert_eq!(relocked.get("b").unwrap().version, "0.1.1");
+
+        let b_path = std::path::Path::new(&dir).join("B-0.1.0.wasm");
+        let mut b_wasm = std::fs::read(&b_path).unwrap();
+        b_wasm.extend_from_slice(&[0, 2, 1, b'x']); // a custom section named `x`
+        std::fs::write(&b_path, b_wasm).unwrap();
+        match resolver(&dir, &["a", "b"])
+            .with_lockfile(lockfile)
+            .resolve_locked()
+        {
+            Err(ResolveError::LockMismatch {
                 name,
-                versions.join(" ")
+                version,
+                part,
+                ..
+            }) => assert_eq!(
+                (name.as_str(), version.as_str(), part),
+                ("b", "0.1.0", "bytecode")
             ),
+            Err(err) => panic!("unexpected error: {}", err),
+            Ok((services, _)) => panic!("unexpected services: {:?}", names(services)),
         }
     }
 }
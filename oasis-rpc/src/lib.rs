@@ -3,8 +3,27 @@
 #[macro_use]
 extern crate serde;
 
+#[cfg(feature = "codec")]
+pub mod codec;
+#[cfg(feature = "compat")]
+pub mod compat;
+#[cfg(feature = "gen")]
+pub mod gen;
+#[cfg(feature = "idl")]
+pub mod idl;
 #[cfg(feature = "import")]
 pub mod import;
+#[cfg(feature = "registry")]
+pub mod registry;
+#[cfg(feature = "selector")]
+pub mod selector;
+#[cfg(test)]
+mod testing;
+mod validate;
+#[cfg(feature = "saveload")]
+pub mod wasm;
+
+pub use validate::{InvalidInterface, ValidationError, ValidationErrors};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, PartialOrd, Hash)]
 pub struct Interface {
@@ -16,9 +35,14 @@ pub struct Interface {
     #[serde(skip_serializing_if = "Vec::is_empty", default)]
     pub type_defs: Vec<TypeDef>,
     pub constructor: Constructor,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub migration: Option<Migration>,
     #[serde(skip_serializing_if = "Vec::is_empty", default)]
     pub functions: Vec<Function>,
     pub oasis_build_version: String,
+    /// The doc comment of the service, if any.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub docs: Option<String>,
 }
 
 #[cfg(feature = "saveload")]
@@ -44,6 +68,13 @@ impl Interface {
 
 pub type Ident = String;
 
+/// The selector of the dispatcher's built-in batch entry point. A batch, `[BATCH_SELECTOR,
+/// [call, ...]]`, contains the encoded inputs of several calls as CBOR byte strings and
+/// runs them in one transaction that fails if any of them fails. A batch cannot transfer
+/// value, which its calls would otherwise share. Its output is the array of the calls'
+/// outputs, and its error is the index of the failed call and that call's error.
+pub const BATCH_SELECTOR: u32 = 0;
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, PartialOrd, Hash)]
 pub struct Function {
     pub name: Ident,
@@ -52,6 +83,23 @@ pub struct Function {
     pub inputs: Vec<Field>,
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub output: Option<Type>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub docs: Option<String>,
+    /// The selector by which a call can identify this function instead of by its name.
+    /// `None` if the dispatcher of the service only accepts named calls.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub selector: Option<u32>,
+    /// Whether calls may transfer value to the service. The dispatcher rejects calls that
+    /// send value to functions that are not payable.
+    #[serde(skip_serializing_if = "std::ops::Not::not", default)]
+    pub payable: bool,
+    /// The callers that may call this function, any of which suffices.
+    /// Anyone may call the function if this is empty.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub only: Vec<Principal>,
+    /// Whether the dispatcher rejects calls to this function while the service is paused.
+    #[serde(skip_serializing_if = "std::ops::Not::not", default)]
+    pub when_not_paused: bool,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, PartialOrd, Hash)]
@@ -60,14 +108,20 @@ pub enum TypeDef {
     Struct {
         name: Ident,
         fields: Vec<Field>,
+        #[serde(skip_serializing_if = "Option::is_none", default)]
+        docs: Option<String>,
     },
     Enum {
         name: Ident,
-        variants: Vec<Ident>,
+        variants: Vec<Variant>,
+        #[serde(skip_serializing_if = "Option::is_none", default)]
+        docs: Option<String>,
     },
     Event {
         name: Ident,
         fields: Vec<IndexedField>,
+        #[serde(skip_serializing_if = "Option::is_none", default)]
+        docs: Option<String>,
     },
 }
 
@@ -79,6 +133,33 @@ impl TypeDef {
             | TypeDef::Event { name, .. } => &name,
         }
     }
+
+    pub fn docs(&self) -> Option<&str> {
+        match self {
+            TypeDef::Struct { docs, .. }
+            | TypeDef::Enum { docs, .. }
+            | TypeDef::Event { docs, .. } => docs.as_ref().map(String::as_str),
+        }
+    }
+}
+
+/// A variant of an enum. Unit variants are serialized as just their name.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, PartialOrd, Hash)]
+#[serde(untagged)]
+pub enum Variant {
+    Unit(Ident),
+    Tuple { name: Ident, items: Vec<Type> },
+    Struct { name: Ident, fields: Vec<Field> },
+}
+
+impl Variant {
+    pub fn name(&self) -> &str {
+        match self {
+            Variant::Unit(name) | Variant::Tuple { name, .. } | Variant::Struct { name, .. } => {
+                &name
+            }
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, PartialOrd, Hash)]
@@ -86,6 +167,18 @@ pub struct Field {
     pub name: Ident,
     #[serde(rename = "type")]
     pub ty: Type,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub docs: Option<String>,
+}
+
+/// Returns whether the fields with the given names are those of a tuple struct
+/// (i.e. are named `0`, `1`, ...). Tuple structs are serialized as sequences rather than maps.
+pub(crate) fn is_tuple_struct<'a>(field_names: impl IntoIterator<Item = &'a str>) -> bool {
+    let mut field_names = field_names.into_iter().peekable();
+    field_names.peek().is_some()
+        && field_names
+            .enumerate()
+            .all(|(i, name)| name.parse::<usize>() == Ok(i))
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, PartialOrd, Hash)]
@@ -95,6 +188,8 @@ pub struct IndexedField {
     pub ty: Type,
     #[serde(skip_serializing_if = "std::ops::Not::not", default)]
     pub indexed: bool,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub docs: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, PartialOrd, Hash)]
@@ -104,6 +199,16 @@ pub enum StateMutability {
     Mutable,
 }
 
+/// A caller that a function's `#[only(...)]` attribute permits.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, PartialOrd, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Principal {
+    /// The owner of the service.
+    Owner,
+    /// Any account that has been granted the role.
+    Role(Ident),
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, PartialOrd, Hash)]
 pub struct Import {
     pub name: Ident,
@@ -116,6 +221,18 @@ pub struct Import {
 pub struct Constructor {
     pub inputs: Vec<Field>,
     pub error: Option<Type>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub docs: Option<String>,
+}
+
+/// The function that transforms the state of a previous version of the service
+/// into the state of this version when the service is upgraded.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, PartialOrd, Hash)]
+pub struct Migration {
+    pub inputs: Vec<Field>,
+    pub error: Option<Type>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub docs: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, PartialOrd, Hash)]
@@ -130,6 +247,12 @@ pub enum Type {
     I32,
     U64,
     I64,
+    /// Encoded as 16 big-endian bytes (`oasis_types::U128`).
+    U128,
+    /// Encoded as 16 big-endian bytes in two's complement (`oasis_types::I128`).
+    I128,
+    /// Encoded as 32 big-endian bytes (`oasis_types::U256`).
+    U256,
     F32,
     F64,
     Bytes,
@@ -0,0 +1,159 @@
+//! Just enough HTTP/1.1 to speak the registry protocol over a local network.
+//! Every connection carries exactly one request and one response.
+
+use std::{
+    io::{self, BufReader, Read as _, Write as _},
+    net::TcpStream,
+};
+
+/// The largest request or response body that will be read (i.e. the largest publishable service).
+const MAX_BODY_LEN: usize = 64 * 1024 * 1024;
+
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub body: Vec<u8>,
+}
+
+pub struct Response {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    pub fn json(status: u16, value: &serde_json::Value) -> Self {
+        Self {
+            status,
+            content_type: "application/json",
+            body: serde_json::to_vec(value).unwrap(),
+        }
+    }
+
+    pub fn error(status: u16, message: impl std::fmt::Display) -> Self {
+        Self::json(status, &serde_json::json!({ "error": message.to_string() }))
+    }
+}
+
+/// Sends a request to `host` (e.g., `localhost:8000`) and returns the status and body of the
+/// response.
+pub fn send(
+    host: &str,
+    method: &str,
+    path: &str,
+    body: &[u8],
+) -> Result<(u16, Vec<u8>), io::Error> {
+    let mut stream = TcpStream::connect(host)?;
+    write!(
+        stream,
+        "{} {} HTTP/1.1\r\nHost: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        method,
+        path,
+        host,
+        body.len()
+    )?;
+    stream.write_all(body)?;
+    stream.flush()?;
+
+    let mut reader = BufReader::new(stream);
+    let status_line = read_line(&mut reader)?;
+    let status = status_line
+        .split(' ')
+        .nth(1)
+        .and_then(|status| status.parse().ok())
+        .ok_or_else(|| invalid_data(format!("Invalid status line: `{}`", status_line)))?;
+    let body = match read_content_length(&mut reader)? {
+        Some(len) => read_body(&mut reader, len)?,
+        None => {
+            let mut body = Vec::new();
+            reader.take(MAX_BODY_LEN as u64).read_to_end(&mut body)?;
+            body
+        }
+    };
+    Ok((status, body))
+}
+
+/// Reads a request from `stream`, passes it to `handler`, and writes back the response.
+pub fn handle(stream: TcpStream, handler: impl FnOnce(Request) -> Response) -> io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let request_line = read_line(&mut reader)?;
+    let mut parts = request_line.split(' ');
+    let response = match (parts.next(), parts.next()) {
+        (Some(method), Some(path)) => {
+            let method = method.to_string();
+            let path = path.to_string();
+            match read_content_length(&mut reader)? {
+                Some(len) if len > MAX_BODY_LEN => Response::error(413, "Body is too large"),
+                len => handler(Request {
+                    method,
+                    path,
+                    body: read_body(&mut reader, len.unwrap_or(0))?,
+                }),
+            }
+        }
+        _ => Response::error(400, "Malformed request line"),
+    };
+
+    let mut stream = stream;
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        response.status,
+        reason(response.status),
+        response.content_type,
+        response.body.len()
+    )?;
+    stream.write_all(&response.body)?;
+    stream.flush()
+}
+
+fn read_line(reader: &mut impl io::BufRead) -> io::Result<String> {
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    Ok(line.trim_end().to_string())
+}
+
+/// Reads the headers and returns the value of `Content-Length`, if present.
+fn read_content_length(reader: &mut impl io::BufRead) -> io::Result<Option<usize>> {
+    let mut content_length = None;
+    loop {
+        let line = read_line(reader)?;
+        if line.is_empty() {
+            return Ok(content_length);
+        }
+        let mut header = line.splitn(2, ':');
+        if let (Some(name), Some(value)) = (header.next(), header.next()) {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                content_length = Some(value.trim().parse().map_err(|_| {
+                    invalid_data(format!("Invalid Content-Length: `{}`", value.trim()))
+                })?);
+            }
+        }
+    }
+}
+
+fn read_body(reader: &mut impl io::Read, len: usize) -> io::Result<Vec<u8>> {
+    if len > MAX_BODY_LEN {
+        return Err(invalid_data(format!("Body of {} bytes is too large", len)));
+    }
+    let mut body = vec![0; len];
+    reader.read_exact(&mut body)?;
+    Ok(body)
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        201 => "Created",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        413 => "Payload Too Large",
+        _ => "Internal Server Error",
+    }
+}
@@ -0,0 +1,131 @@
+//! A registry from which services can be imported without sharing their Wasm files.
+//!
+//! The registry protocol is a small JSON-over-HTTP API. Services are named by their interface
+//! name or namespace; as with imports, case, `-`, and `_` are not significant. Versions are semver.
+//!
+//! | Request | Response |
+//! | --- | --- |
+//! | `GET /v1/services` | `["<name>", ...]` |
+//! | `GET /v1/services/<name>` | `{ "name": "<name>", "versions": ["<version>", ...] }` |
+//! | `GET /v1/services/<name>/<version>/interface` | the interface, as JSON |
+//! | `GET /v1/services/<name>/<version>/wasm` | the bytecode of the service |
+//! | `PUT /v1/services/<name>/<version>` | publishes the bytecode in the request body |
+//!
+//! Published bytecode must contain an interface section whose name and version match the path.
+//! A published version cannot be changed, so republishing it is only accepted if the bytecode
+//! is identical. Errors are returned as `{ "error": "<message>" }`.
+
+mod http;
+mod server;
+
+pub use server::Server;
+
+use crate::Interface;
+
+/// A client of the registry at a `registry://` or `http://` URL (e.g., `registry://localhost:8000`).
+pub struct Client {
+    host: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ServiceVersions {
+    pub name: String,
+    pub versions: Vec<String>,
+}
+
+impl Client {
+    pub fn new(url: &url::Url) -> Result<Self, RegistryError> {
+        match (url.scheme(), url.host_str()) {
+            ("registry", Some(host)) | ("http", Some(host)) => Ok(Self {
+                host: format!("{}:{}", host, url.port().unwrap_or(80)),
+            }),
+            _ => Err(RegistryError::InvalidUrl(url.to_string())),
+        }
+    }
+
+    /// Returns the canonical URL of the registry, which is recorded in the interfaces
+    /// of services that import from it.
+    pub fn url(&self) -> String {
+        format!("registry://{}", self.host)
+    }
+
+    /// Returns the names of the services in the registry.
+    pub fn services(&self) -> Result<Vec<String>, RegistryError> {
+        Ok(serde_json::from_slice(&self.get("/v1/services")?)?)
+    }
+
+    /// Returns the published versions of the service, in no particular order.
+    pub fn versions(&self, name: &str) -> Result<ServiceVersions, RegistryError> {
+        Ok(serde_json::from_slice(
+            &self.get(&format!("/v1/services/{}", name))?,
+        )?)
+    }
+
+    pub fn interface(&self, name: &str, version: &str) -> Result<Interface, RegistryError> {
+        Ok(serde_json::from_slice(&self.get(&format!(
+            "/v1/services/{}/{}/interface",
+            name, version
+        ))?)?)
+    }
+
+    pub fn wasm(&self, name: &str, version: &str) -> Result<Vec<u8>, RegistryError> {
+        self.get(&format!("/v1/services/{}/{}/wasm", name, version))
+    }
+
+    /// Publishes the service, which is named by its interface section.
+    pub fn publish(&self, wasm: &[u8]) -> Result<Interface, RegistryError> {
+        let iface = crate::wasm::interface(wasm)
+            .map_err(|err| RegistryError::InvalidService(err.to_string()))?
+            .ok_or_else(|| RegistryError::InvalidService("missing interface section".into()))?;
+        let path = format!("/v1/services/{}/{}", iface.name, iface.version);
+        self.request("PUT", &path, wasm)?;
+        Ok(iface)
+    }
+
+    fn get(&self, path: &str) -> Result<Vec<u8>, RegistryError> {
+        self.request("GET", path, &[])
+    }
+
+    fn request(&self, method: &str, path: &str, body: &[u8]) -> Result<Vec<u8>, RegistryError> {
+        let (status, body) = http::send(&self.host, method, path, body)
+            .map_err(|err| RegistryError::Io(self.url(), err))?;
+        if status < 300 {
+            return Ok(body);
+        }
+        let message = serde_json::from_slice::<serde_json::Value>(&body)
+            .ok()
+            .and_then(|err| err["error"].as_str().map(str::to_string))
+            .unwrap_or_else(|| String::from_utf8_lossy(&body).into_owned());
+        Err(match status {
+            404 => RegistryError::NotFound(message),
+            _ => RegistryError::Rejected(status, message),
+        })
+    }
+}
+
+#[derive(Debug, failure::Fail)]
+pub enum RegistryError {
+    #[fail(display = "Invalid registry URL `{}`", _0)]
+    InvalidUrl(String),
+
+    #[fail(display = "Could not contact registry at `{}`: {}", _0, _1)]
+    Io(String /* registry */, #[fail(cause)] std::io::Error),
+
+    #[fail(display = "{}", _0)]
+    NotFound(String),
+
+    #[fail(display = "Registry responded with status {}: {}", _0, _1)]
+    Rejected(u16, String),
+
+    #[fail(display = "Invalid service: {}", _0)]
+    InvalidService(String),
+
+    #[fail(display = "Invalid registry response: {}", _0)]
+    InvalidResponse(#[fail(cause)] serde_json::Error),
+}
+
+impl From<serde_json::Error> for RegistryError {
+    fn from(err: serde_json::Error) -> Self {
+        RegistryError::InvalidResponse(err)
+    }
+}
@@ -0,0 +1,273 @@
+use std::{
+    net::TcpListener,
+    path::{Path, PathBuf},
+};
+
+use serde_json::json;
+
+use super::http::{self, Request, Response};
+
+/// A reference registry that serves the services stored in a directory.
+/// Each published version is stored as `<root>/<service name>/<version>.wasm`, which
+/// is also a layout from which services can be imported using a `file://` URL.
+pub struct Server {
+    root: PathBuf,
+}
+
+impl Server {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Handles requests, one at a time, until accepting a connection fails.
+    pub fn serve(&self, listener: TcpListener) -> std::io::Result<()> {
+        for stream in listener.incoming() {
+            // A misbehaving client should not bring down the registry.
+            if let Err(err) = http::handle(stream?, |req| self.respond(req)) {
+                eprintln!("registry: {}", err);
+            }
+        }
+        Ok(())
+    }
+
+    fn respond(&self, req: Request) -> Response {
+        let path = req.path.split('?').next().unwrap_or_default();
+        let segments: Vec<_> = path.trim_matches('/').split('/').collect();
+        let result = match (req.method.as_str(), segments.as_slice()) {
+            ("GET", ["v1", "services"]) => self.services().map(|names| json!(names)),
+            ("GET", ["v1", "services", name]) => self
+                .find(name)
+                .and_then(|(name, dir)| Ok(json!({ "name": name, "versions": versions(&dir)? }))),
+            ("GET", ["v1", "services", name, version, "wasm"]) => {
+                return match self.read(name, version) {
+                    Ok(wasm) => Response {
+                        status: 200,
+                        content_type: "application/wasm",
+                        body: wasm,
+                    },
+                    Err(err) => err,
+                };
+            }
+            ("GET", ["v1", "services", name, version, "interface"]) => self
+                .read(name, version)
+                .and_then(|wasm| match crate::wasm::interface(&wasm) {
+                    Ok(Some(iface)) => Ok(json!(iface)),
+                    _ => Err(Response::error(
+                        500,
+                        "Stored service has no valid interface",
+                    )),
+                }),
+            ("PUT", ["v1", "services", name, version]) => {
+                return self
+                    .publish(name, version, req.body)
+                    .unwrap_or_else(|err| err);
+            }
+            (_, ["v1", "services", ..]) => Err(Response::error(405, "Method not allowed")),
+            _ => Err(Response::error(
+                404,
+                format!("No such resource: `{}`", path),
+            )),
+        };
+        match result {
+            Ok(value) => Response::json(200, &value),
+            Err(err) => err,
+        }
+    }
+
+    fn services(&self) -> Result<Vec<String>, Response> {
+        Ok(self
+            .service_dirs()?
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect())
+    }
+
+    /// Returns the name and directory of the service referred to by `name`.
+    fn find(&self, name: &str) -> Result<(String, PathBuf), Response> {
+        let dirs = self.service_dirs()?;
+        let found = dirs
+            .iter()
+            .find(|(dir_name, _)| crate::import::same_name(dir_name, name))
+            .or_else(|| {
+                // `name` may instead be the namespace (i.e. crate name) of the service.
+                dirs.iter().find(|(_, dir)| {
+                    let latest = versions(dir)
+                        .ok()
+                        .and_then(|versions| versions.last().cloned());
+                    latest
+                        .and_then(|version| std::fs::read(wasm_path(dir, &version)).ok())
+                        .and_then(|wasm| crate::wasm::interface(&wasm).ok())
+                        .and_then(|iface| iface)
+                        .map(|iface| crate::import::is_named(&iface, name))
+                        .unwrap_or(false)
+                })
+            });
+        found
+            .cloned()
+            .ok_or_else(|| Response::error(404, format!("No service named `{}`", name)))
+    }
+
+    fn read(&self, name: &str, version: &str) -> Result<Vec<u8>, Response> {
+        let (name, dir) = self.find(name)?;
+        let not_found = || Response::error(404, format!("No version {} of `{}`", version, name));
+        if semver::Version::parse(version).is_err() {
+            return Err(not_found());
+        }
+        std::fs::read(wasm_path(&dir, version)).map_err(|_| not_found())
+    }
+
+    fn publish(&self, name: &str, version: &str, wasm: Vec<u8>) -> Result<Response, Response> {
+        let bad_request = |msg: String| Response::error(400, msg);
+        let iface = crate::wasm::interface(&wasm)
+            .map_err(|err| bad_request(format!("Invalid service: {}", err)))?
+            .ok_or_else(|| bad_request("Service has no interface section".to_string()))?;
+        iface
+            .validate()
+            .map_err(|err| bad_request(err.to_string()))?;
+        if !crate::import::is_named(&iface, name) || iface.version != version {
+            return Err(bad_request(format!(
+                "Cannot publish `{}` v{} as `{}` v{}",
+                iface.name, iface.version, name, version
+            )));
+        }
+        crate::import::version_of(&iface).map_err(|err| bad_request(err.to_string()))?;
+
+        let dir = self.root.join(&iface.name);
+        let path = wasm_path(&dir, version);
+        match std::fs::read(&path) {
+            Ok(ref published) if *published == wasm => {
+                return Ok(Response::json(
+                    200,
+                    &json!({ "name": iface.name, "version": version }),
+                ));
+            }
+            Ok(_) => {
+                return Err(Response::error(
+                    409,
+                    format!("`{}` v{} has already been published", iface.name, version),
+                ));
+            }
+            Err(_) => (),
+        }
+        // Write then rename so that a partially written service is never served.
+        let tmp_path = path.with_extension("wasm.tmp");
+        std::fs::create_dir_all(&dir)
+            .and_then(|_| std::fs::write(&tmp_path, &wasm))
+            .and_then(|_| std::fs::rename(&tmp_path, &path))
+            .map_err(|err| Response::error(500, err))?;
+        Ok(Response::json(
+            201,
+            &json!({ "name": iface.name, "version": version }),
+        ))
+    }
+
+    /// Returns the names and directories of the services in the registry, sorted by name.
+    fn service_dirs(&self) -> Result<Vec<(String, PathBuf)>, Response> {
+        let entries = match std::fs::read_dir(&self.root) {
+            Ok(entries) => entries,
+            Err(ref err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(Response::error(500, err)),
+        };
+        let mut dirs = Vec::new();
+        for entry in entries {
+            let entry = entry.map_err(|err| Response::error(500, err))?;
+            let path = entry.path();
+            if path.is_dir() {
+                if let Some(name) = entry.file_name().to_str() {
+                    dirs.push((name.to_string(), path));
+                }
+            }
+        }
+        dirs.sort();
+        Ok(dirs)
+    }
+}
+
+/// Returns the published versions of the service stored in `dir`, in ascending order.
+fn versions(dir: &Path) -> Result<Vec<String>, Response> {
+    let mut versions = Vec::new();
+    for entry in std::fs::read_dir(dir).map_err(|err| Response::error(500, err))? {
+        let path = entry.map_err(|err| Response::error(500, err))?.path();
+        if path.extension() != Some("wasm".as_ref()) {
+            continue;
+        }
+        let version = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .and_then(|stem| semver::Version::parse(stem).ok());
+        if let Some(version) = version {
+            versions.push(version);
+        }
+    }
+    versions.sort();
+    Ok(versions.iter().map(ToString::to_string).collect())
+}
+
+fn wasm_path(dir: &Path, version: &str) -> PathBuf {
+    dir.join(format!("{}.wasm", version))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::registry::Client;
+
+    #[test]
+    fn test_registry() {
+        let iface = crate::testing::test_service();
+        let with_version = |version: &str| {
+            let mut iface = iface.clone();
+            iface.version = version.to_string();
+            iface
+        };
+        let service = |version: &str| crate::testing::service_wasm(&with_version(version));
+
+        let temp_dir = crate::testing::TempDir::new("registry");
+        let root = temp_dir.path().to_path_buf();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("registry://{}", listener.local_addr().unwrap());
+        let server = Server::new(&root);
+        std::thread::spawn(move || server.serve(listener));
+
+        let client = Client::new(&url::Url::parse(&url).unwrap()).unwrap();
+        assert_eq!(client.services().unwrap(), Vec::<String>::new());
+        assert!(client.versions("TestService").is_err());
+
+        let v0_1_0 = service("0.1.0");
+        client.publish(&v0_1_0).unwrap();
+        client.publish(&service("0.2.0")).unwrap();
+        client.publish(&service("0.1.1")).unwrap();
+        client.publish(&v0_1_0).unwrap(); // republishing is idempotent
+        let mut changed = with_version("0.1.0");
+        changed.docs = None;
+        match client.publish(&crate::testing::service_wasm(&changed)) {
+            Err(crate::registry::RegistryError::Rejected(409, _)) => (),
+            result => panic!("unexpected result: {:?}", result.map(|iface| iface.version)),
+        }
+
+        assert_eq!(client.services().unwrap(), vec!["TestService"]);
+        let versions = client.versions("types").unwrap();
+        assert_eq!(versions.name, "TestService");
+        assert_eq!(versions.versions, vec!["0.1.0", "0.1.1", "0.2.0"]);
+        assert_eq!(client.wasm("test-service", "0.1.0").unwrap(), v0_1_0);
+        assert_eq!(
+            client.interface("TestService", "0.2.0").unwrap().version,
+            "0.2.0"
+        );
+        assert!(client.wasm("TestService", "0.3.0").is_err());
+
+        let import = |query: &str| {
+            crate::import::Importer::for_url(&format!("{}{}", url, query), root.clone())
+                .unwrap()
+                .import("types")
+                .map(|service| (service.interface.version, service.registry))
+        };
+        assert_eq!(
+            import("?version=0.1").unwrap(),
+            ("0.1.1".to_string(), Some(url.clone()))
+        );
+        assert_eq!(import("").unwrap().0, "0.2.0");
+        assert!(import("?version=0.3").is_err());
+    }
+}
@@ -0,0 +1,108 @@
+//! Function selectors: short, stable identifiers of RPC functions.
+//!
+//! A call that uses a selector is a single CBOR array, `[selector, [args...]]`, rather than
+//! the `{"method": name, "payload": [args...]}` map that names the function.
+//! Clients read the selector from `Function::selector` rather than computing it.
+
+use crate::Function;
+
+/// Returns the selector of the function with `signature`: the first four bytes
+/// (big-endian) of its keccak256 hash. Whitespace is not part of the signature.
+pub fn selector(signature: &str) -> u32 {
+    let signature: String = signature.chars().filter(|ch| !ch.is_whitespace()).collect();
+    let hash = tiny_keccak::keccak256(signature.as_bytes());
+    u32::from_be_bytes([hash[0], hash[1], hash[2], hash[3]])
+}
+
+/// Returns the selector of `func`, which is that of its canonical signature.
+pub fn function_selector(func: &Function) -> u32 {
+    selector(&signature(func))
+}
+
+/// Returns the canonical signature of `func`: its name and the types of its inputs and output
+/// as written in the IDL, e.g., `transfer(address, u128) -> Result<(), Error>`. It does not
+/// depend on the names of the inputs nor on how the service's source spells their types.
+pub fn signature(func: &Function) -> String {
+    let inputs: Vec<_> = func
+        .inputs
+        .iter()
+        .map(|input| crate::idl::fmt_ty(&input.ty))
+        .collect();
+    let output = match &func.output {
+        Some(ty) => format!(" -> {}", crate::idl::fmt_ty(ty)),
+        None => String::new(),
+    };
+    format!("{}({}){}", func.name, inputs.join(", "), output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_selector() {
+        let hash = tiny_keccak::keccak256(b"transfer(Address,u128)->Result<(),Error>");
+        assert_eq!(
+            selector("transfer(Address, u128) -> Result<(), Error>").to_be_bytes(),
+            &hash[..4]
+        );
+        assert_ne!(
+            selector("transfer(Address, u64) -> Result<(), Error>"),
+            selector("transfer(Address, u128) -> Result<(), Error>")
+        );
+    }
+
+    #[test]
+    fn test_function_selector() {
+        use crate::{Field, StateMutability, Type};
+
+        let input = |name: &str, ty: Type| Field {
+            name: name.to_string(),
+            ty,
+            docs: None,
+        };
+        let mut func = Function {
+            name: "transfer".to_string(),
+            mutability: StateMutability::Mutable,
+            inputs: vec![input("to", Type::Address), input("amount", Type::U128)],
+            output: Some(Type::Result(
+                Box::new(Type::Tuple(Vec::new())),
+                Box::new(Type::Defined {
+                    namespace: None,
+                    ty: "Error".to_string(),
+                }),
+            )),
+            docs: None,
+            selector: None,
+            payable: false,
+            only: Vec::new(),
+            when_not_paused: false,
+        };
+        assert_eq!(
+            signature(&func),
+            "transfer(address, u128) -> Result<(), Error>"
+        );
+        assert_eq!(
+            function_selector(&func),
+            selector("transfer(address, u128) -> Result<(), Error>")
+        );
+
+        let selector = function_selector(&func);
+        func.inputs[0].name = "recipient".to_string();
+        assert_eq!(function_selector(&func), selector);
+        func.output = None;
+        assert_ne!(function_selector(&func), selector);
+    }
+
+    #[test]
+    fn test_fixture_selectors() {
+        for func in crate::testing::test_service().functions.iter() {
+            assert_eq!(
+                func.selector,
+                Some(function_selector(func)),
+                "{}",
+                func.name
+            );
+        }
+    }
+}
@@ -0,0 +1,48 @@
+//! Fixtures shared by the unit tests.
+
+/// Returns the interface of `tests/idl-gen/res/TestService.json`.
+#[cfg(any(
+    feature = "codec",
+    feature = "gen",
+    feature = "saveload",
+    feature = "selector"
+))]
+pub(crate) fn test_service() -> crate::Interface {
+    let path = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../tests/idl-gen/res/TestService.json"
+    );
+    serde_json::from_slice(&std::fs::read(path).unwrap()).unwrap()
+}
+
+/// Returns an empty Wasm module that carries `iface`.
+#[cfg(feature = "saveload")]
+pub(crate) fn service_wasm(iface: &crate::Interface) -> Vec<u8> {
+    crate::wasm::replace_interface(b"\0asm\x01\0\0\0", iface).unwrap()
+}
+
+/// A new directory in the system temp dir that is removed, along with its contents,
+/// when dropped, even if the test panics.
+#[cfg(any(feature = "gen", feature = "import"))]
+pub(crate) struct TempDir(std::path::PathBuf);
+
+#[cfg(any(feature = "gen", feature = "import"))]
+impl TempDir {
+    /// Creates `oasis-<name>-<pid>`. `name` must be unique among the tests.
+    pub(crate) fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("oasis-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&path).unwrap();
+        TempDir(path)
+    }
+
+    pub(crate) fn path(&self) -> &std::path::Path {
+        &self.0
+    }
+}
+
+#[cfg(any(feature = "gen", feature = "import"))]
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        std::fs::remove_dir_all(&self.0).ok();
+    }
+}
@@ -0,0 +1,643 @@
+//! Checks that an `Interface` can be used to generate clients in every supported language.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{Field, Interface, Type, TypeDef, Variant};
+
+impl Interface {
+    /// Returns every problem that would prevent this interface from being imported.
+    pub fn validate(&self) -> Result<(), InvalidInterface> {
+        let mut validator = Validator {
+            iface: self,
+            errors: Vec::new(),
+        };
+        validator.validate();
+        if validator.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(InvalidInterface {
+                name: self.name.clone(),
+                errors: ValidationErrors(validator.errors),
+            })
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, failure::Fail)]
+#[fail(display = "Invalid interface for `{}`:{}", name, errors)]
+pub struct InvalidInterface {
+    pub name: String,
+    pub errors: ValidationErrors,
+}
+
+/// The problems with an interface, which are displayed one per line.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ValidationErrors(pub Vec<ValidationError>);
+
+impl std::fmt::Display for ValidationErrors {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for err in self.0.iter() {
+            write!(f, "\n        {}", err)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ValidationError {
+    /// `location` refers to a type that is not defined by the interface.
+    UnknownType {
+        location: String,
+        ty: String,
+    },
+    /// `location` refers to a type in a namespace that is not declared as an import.
+    UnknownNamespace {
+        location: String,
+        namespace: String,
+    },
+    DuplicateImport(String),
+    DuplicateFunction(String),
+    /// Two functions have the same selector, so calls that use it would be ambiguous.
+    DuplicateSelector {
+        selector: u32,
+        functions: (String, String),
+    },
+    /// The function has the selector that is reserved for batches of calls.
+    ReservedSelector(String),
+    DuplicateType(String),
+    /// A field, variant, or input named `name` appears more than once in `location`.
+    DuplicateName {
+        location: String,
+        name: String,
+    },
+    InvalidIdentifier {
+        location: String,
+        ident: String,
+    },
+    /// The type contains itself without indirection (e.g., through a `List`) and so
+    /// would have infinite size. `cycle` begins and ends with the type.
+    RecursiveType {
+        cycle: Vec<String>,
+    },
+}
+
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        use ValidationError::*;
+        match self {
+            UnknownType { location, ty } => {
+                write!(f, "{} refers to undefined type `{}`", location, ty)
+            }
+            UnknownNamespace {
+                location,
+                namespace,
+            } => write!(
+                f,
+                "{} refers to a type in `{}`, which is not imported",
+                location, namespace
+            ),
+            DuplicateImport(name) => write!(f, "`{}` is imported more than once", name),
+            DuplicateFunction(name) => write!(f, "function `{}` is defined more than once", name),
+            DuplicateSelector {
+                selector,
+                functions: (a, b),
+            } => write!(
+                f,
+                "functions `{}` and `{}` have the same selector `{:#010x}`",
+                a, b, selector
+            ),
+            ReservedSelector(name) => write!(
+                f,
+                "function `{}` has the selector reserved for batches and must be renamed",
+                name
+            ),
+            DuplicateType(name) => write!(f, "type `{}` is defined more than once", name),
+            DuplicateName { location, name } => {
+                write!(f, "{} has more than one `{}`", location, name)
+            }
+            InvalidIdentifier { location, ident } => {
+                write!(f, "{} has invalid identifier `{}`", location, ident)
+            }
+            RecursiveType { cycle } => write!(
+                f,
+                "type `{}` has infinite size: {}",
+                cycle[0],
+                cycle.join(" -> ")
+            ),
+        }
+    }
+}
+
+struct Validator<'a> {
+    iface: &'a Interface,
+    errors: Vec<ValidationError>,
+}
+
+impl<'a> Validator<'a> {
+    fn validate(&mut self) {
+        let iface = self.iface;
+        self.check_ident("interface", &iface.name);
+        self.check_ident("interface", &iface.namespace);
+
+        let mut import_names = BTreeSet::new();
+        for import in iface.imports.iter() {
+            self.check_ident("import", &import.name);
+            if !import_names.insert(&import.name) {
+                self.errors
+                    .push(ValidationError::DuplicateImport(import.name.clone()));
+            }
+        }
+
+        let mut type_names = BTreeSet::new();
+        for def in iface.type_defs.iter() {
+            let location = format!("type `{}`", def.name());
+            self.check_ident(&location, def.name());
+            if !type_names.insert(def.name()) {
+                self.errors
+                    .push(ValidationError::DuplicateType(def.name().to_string()));
+            }
+            match def {
+                TypeDef::Struct { fields, .. } => {
+                    let is_tuple_struct =
+                        crate::is_tuple_struct(fields.iter().map(|f| f.name.as_str()));
+                    for field in fields.iter() {
+                        if !is_tuple_struct {
+                            self.check_ident(&location, &field.name);
+                        }
+                        self.check_ty(&format!("field `{}.{}`", def.name(), field.name), &field.ty);
+                    }
+                    self.check_unique(&location, fields.iter().map(|f| f.name.as_str()));
+                }
+                TypeDef::Event { fields, .. } => {
+                    for field in fields.iter() {
+                        self.check_ident(&location, &field.name);
+                        self.check_ty(&format!("field `{}.{}`", def.name(), field.name), &field.ty);
+                    }
+                    self.check_unique(&location, fields.iter().map(|f| f.name.as_str()));
+                }
+                TypeDef::Enum { variants, .. } => {
+                    for variant in variants.iter() {
+                        self.check_ident(&location, variant.name());
+                        let variant_location =
+                            format!("variant `{}::{}`", def.name(), variant.name());
+                        match variant {
+                            Variant::Unit(_) => (),
+                            Variant::Tuple { items, .. } => {
+                                for ty in items.iter() {
+                                    self.check_ty(&variant_location, ty);
+                                }
+                            }
+                            Variant::Struct { fields, .. } => {
+                                for field in fields.iter() {
+                                    self.check_ident(&variant_location, &field.name);
+                                    self.check_ty(
+                                        &format!(
+                                            "field `{}::{}.{}`",
+                                            def.name(),
+                                            variant.name(),
+                                            field.name
+                                        ),
+                                        &field.ty,
+                                    );
+                                }
+                                self.check_unique(
+                                    &variant_location,
+                                    fields.iter().map(|f| f.name.as_str()),
+                                );
+                            }
+                        }
+                    }
+                    self.check_unique(&location, variants.iter().map(Variant::name));
+                }
+            }
+        }
+        self.check_recursion();
+
+        self.check_inputs("constructor", &iface.constructor.inputs);
+        if let Some(err_ty) = &iface.constructor.error {
+            self.check_ty("constructor error", err_ty);
+        }
+        if let Some(migration) = &iface.migration {
+            self.check_inputs("migration", &migration.inputs);
+            if let Some(err_ty) = &migration.error {
+                self.check_ty("migration error", err_ty);
+            }
+        }
+
+        let mut fn_names = BTreeSet::new();
+        let mut selectors = BTreeMap::new();
+        for func in iface.functions.iter() {
+            let location = format!("function `{}`", func.name);
+            self.check_ident(&location, &func.name);
+            if !fn_names.insert(&func.name) {
+                self.errors
+                    .push(ValidationError::DuplicateFunction(func.name.clone()));
+            }
+            if let Some(selector) = func.selector {
+                if selector == crate::BATCH_SELECTOR {
+                    self.errors
+                        .push(ValidationError::ReservedSelector(func.name.clone()));
+                }
+                if let Some(other) = selectors.insert(selector, &func.name) {
+                    self.errors.push(ValidationError::DuplicateSelector {
+                        selector,
+                        functions: (other.clone(), func.name.clone()),
+                    });
+                }
+            }
+            for principal in func.only.iter() {
+                if let crate::Principal::Role(role) = principal {
+                    self.check_ident(&format!("role of {}", location), role);
+                }
+            }
+            self.check_inputs(&location, &func.inputs);
+            if let Some(output) = &func.output {
+                self.check_ty(&format!("output of {}", location), output);
+            }
+        }
+    }
+
+    fn check_inputs(&mut self, location: &str, inputs: &[Field]) {
+        for inp in inputs.iter() {
+            self.check_ident(location, &inp.name);
+            self.check_ty(&format!("input `{}` of {}", inp.name, location), &inp.ty);
+        }
+        self.check_unique(location, inputs.iter().map(|inp| inp.name.as_str()));
+    }
+
+    fn check_ident(&mut self, location: &str, ident: &str) {
+        let mut chars = ident.chars();
+        let is_valid = chars
+            .next()
+            .map(|ch| ch.is_ascii_alphabetic() || ch == '_')
+            .unwrap_or_default()
+            && chars.all(|ch| ch.is_ascii_alphanumeric() || ch == '_');
+        if !is_valid {
+            self.errors.push(ValidationError::InvalidIdentifier {
+                location: location.to_string(),
+                ident: ident.to_string(),
+            });
+        }
+    }
+
+    fn check_unique<'n>(&mut self, location: &str, names: impl Iterator<Item = &'n str>) {
+        let mut seen = BTreeSet::new();
+        let mut reported = BTreeSet::new();
+        for name in names {
+            if !seen.insert(name) && reported.insert(name) {
+                self.errors.push(ValidationError::DuplicateName {
+                    location: location.to_string(),
+                    name: name.to_string(),
+                });
+            }
+        }
+    }
+
+    fn check_ty(&mut self, location: &str, ty: &Type) {
+        use Type::*;
+        match ty {
+            Defined {
+                namespace: Some(namespace),
+                ..
+            } => {
+                if !self.iface.imports.iter().any(|imp| imp.name == *namespace) {
+                    self.errors.push(ValidationError::UnknownNamespace {
+                        location: location.to_string(),
+                        namespace: namespace.clone(),
+                    });
+                }
+            }
+            Defined {
+                namespace: None,
+                ty,
+            } => {
+                if !self.iface.type_defs.iter().any(|def| def.name() == ty) {
+                    self.errors.push(ValidationError::UnknownType {
+                        location: location.to_string(),
+                        ty: ty.clone(),
+                    });
+                }
+            }
+            Tuple(tys) => tys.iter().for_each(|ty| self.check_ty(location, ty)),
+            Array(ty, _) | List(ty) | Set(ty) | Optional(ty) => self.check_ty(location, ty),
+            Map(k_ty, v_ty) | Result(k_ty, v_ty) => {
+                self.check_ty(location, k_ty);
+                self.check_ty(location, v_ty);
+            }
+            _ => (),
+        }
+    }
+
+    /// Reports types that contain themselves by value.
+    fn check_recursion(&mut self) {
+        // Edges from each type to the local types that it contains by value.
+        let mut contains: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        for def in self.iface.type_defs.iter() {
+            let mut contained = Vec::new();
+            match def {
+                TypeDef::Struct { fields, .. } => fields
+                    .iter()
+                    .for_each(|f| collect_by_value(&f.ty, &mut contained)),
+                TypeDef::Event { fields, .. } => fields
+                    .iter()
+                    .for_each(|f| collect_by_value(&f.ty, &mut contained)),
+                TypeDef::Enum { variants, .. } => {
+                    for variant in variants.iter() {
+                        match variant {
+                            Variant::Unit(_) => (),
+                            Variant::Tuple { items, .. } => items
+                                .iter()
+                                .for_each(|ty| collect_by_value(ty, &mut contained)),
+                            Variant::Struct { fields, .. } => fields
+                                .iter()
+                                .for_each(|f| collect_by_value(&f.ty, &mut contained)),
+                        }
+                    }
+                }
+            }
+            contains.entry(def.name()).or_default().extend(contained);
+        }
+
+        let mut reported = BTreeSet::new();
+        for def in self.iface.type_defs.iter() {
+            let mut path = vec![def.name()];
+            if let Some(cycle) = find_cycle(&contains, &mut path, &mut BTreeSet::new()) {
+                // Each cycle is reported once, starting from the first type that it contains.
+                let mut members: Vec<_> = cycle[1..].to_vec();
+                members.sort();
+                if reported.insert(members) {
+                    self.errors.push(ValidationError::RecursiveType {
+                        cycle: cycle.iter().map(|ty| ty.to_string()).collect(),
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// Collects the local types that are stored inline in a value of type `ty`.
+/// Collections are stored on the heap and so do not contain their elements by value.
+fn collect_by_value<'a>(ty: &'a Type, contained: &mut Vec<&'a str>) {
+    use Type::*;
+    match ty {
+        Defined {
+            namespace: None,
+            ty,
+        } => contained.push(ty),
+        Tuple(tys) => tys.iter().for_each(|ty| collect_by_value(ty, contained)),
+        Array(ty, len) if *len > 0 => collect_by_value(ty, contained),
+        Optional(ty) => collect_by_value(ty, contained),
+        Result(ok_ty, err_ty) => {
+            collect_by_value(ok_ty, contained);
+            collect_by_value(err_ty, contained);
+        }
+        _ => (),
+    }
+}
+
+/// Returns the path from `path[0]` back to itself through `contains`, if one exists.
+fn find_cycle<'a>(
+    contains: &BTreeMap<&'a str, Vec<&'a str>>,
+    path: &mut Vec<&'a str>,
+    visited: &mut BTreeSet<&'a str>,
+) -> Option<Vec<&'a str>> {
+    let current = *path.last().unwrap();
+    for next in contains.get(current).into_iter().flatten() {
+        if *next == path[0] {
+            let mut cycle = path.clone();
+            cycle.push(next);
+            return Some(cycle);
+        }
+        if visited.insert(next) {
+            path.push(next);
+            if let Some(cycle) = find_cycle(contains, path, visited) {
+                return Some(cycle);
+            }
+            path.pop();
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{Constructor, Function, Import, StateMutability};
+
+    fn defined(ty: &str) -> Type {
+        Type::Defined {
+            namespace: None,
+            ty: ty.to_string(),
+        }
+    }
+
+    fn field(name: &str, ty: Type) -> Field {
+        Field {
+            name: name.to_string(),
+            ty,
+            docs: None,
+        }
+    }
+
+    fn strukt(name: &str, fields: Vec<Field>) -> TypeDef {
+        TypeDef::Struct {
+            name: name.to_string(),
+            fields,
+            docs: None,
+        }
+    }
+
+    fn iface(type_defs: Vec<TypeDef>, functions: Vec<Function>) -> Interface {
+        Interface {
+            name: "Service".to_string(),
+            namespace: "service".to_string(),
+            version: "0.1.0".to_string(),
+            imports: vec![Import {
+                name: "other".to_string(),
+                version: "0.1.0".to_string(),
+                registry: None,
+            }],
+            type_defs,
+            constructor: Constructor {
+                inputs: Vec::new(),
+                error: None,
+                docs: None,
+            },
+            migration: None,
+            functions,
+            oasis_build_version: "0.2.2".to_string(),
+            docs: None,
+        }
+    }
+
+    fn errors(iface: Interface) -> Vec<ValidationError> {
+        iface.validate().unwrap_err().errors.0
+    }
+
+    #[test]
+    fn test_fixtures() {
+        for res_dir in &["../tests/idl-gen/res", "../tests/xcc-a/res"] {
+            let res_dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join(res_dir);
+            for entry in std::fs::read_dir(res_dir).unwrap() {
+                let iface: Interface =
+                    serde_json::from_slice(&std::fs::read(entry.unwrap().path()).unwrap()).unwrap();
+                assert_eq!(iface.validate(), Ok(()));
+            }
+        }
+    }
+
+    #[test]
+    fn test_unknown_types() {
+        let func = Function {
+            name: "f".to_string(),
+            mutability: StateMutability::Immutable,
+            inputs: vec![field("a", Type::List(Box::new(defined("Missing"))))],
+            output: Some(Type::Defined {
+                namespace: Some("unimported".to_string()),
+                ty: "T".to_string(),
+            }),
+            docs: None,
+            selector: None,
+            payable: false,
+            only: Vec::new(),
+            when_not_paused: false,
+        };
+        assert_eq!(
+            errors(iface(Vec::new(), vec![func])),
+            vec![
+                ValidationError::UnknownType {
+                    location: "input `a` of function `f`".to_string(),
+                    ty: "Missing".to_string(),
+                },
+                ValidationError::UnknownNamespace {
+                    location: "output of function `f`".to_string(),
+                    namespace: "unimported".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_duplicates_and_identifiers() {
+        let func = Function {
+            name: "f".to_string(),
+            mutability: StateMutability::Mutable,
+            inputs: vec![field("a", Type::U8), field("a", Type::U8)],
+            output: None,
+            docs: None,
+            selector: None,
+            payable: false,
+            only: vec![crate::Principal::Role("mint-er".to_string())],
+            when_not_paused: false,
+        };
+        let type_defs = vec![
+            strukt("S", vec![field("x", Type::U8), field("x-y", Type::U8)]),
+            strukt("S", Vec::new()),
+            strukt("T", vec![field("0", Type::U8), field("1", Type::U8)]),
+        ];
+        let role_err = ValidationError::InvalidIdentifier {
+            location: "role of function `f`".to_string(),
+            ident: "mint-er".to_string(),
+        };
+        assert_eq!(
+            errors(iface(type_defs, vec![func.clone(), func])),
+            vec![
+                ValidationError::InvalidIdentifier {
+                    location: "type `S`".to_string(),
+                    ident: "x-y".to_string(),
+                },
+                ValidationError::DuplicateType("S".to_string()),
+                role_err.clone(),
+                ValidationError::DuplicateName {
+                    location: "function `f`".to_string(),
+                    name: "a".to_string(),
+                },
+                ValidationError::DuplicateFunction("f".to_string()),
+                role_err,
+                ValidationError::DuplicateName {
+                    location: "function `f`".to_string(),
+                    name: "a".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_duplicate_selectors() {
+        let func = |name: &str| Function {
+            name: name.to_string(),
+            mutability: StateMutability::Immutable,
+            inputs: Vec::new(),
+            output: None,
+            docs: None,
+            selector: Some(0xdead_beef),
+            payable: false,
+            only: Vec::new(),
+            when_not_paused: false,
+        };
+        let err = ValidationError::DuplicateSelector {
+            selector: 0xdead_beef,
+            functions: ("f".to_string(), "g".to_string()),
+        };
+        assert_eq!(
+            err.to_string(),
+            "functions `f` and `g` have the same selector `0xdeadbeef`"
+        );
+        assert_eq!(
+            errors(iface(Vec::new(), vec![func("f"), func("g")])),
+            vec![err]
+        );
+
+        let mut batch = func("batch");
+        batch.selector = Some(crate::BATCH_SELECTOR);
+        assert_eq!(
+            errors(iface(Vec::new(), vec![batch])),
+            vec![ValidationError::ReservedSelector("batch".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_recursion() {
+        let type_defs = vec![
+            strukt(
+                "A",
+                vec![field("b", Type::Optional(Box::new(defined("B"))))],
+            ),
+            strukt(
+                "B",
+                vec![field("a", Type::Tuple(vec![Type::U8, defined("A")]))],
+            ),
+            strukt(
+                "List",
+                vec![field("next", Type::List(Box::new(defined("List"))))],
+            ),
+            strukt(
+                "Empty",
+                vec![field("0", Type::Array(Box::new(defined("Empty")), 0))],
+            ),
+            TypeDef::Enum {
+                name: "Expr".to_string(),
+                variants: vec![
+                    Variant::Unit("Zero".to_string()),
+                    Variant::Struct {
+                        name: "Neg".to_string(),
+                        fields: vec![field("expr", defined("Expr"))],
+                    },
+                ],
+                docs: None,
+            },
+        ];
+        assert_eq!(
+            errors(iface(type_defs, Vec::new())),
+            vec![
+                ValidationError::RecursiveType {
+                    cycle: vec!["A".to_string(), "B".to_string(), "A".to_string()],
+                },
+                ValidationError::RecursiveType {
+                    cycle: vec!["Expr".to_string(), "Expr".to_string()],
+                },
+            ]
+        );
+    }
+}
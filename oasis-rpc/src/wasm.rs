@@ -0,0 +1,231 @@
+//! Reads and edits the sections of a service's Wasm bytecode without re-encoding the module,
+//! so that artifacts can be audited and repacked byte-for-byte.
+
+use crate::Interface;
+
+/// The name of the custom section into which `oasis-build` packs the service's interface.
+pub const INTERFACE_SECTION: &str = "oasis-interface";
+
+const MAGIC: &[u8] = b"\0asm";
+const VERSION: &[u8] = &[1, 0, 0, 0];
+
+#[derive(Debug, failure::Fail)]
+pub enum WasmError {
+    #[fail(display = "Not a Wasm module")]
+    NotWasm,
+
+    #[fail(display = "Malformed Wasm module: {} at offset {}", _0, _1)]
+    Malformed(&'static str, usize),
+}
+
+/// A top-level section of a Wasm module.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Section<'a> {
+    pub id: u8,
+    /// The name of a custom section.
+    pub name: Option<&'a str>,
+    /// The contents of the section, excluding the name of a custom section.
+    pub data: &'a [u8],
+    /// The number of bytes that the section occupies in the module, including its header.
+    pub encoded_len: usize,
+}
+
+impl<'a> Section<'a> {
+    /// Returns the name of a custom section or the kind of a known section.
+    pub fn kind(&self) -> &'a str {
+        match (self.id, self.name) {
+            (0, Some(name)) => name,
+            (1, _) => "type",
+            (2, _) => "import",
+            (3, _) => "function",
+            (4, _) => "table",
+            (5, _) => "memory",
+            (6, _) => "global",
+            (7, _) => "export",
+            (8, _) => "start",
+            (9, _) => "element",
+            (10, _) => "code",
+            (11, _) => "data",
+            (12, _) => "datacount",
+            _ => "unknown",
+        }
+    }
+
+    pub fn is_custom(&self) -> bool {
+        self.id == 0
+    }
+}
+
+/// Splits a Wasm module into its sections, in order.
+pub fn sections(wasm: &[u8]) -> Result<Vec<Section>, WasmError> {
+    if wasm.len() < 8 || &wasm[..4] != MAGIC || &wasm[4..8] != VERSION {
+        return Err(WasmError::NotWasm);
+    }
+    let mut sections = Vec::new();
+    let mut pos = 8;
+    while pos < wasm.len() {
+        let start = pos;
+        let id = wasm[pos];
+        pos += 1;
+        let len = read_u32(wasm, &mut pos)? as usize;
+        let end = pos
+            .checked_add(len)
+            .filter(|&end| end <= wasm.len())
+            .ok_or(WasmError::Malformed("section overruns module", start))?;
+        let mut data = &wasm[pos..end];
+        let name = if id == 0 {
+            let mut name_pos = pos;
+            let name_len = read_u32(&wasm[..end], &mut name_pos)? as usize;
+            let name_end = name_pos
+                .checked_add(name_len)
+                .filter(|&name_end| name_end <= end)
+                .ok_or(WasmError::Malformed("name overruns section", name_pos))?;
+            data = &wasm[name_end..end];
+            Some(
+                std::str::from_utf8(&wasm[name_pos..name_end])
+                    .map_err(|_| WasmError::Malformed("name is not UTF-8", name_pos))?,
+            )
+        } else {
+            None
+        };
+        sections.push(Section {
+            id,
+            name,
+            data,
+            encoded_len: end - start,
+        });
+        pos = end;
+    }
+    Ok(sections)
+}
+
+/// Returns the contents of the interface section, if the module has one.
+pub fn interface_section(wasm: &[u8]) -> Result<Option<&[u8]>, WasmError> {
+    Ok(sections(wasm)?
+        .into_iter()
+        .find(|s| s.name == Some(INTERFACE_SECTION))
+        .map(|s| s.data))
+}
+
+/// Returns the interface packed into the module, if any.
+pub fn interface(wasm: &[u8]) -> Result<Option<Interface>, failure::Error> {
+    Ok(match interface_section(wasm)? {
+        Some(data) => Some(Interface::from_slice(data)?),
+        None => None,
+    })
+}
+
+/// Returns a copy of the module without its interface section.
+pub fn strip_interface(wasm: &[u8]) -> Result<Vec<u8>, WasmError> {
+    let mut stripped = wasm[..8].to_vec();
+    let mut pos = 8;
+    for section in sections(wasm)? {
+        if section.name != Some(INTERFACE_SECTION) {
+            stripped.extend_from_slice(&wasm[pos..pos + section.encoded_len]);
+        }
+        pos += section.encoded_len;
+    }
+    Ok(stripped)
+}
+
+/// Returns a copy of the module whose interface section is `iface`.
+/// Any existing interface section is removed and the new one is appended to the module.
+pub fn replace_interface(wasm: &[u8], iface: &Interface) -> Result<Vec<u8>, failure::Error> {
+    iface.validate()?;
+    let mut replaced = strip_interface(wasm)?;
+    let data = iface.to_vec()?;
+    let mut contents = Vec::with_capacity(INTERFACE_SECTION.len() + data.len() + 5);
+    write_u32(&mut contents, INTERFACE_SECTION.len() as u32);
+    contents.extend_from_slice(INTERFACE_SECTION.as_bytes());
+    contents.extend_from_slice(&data);
+    replaced.push(0);
+    write_u32(&mut replaced, contents.len() as u32);
+    replaced.extend_from_slice(&contents);
+    Ok(replaced)
+}
+
+/// Reads an unsigned LEB128-encoded integer.
+fn read_u32(buf: &[u8], pos: &mut usize) -> Result<u32, WasmError> {
+    let start = *pos;
+    let mut result = 0u32;
+    for shift in (0..35).step_by(7) {
+        let byte = *buf
+            .get(*pos)
+            .ok_or(WasmError::Malformed("truncated integer", start))?;
+        *pos += 1;
+        result |= u32::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(result);
+        }
+    }
+    Err(WasmError::Malformed("integer too long", start))
+}
+
+fn write_u32(buf: &mut Vec<u8>, mut value: u32) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            buf.push(byte);
+            return;
+        }
+        buf.push(byte | 0x80);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An empty module with a type section and a custom section named `name`.
+    fn module() -> Vec<u8> {
+        let mut wasm = [MAGIC, VERSION].concat();
+        wasm.extend_from_slice(&[1, 1, 0]); // type section with no types
+        wasm.extend_from_slice(&[0, 7, 4, b'n', b'a', b'm', b'e', 1, 2]);
+        wasm
+    }
+
+    #[test]
+    fn test_sections() {
+        let wasm = module();
+        let sections = sections(&wasm).unwrap();
+        assert_eq!(sections.len(), 2);
+        assert_eq!(sections[0].kind(), "type");
+        assert_eq!(sections[0].encoded_len, 3);
+        assert_eq!(sections[1].kind(), "name");
+        assert_eq!(sections[1].data, &[1, 2]);
+        assert_eq!(sections[1].encoded_len, 9);
+
+        assert!(super::sections(b"\0asm").is_err());
+        assert!(super::sections(&wasm[..wasm.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_replace_strip() {
+        let wasm = module();
+        assert_eq!(interface(&wasm).unwrap(), None);
+
+        let iface = crate::testing::test_service();
+        let packed = replace_interface(&wasm, &iface).unwrap();
+        assert_eq!(interface(&packed).unwrap().as_ref(), Some(&iface));
+        assert_eq!(&packed[..wasm.len()], wasm.as_slice());
+
+        let repacked = replace_interface(&packed, &iface).unwrap();
+        assert_eq!(repacked, packed);
+        assert_eq!(strip_interface(&packed).unwrap(), wasm);
+
+        let mut big_iface = iface;
+        big_iface.docs = Some("docs ".repeat(10_000));
+        let packed = replace_interface(&wasm, &big_iface).unwrap();
+        assert_eq!(interface(&packed).unwrap(), Some(big_iface));
+    }
+
+    #[test]
+    fn test_leb128() {
+        for &value in &[0, 1, 127, 128, 300, 16_384, u32::max_value()] {
+            let mut buf = Vec::new();
+            write_u32(&mut buf, value);
+            assert_eq!(read_u32(&buf, &mut 0).unwrap(), value);
+        }
+    }
+}
@@ -0,0 +1,230 @@
+//! Ownership, roles, and pausing: the state checked by the access-control attributes.
+//!
+//! An RPC method marked `#[only(owner)]` may be called only by the owner of the service,
+//! and one marked `#[only(role = "minter")]` only by accounts that have the `minter` role.
+//! `#[only(owner, role = "minter")]` permits either. A method marked `#[when_not_paused]`
+//! cannot be called while the service is paused.
+//!
+//! The dispatcher checks the attributes against `Context::sender` before calling the method.
+//! A method that returns a `Result` fails with its error type's `From<AccessError>` conversion
+//! of the violation, and any other method panics.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use oasis_types::Address;
+
+/// A service whose RPC methods have access-control attributes.
+pub trait AccessControlled {
+    /// Returns the `AccessControl` stored in the service state.
+    fn access_control(&self) -> &AccessControl;
+}
+
+/// The owner of a service, the roles granted to accounts, and whether the service is paused.
+/// Services store one in their state and expose it through `AccessControlled`.
+///
+/// The methods that modify it do not check the sender. They are meant to be called by
+/// RPC methods that have access-control attributes, e.g.
+///
+/// ```ignore
+/// #[oasis_std::only(owner)]
+/// pub fn add_minter(&mut self, _ctx: &Context, minter: Address) {
+///     self.access.grant_role("minter", minter);
+/// }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct AccessControl {
+    owner: Address,
+    roles: BTreeMap<String, BTreeSet<Address>>,
+    paused: bool,
+}
+
+impl AccessControl {
+    /// Creates an unpaused `AccessControl` in which no account has a role.
+    pub fn new(owner: Address) -> Self {
+        Self {
+            owner,
+            ..Default::default()
+        }
+    }
+
+    pub fn owner(&self) -> Address {
+        self.owner
+    }
+
+    pub fn transfer_ownership(&mut self, new_owner: Address) {
+        self.owner = new_owner;
+    }
+
+    pub fn has_role(&self, role: &str, account: &Address) -> bool {
+        self.roles
+            .get(role)
+            .map(|accounts| accounts.contains(account))
+            .unwrap_or_default()
+    }
+
+    /// Grants `role` to `account`. Returns whether the account did not already have it.
+    pub fn grant_role(&mut self, role: &str, account: Address) -> bool {
+        self.roles
+            .entry(role.to_string())
+            .or_default()
+            .insert(account)
+    }
+
+    /// Revokes `role` from `account`. Returns whether the account had it.
+    pub fn revoke_role(&mut self, role: &str, account: &Address) -> bool {
+        let accounts = match self.roles.get_mut(role) {
+            Some(accounts) => accounts,
+            None => return false,
+        };
+        let revoked = accounts.remove(account);
+        if accounts.is_empty() {
+            self.roles.remove(role);
+        }
+        revoked
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn unpause(&mut self) {
+        self.paused = false;
+    }
+
+    /// Checks the access-control attributes of an RPC method against its `sender`.
+    /// `owner` and `roles` are the principals permitted by `#[only(...)]`, if any.
+    #[doc(hidden)]
+    pub fn check(
+        &self,
+        sender: &Address,
+        owner: bool,
+        roles: &[&str],
+        when_not_paused: bool,
+    ) -> Result<(), AccessError> {
+        if when_not_paused && self.paused {
+            return Err(AccessError::Paused);
+        }
+        let restricted = owner || !roles.is_empty();
+        let permitted = (owner && *sender == self.owner)
+            || roles.iter().any(|role| self.has_role(role, sender));
+        if restricted && !permitted {
+            return Err(AccessError::NotPermitted);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AccessError {
+    /// The sender is neither the owner nor has any role that the RPC method permits.
+    NotPermitted,
+
+    /// The RPC method cannot be called while the service is paused.
+    Paused,
+}
+
+impl std::fmt::Display for AccessError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            AccessError::NotPermitted => write!(f, "The sender is not permitted to call the RPC."),
+            AccessError::Paused => write!(f, "The service is paused."),
+        }
+    }
+}
+
+impl std::error::Error for AccessError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OWNER: Address = Address([1u8; 20]);
+    const MINTER: Address = Address([2u8; 20]);
+    const OTHER: Address = Address([3u8; 20]);
+
+    fn access() -> AccessControl {
+        let mut access = AccessControl::new(OWNER);
+        access.grant_role("minter", MINTER);
+        access
+    }
+
+    #[test]
+    fn test_unrestricted() {
+        let access = access();
+        for sender in &[OWNER, MINTER, OTHER] {
+            assert_eq!(access.check(sender, false, &[], false), Ok(()));
+        }
+    }
+
+    #[test]
+    fn test_owner_only() {
+        let mut access = access();
+        assert_eq!(access.check(&OWNER, true, &[], false), Ok(()));
+        assert_eq!(
+            access.check(&MINTER, true, &[], false),
+            Err(AccessError::NotPermitted)
+        );
+
+        access.transfer_ownership(OTHER);
+        assert_eq!(access.check(&OTHER, true, &[], false), Ok(()));
+        assert_eq!(
+            access.check(&OWNER, true, &[], false),
+            Err(AccessError::NotPermitted)
+        );
+    }
+
+    #[test]
+    fn test_role_only() {
+        let mut access = access();
+        assert_eq!(access.check(&MINTER, false, &["minter"], false), Ok(()));
+        assert_eq!(
+            access.check(&OWNER, false, &["minter"], false),
+            Err(AccessError::NotPermitted)
+        );
+        assert_eq!(
+            access.check(&MINTER, false, &["burner"], false),
+            Err(AccessError::NotPermitted)
+        );
+
+        assert!(access.revoke_role("minter", &MINTER));
+        assert!(!access.revoke_role("minter", &MINTER));
+        assert_eq!(
+            access.check(&MINTER, false, &["minter"], false),
+            Err(AccessError::NotPermitted)
+        );
+    }
+
+    #[test]
+    fn test_owner_or_role() {
+        let access = access();
+        assert_eq!(access.check(&OWNER, true, &["minter"], false), Ok(()));
+        assert_eq!(access.check(&MINTER, true, &["minter"], false), Ok(()));
+        assert_eq!(
+            access.check(&OTHER, true, &["minter"], false),
+            Err(AccessError::NotPermitted)
+        );
+    }
+
+    #[test]
+    fn test_paused() {
+        let mut access = access();
+        access.pause();
+        assert_eq!(
+            access.check(&OWNER, true, &[], true),
+            Err(AccessError::Paused)
+        );
+        assert_eq!(
+            access.check(&OTHER, false, &[], true),
+            Err(AccessError::Paused)
+        );
+        // Methods without `#[when_not_paused]` can be called while the service is paused.
+        assert_eq!(access.check(&OWNER, true, &[], false), Ok(()));
+
+        access.unpause();
+        assert_eq!(access.check(&OTHER, false, &[], true), Ok(()));
+    }
+}
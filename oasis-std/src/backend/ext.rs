@@ -4,7 +4,7 @@ use super::Error;
 
 /// @see the `blockchain-traits` crate for descriptions of these methods.
 extern "C" {
-    pub fn oasis_balance(addr: *const Address, balance: *mut u64) -> ExtStatusCode;
+    pub fn oasis_balance(addr: *const Address, balance: *mut u128) -> ExtStatusCode;
 
     pub fn oasis_code(addr: *const Address, buf: *mut u8) -> ExtStatusCode;
     pub fn oasis_code_len(addr: *const Address, len: *mut u32) -> ExtStatusCode;
@@ -26,15 +26,32 @@ extern "C" {
 
     pub fn oasis_transact(
         callee: *const Address,
-        value: u64,
+        value: *const u128,
         input: *const u8,
         input_len: u32,
     ) -> ExtStatusCode;
 
+    pub fn oasis_upgrade(
+        addr: *const Address,
+        code: *const u8,
+        code_len: u32,
+        migration_input: *const u8,
+        migration_input_len: u32,
+    ) -> ExtStatusCode;
+
+    pub fn oasis_create(
+        value: *const u128,
+        code: *const u8,
+        code_len: u32,
+        ctor_input: *const u8,
+        ctor_input_len: u32,
+        addr: *mut Address,
+    ) -> ExtStatusCode;
+
     pub fn oasis_address(addr: *mut Address) -> ExtStatusCode;
     pub fn oasis_sender(addr: *mut Address) -> ExtStatusCode;
     pub fn oasis_payer(addr: *mut Address) -> ExtStatusCode;
-    pub fn oasis_value(value: *mut u64) -> ExtStatusCode;
+    pub fn oasis_value(value: *mut u128) -> ExtStatusCode;
 
     pub fn oasis_read(key: *const u8, key_len: u32, value: *mut u8) -> ExtStatusCode;
     pub fn oasis_read_len(key: *const u8, key_len: u32, value_len: *mut u32) -> ExtStatusCode;
@@ -61,6 +78,7 @@ impl From<ExtStatusCode> for Error {
             ExtStatusCode::InsufficientFunds => Error::InsufficientFunds,
             ExtStatusCode::InvalidInput => Error::InvalidInput,
             ExtStatusCode::NoAccount => Error::NoAccount,
+            ExtStatusCode::PermissionDenied => Error::PermissionDenied,
             code if code.0 <= u32::from(u8::max_value()) => Error::Unknown,
             code => Error::Execution {
                 code: code.0,
@@ -110,13 +128,13 @@ pub fn aad() -> Vec<u8> {
     aad
 }
 
-pub fn value() -> u64 {
+pub fn value() -> u128 {
     let mut value = 0;
     ext!(oasis_value(&mut value as *mut _)).unwrap();
     value
 }
 
-pub fn balance(addr: &Address) -> Option<u64> {
+pub fn balance(addr: &Address) -> Option<u128> {
     let mut balance = 0;
     ext!(oasis_balance(addr as *const _, &mut balance as *mut _))
         .ok()
@@ -139,10 +157,10 @@ pub fn code(addr: &Address) -> Option<Vec<u8>> {
         .map(|_| code)
 }
 
-pub fn transact(callee: &Address, value: u64, input: &[u8]) -> Result<Vec<u8>, Error> {
+pub fn transact(callee: &Address, value: u128, input: &[u8]) -> Result<Vec<u8>, Error> {
     ext!(oasis_transact(
         callee as *const _,
-        value,
+        &value as *const _,
         input.as_ptr(),
         if input.len() > u32::max_value() as usize {
             return Err(Error::InvalidInput);
@@ -150,7 +168,40 @@ pub fn transact(callee: &Address, value: u64, input: &[u8]) -> Result<Vec<u8>, E
             input.len() as u32
         },
     ))?;
+    fetch_ret()
+}
+
+pub fn upgrade(addr: &Address, code: &[u8], migration_input: &[u8]) -> Result<Vec<u8>, Error> {
+    if code.len() > u32::max_value() as usize || migration_input.len() > u32::max_value() as usize {
+        return Err(Error::InvalidInput);
+    }
+    ext!(oasis_upgrade(
+        addr as *const _,
+        code.as_ptr(),
+        code.len() as u32,
+        migration_input.as_ptr(),
+        migration_input.len() as u32,
+    ))?;
+    fetch_ret()
+}
+
+pub fn create(value: u128, code: &[u8], ctor_input: &[u8]) -> Result<Address, Error> {
+    if code.len() > u32::max_value() as usize || ctor_input.len() > u32::max_value() as usize {
+        return Err(Error::InvalidInput);
+    }
+    let mut addr = Address::default();
+    ext!(oasis_create(
+        &value as *const _,
+        code.as_ptr(),
+        code.len() as u32,
+        ctor_input.as_ptr(),
+        ctor_input.len() as u32,
+        &mut addr as *mut _,
+    ))?;
+    Ok(addr)
+}
 
+fn fetch_ret() -> Result<Vec<u8>, Error> {
     let mut ret_len = 0u32;
     ext!(oasis_ret_len(&mut ret_len as *mut _))?;
 
@@ -9,7 +9,8 @@ cfg_if::cfg_if! {
 }
 
 pub use imp::{
-    aad, address, balance, code, emit, err, input, payer, read, ret, sender, transact, value, write,
+    aad, address, balance, code, create, emit, err, input, payer, read, ret, sender, transact,
+    upgrade, value, write,
 };
 
 #[derive(Debug, Eq, PartialEq)]
@@ -26,6 +27,9 @@ pub enum Error {
     /// No account at destination of transaction
     NoAccount,
 
+    /// Caller is not permitted to perform the operation
+    PermissionDenied,
+
     /// Transaction failed with status code and payload
     Execution { code: u32, payload: Vec<u8> },
 }
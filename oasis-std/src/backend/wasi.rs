@@ -47,18 +47,25 @@ pub fn aad() -> Vec<u8> {
     base64::decode(&std::env::var_os("AAD").unwrap().into_vec()).unwrap()
 }
 
-pub fn value() -> u64 {
-    u64::from_str(&std::env::var("VALUE").unwrap()).unwrap()
+pub fn value() -> u128 {
+    u128::from_str(&std::env::var("VALUE").unwrap()).unwrap()
 }
 
-pub fn balance(addr: &Address) -> Option<u64> {
-    Some(unsafe {
-        *std::mem::transmute::<*const u8, *const u64>(match fs::read(home(&*addr, "balance")) {
-            Ok(balance) => balance.as_ptr(),
-            Err(err) if err.kind() == io::ErrorKind::NotFound => return None,
-            Err(err) => panic!(err),
-        })
-    })
+/// Returns the balance stored as little-endian bytes in the account's `balance` file.
+/// Both the 16-byte form and the older 8-byte form are accepted. Any other length
+/// is treated as a missing balance.
+pub fn balance(addr: &Address) -> Option<u128> {
+    let bytes = match fs::read(home(&*addr, "balance")) {
+        Ok(bytes) => bytes,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return None,
+        Err(err) => panic!(err),
+    };
+    let mut balance = [0u8; 16];
+    match bytes.len() {
+        8 | 16 => balance[..bytes.len()].copy_from_slice(&bytes),
+        _ => return None,
+    }
+    Some(u128::from_le_bytes(balance))
 }
 
 pub fn code(addr: &Address) -> Option<Vec<u8>> {
@@ -72,24 +79,79 @@ pub fn code(addr: &Address) -> Option<Vec<u8>> {
 extern "C" {
     fn __wasi_blockchain_transact(
         callee_addr: *const u8,
-        value: u64,
+        value: *const u128,
         input: *const u8,
         input_len: u64,
         fd: *mut __wasi_fd_t,
     ) -> __wasi_errno_t;
+
+    fn __wasi_blockchain_upgrade(
+        addr: *const u8,
+        code: *const u8,
+        code_len: u64,
+        migration_input: *const u8,
+        migration_input_len: u64,
+        fd: *mut __wasi_fd_t,
+    ) -> __wasi_errno_t;
+
+    fn __wasi_blockchain_create(
+        value: *const u128,
+        code: *const u8,
+        code_len: u64,
+        ctor_input: *const u8,
+        ctor_input_len: u64,
+        addr: *mut u8,
+        fd: *mut __wasi_fd_t,
+    ) -> __wasi_errno_t;
 }
 
-pub fn transact(callee: &Address, value: u64, input: &[u8]) -> Result<Vec<u8>, Error> {
+pub fn transact(callee: &Address, value: u128, input: &[u8]) -> Result<Vec<u8>, Error> {
     let mut fd: __wasi_fd_t = 0;
     let errno = unsafe {
         __wasi_blockchain_transact(
             callee.0.as_ptr(),
-            value,
+            &value as *const _,
             input.as_ptr(),
             input.len() as u64,
             &mut fd as *mut _,
         )
     };
+    read_output(fd, errno)
+}
+
+pub fn upgrade(addr: &Address, code: &[u8], migration_input: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut fd: __wasi_fd_t = 0;
+    let errno = unsafe {
+        __wasi_blockchain_upgrade(
+            addr.0.as_ptr(),
+            code.as_ptr(),
+            code.len() as u64,
+            migration_input.as_ptr(),
+            migration_input.len() as u64,
+            &mut fd as *mut _,
+        )
+    };
+    read_output(fd, errno)
+}
+
+pub fn create(value: u128, code: &[u8], ctor_input: &[u8]) -> Result<Address, Error> {
+    let mut addr = Address::default();
+    let mut fd: __wasi_fd_t = 0;
+    let errno = unsafe {
+        __wasi_blockchain_create(
+            &value as *const _,
+            code.as_ptr(),
+            code.len() as u64,
+            ctor_input.as_ptr(),
+            ctor_input.len() as u64,
+            addr.0.as_mut_ptr(),
+            &mut fd as *mut _,
+        )
+    };
+    read_output(fd, errno).map(|_| addr)
+}
+
+fn read_output(fd: __wasi_fd_t, errno: __wasi_errno_t) -> Result<Vec<u8>, Error> {
     let mut f_out = unsafe { fs::File::from_raw_fd(fd) };
     let mut out = Vec::new();
     f_out
@@ -100,6 +162,7 @@ pub fn transact(callee: &Address, value: u64, input: &[u8]) -> Result<Vec<u8>, E
         libc::__WASI_EFAULT | libc::__WASI_EINVAL => return Err(Error::InvalidInput),
         libc::__WASI_ENOENT => return Err(Error::NoAccount),
         libc::__WASI_EDQUOT => return Err(Error::InsufficientFunds),
+        libc::__WASI_EACCES => return Err(Error::PermissionDenied),
         _ => {
             return Err(Error::Execution {
                 code: errno as u32,
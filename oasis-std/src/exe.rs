@@ -24,7 +24,7 @@ pub struct Context {
     pub sender: Option<Address>,
 
     #[doc(hidden)]
-    pub value: Option<u64>,
+    pub value: Option<u128>,
 
     #[doc(hidden)]
     pub gas: Option<u64>,
@@ -78,7 +78,7 @@ impl Context {
     }
 
     /// Returns the value with which this `Context` was created.
-    pub fn value(&self) -> u64 {
+    pub fn value(&self) -> u128 {
         self.value.unwrap_or_else(crate::backend::value)
     }
 }
@@ -92,7 +92,7 @@ impl Context {
     }
 
     /// Amends a Context with the value that should be transferred to the callee.
-    pub fn with_value(mut self, value: u64) -> Self {
+    pub fn with_value(mut self, value: u128) -> Self {
         self.value = Some(value);
         self
     }
@@ -109,6 +109,12 @@ pub enum RpcError<T> {
     /// The caller did not provide enough gas to complete the transaction.
     InsufficientGas,
 
+    /// The caller is not permitted to perform the requested operation.
+    PermissionDenied,
+
+    /// The caller sent value to an RPC, or a batch, that is not payable.
+    NotPayable,
+
     InvalidInput,
 
     InvalidOutput(Vec<u8>),
@@ -125,6 +131,10 @@ impl<T: serde::de::DeserializeOwned> From<crate::backend::Error> for RpcError<T>
             BackendError::InsufficientFunds => RpcError::InsufficientFunds,
             BackendError::InvalidInput => RpcError::InvalidInput,
             BackendError::NoAccount => RpcError::NoAccount,
+            BackendError::PermissionDenied => RpcError::PermissionDenied,
+            BackendError::Execution { ref payload, .. } if payload == NOT_PAYABLE_ERROR => {
+                RpcError::NotPayable
+            }
             BackendError::Execution { payload, .. } => {
                 RpcError::Exec(match serde_cbor::from_slice::<T>(&payload) {
                     Ok(t) => t,
@@ -134,3 +144,209 @@ impl<T: serde::de::DeserializeOwned> From<crate::backend::Error> for RpcError<T>
         }
     }
 }
+
+/// The error with which the dispatcher of a service rejects a call that sends value to an RPC,
+/// or a batch, that is not payable. It is a tagged CBOR `null`, so it cannot be mistaken for
+/// an RPC's own error, which is serialized without tags.
+#[doc(hidden)]
+pub const NOT_PAYABLE_ERROR: &[u8] = &[0xd8, 0x50, 0xf6];
+
+/// Encodes a call to the RPC method named `method` as the `{"method": .., "payload": ..}` map
+/// decoded by the dispatcher of a service. `args` is the tuple of the method's arguments.
+#[doc(hidden)]
+pub fn encode_named_call<T: serde::Serialize>(method: &str, args: &T) -> Vec<u8> {
+    #[derive(Serialize)]
+    struct NamedCall<'a, T> {
+        method: &'a str,
+        payload: &'a T,
+    }
+    serde_cbor::to_vec(&NamedCall {
+        method,
+        payload: args,
+    })
+    .unwrap()
+}
+
+/// The position in a batch of a call whose output is a `T` and whose error is an `E`.
+/// Returned by the methods of a generated client's batch builder.
+pub struct BatchCall<T, E> {
+    index: usize,
+    _output: std::marker::PhantomData<(T, E)>,
+}
+
+impl<T, E> BatchCall<T, E> {
+    #[doc(hidden)]
+    pub fn new(index: usize) -> Self {
+        Self {
+            index,
+            _output: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<T, E> Clone for BatchCall<T, E> {
+    fn clone(&self) -> Self {
+        Self::new(self.index)
+    }
+}
+
+impl<T, E> Copy for BatchCall<T, E> {}
+
+/// The outputs of the calls in a batch, all of which succeeded.
+#[derive(Clone, Debug)]
+pub struct BatchOutputs(Vec<Vec<u8>>);
+
+impl BatchOutputs {
+    /// Decodes the output of a batch, which is an array of the calls' outputs.
+    pub fn from_output(output: Vec<u8>) -> Result<Self, RpcError<BatchError>> {
+        let outputs = match serde_cbor::from_slice::<Vec<serde_cbor::Value>>(&output) {
+            Ok(outputs) => outputs,
+            Err(_) => return Err(RpcError::InvalidOutput(output)),
+        };
+        let mut decoded = Vec::with_capacity(outputs.len());
+        for call_output in outputs {
+            match call_output {
+                serde_cbor::Value::Bytes(call_output) => decoded.push(call_output),
+                _ => return Err(RpcError::InvalidOutput(output)),
+            }
+        }
+        Ok(BatchOutputs(decoded))
+    }
+
+    /// Returns the output of `call`.
+    pub fn get<T: serde::de::DeserializeOwned, E>(
+        &self,
+        call: BatchCall<T, E>,
+    ) -> Result<T, RpcError<BatchError>> {
+        let output = self
+            .0
+            .get(call.index)
+            .ok_or_else(|| RpcError::InvalidOutput(Vec::new()))?;
+        serde_cbor::from_slice(output).map_err(|_| RpcError::InvalidOutput(output.clone()))
+    }
+}
+
+/// The error of a batch in which a call failed. None of the calls in the batch took effect.
+#[derive(Clone, Debug)]
+pub struct BatchError {
+    /// The position of the failed call in the batch.
+    pub index: usize,
+
+    /// The error returned by the failed call.
+    pub error: Vec<u8>,
+}
+
+impl BatchError {
+    /// Decodes the error returned by the failed call if the failed call was `call`.
+    pub fn decode<T, E: serde::de::DeserializeOwned>(&self, call: BatchCall<T, E>) -> Option<E> {
+        if call.index == self.index {
+            serde_cbor::from_slice(&self.error).ok()
+        } else {
+            None
+        }
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for BatchError {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (index, error): (usize, serde_cbor::Value) =
+            serde::Deserialize::deserialize(deserializer)?;
+        match error {
+            serde_cbor::Value::Bytes(error) => Ok(Self { index, error }),
+            _ => Err(serde::de::Error::custom(
+                "expected the error of the failed call",
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The payload enum generated by the dispatcher of a service.
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(tag = "method", content = "payload")]
+    #[allow(non_camel_case_types)]
+    enum RpcPayload {
+        void(),
+        one((u32,)),
+        two((String, Vec<u8>)),
+    }
+
+    #[test]
+    fn test_named_call() {
+        let decode = |call: Vec<u8>| serde_cbor::from_slice::<RpcPayload>(&call).unwrap();
+        assert_eq!(
+            decode(encode_named_call("void", &[(); 0])),
+            RpcPayload::void()
+        );
+        assert_eq!(
+            decode(encode_named_call("one", &(7u32,))),
+            RpcPayload::one((7,))
+        );
+        assert_eq!(
+            decode(encode_named_call("two", &("a", vec![1u8, 2]))),
+            RpcPayload::two(("a".to_string(), vec![1, 2]))
+        );
+    }
+
+    #[test]
+    fn test_batch_outputs() {
+        let output = serde_cbor::to_vec(&vec![
+            serde_cbor::Value::Bytes(serde_cbor::to_vec(&()).unwrap()),
+            serde_cbor::Value::Bytes(serde_cbor::to_vec(&42u32).unwrap()),
+        ])
+        .unwrap();
+        let outputs = BatchOutputs::from_output(output).unwrap();
+        outputs.get(BatchCall::<(), ()>::new(0)).unwrap();
+        assert_eq!(outputs.get(BatchCall::<u32, ()>::new(1)).unwrap(), 42);
+        match outputs.get(BatchCall::<String, ()>::new(1)) {
+            Err(RpcError::InvalidOutput(output)) => assert_eq!(output, vec![0x18, 42]),
+            res => panic!("unexpected {:?}", res),
+        }
+        match outputs.get(BatchCall::<u32, ()>::new(2)) {
+            Err(RpcError::InvalidOutput(output)) => assert!(output.is_empty()),
+            res => panic!("unexpected {:?}", res),
+        }
+
+        // Each output must be a byte string.
+        let output = serde_cbor::to_vec(&vec![42u32]).unwrap();
+        match BatchOutputs::from_output(output.clone()) {
+            Err(RpcError::InvalidOutput(invalid)) => assert_eq!(invalid, output),
+            res => panic!("unexpected {:?}", res),
+        }
+        match BatchOutputs::from_output(vec![0xff]) {
+            Err(RpcError::InvalidOutput(invalid)) => assert_eq!(invalid, vec![0xff]),
+            res => panic!("unexpected {:?}", res),
+        }
+    }
+
+    #[test]
+    fn test_batch_error() {
+        let failure = |payload: Vec<u8>| {
+            RpcError::<BatchError>::from(crate::backend::Error::Execution { code: 1, payload })
+        };
+
+        let call_error = serde_cbor::to_vec(&"insufficient balance").unwrap();
+        let payload = serde_cbor::to_vec(&(1u32, serde_cbor::Value::Bytes(call_error))).unwrap();
+        let err = match failure(payload) {
+            RpcError::Exec(err) => err,
+            err => panic!("unexpected {:?}", err),
+        };
+        assert_eq!(err.index, 1);
+        assert_eq!(
+            err.decode(BatchCall::<(), String>::new(1)),
+            Some("insufficient balance".to_string())
+        );
+        assert_eq!(err.decode(BatchCall::<(), String>::new(0)), None);
+        assert_eq!(err.decode(BatchCall::<(), u32>::new(1)), None);
+
+        // The error of the failed call must be a byte string.
+        let payload = serde_cbor::to_vec(&(1u32, "insufficient balance")).unwrap();
+        match failure(payload.clone()) {
+            RpcError::InvalidOutput(invalid) => assert_eq!(invalid, payload),
+            err => panic!("unexpected {:?}", err),
+        }
+    }
+}
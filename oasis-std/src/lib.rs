@@ -10,6 +10,7 @@
 pub extern crate serde;
 extern crate oasis_macros;
 
+pub mod access;
 pub mod backend;
 pub mod exe;
 
@@ -19,8 +20,8 @@ pub mod reexports {
     pub use tiny_keccak;
 }
 
-pub use oasis_macros::{default, Event, Service};
-pub use oasis_types::Address;
+pub use oasis_macros::{default, migrate, only, payable, when_not_paused, Event, Service};
+pub use oasis_types::{Address, I128, U128, U256};
 
 pub use crate::exe::*;
 
@@ -39,23 +40,40 @@ macro_rules! service {
 }
 
 pub trait AddressExt {
-    fn transfer(&self, value: u64) -> Result<(), crate::backend::Error>;
+    fn transfer(&self, value: u128) -> Result<(), crate::backend::Error>;
 
-    fn balance(&self) -> u64;
+    fn balance(&self) -> u128;
 
     fn code(&self) -> Vec<u8>;
+
+    /// Replaces the code of the service at this address with `code` and calls the new
+    /// code's migration function, if any, with `migration_input`.
+    /// Only the service itself or its admin may perform an upgrade.
+    fn upgrade(
+        &self,
+        code: &[u8],
+        migration_input: &[u8],
+    ) -> Result<Vec<u8>, crate::backend::Error>;
 }
 
 impl AddressExt for Address {
-    fn transfer(&self, value: u64) -> Result<(), crate::backend::Error> {
+    fn transfer(&self, value: u128) -> Result<(), crate::backend::Error> {
         crate::backend::transact(self, value, &[]).map(|_| ())
     }
 
-    fn balance(&self) -> u64 {
+    fn balance(&self) -> u128 {
         crate::backend::balance(self).unwrap()
     }
 
     fn code(&self) -> Vec<u8> {
         crate::backend::code(self).unwrap()
     }
+
+    fn upgrade(
+        &self,
+        code: &[u8],
+        migration_input: &[u8],
+    ) -> Result<Vec<u8>, crate::backend::Error> {
+        crate::backend::upgrade(self, code, migration_input)
+    }
 }
@@ -6,7 +6,7 @@ use oasis_types::{Address, ExtStatusCode};
 static oasis_testing: bool = true;
 
 #[no_mangle]
-pub extern "C" fn oasis_balance(addr: *const Address, balance: *mut u64) -> ExtStatusCode {
+pub extern "C" fn oasis_balance(addr: *const Address, balance: *mut u128) -> ExtStatusCode {
     ExtStatusCode::Success
 }
 
@@ -73,13 +73,38 @@ pub extern "C" fn oasis_err_len(len: *mut u32) -> ExtStatusCode {
 #[no_mangle]
 pub extern "C" fn oasis_transact(
     callee: *const Address,
-    value: u64,
+    value: *const u128,
     input: *const u8,
     input_len: u32,
 ) -> ExtStatusCode {
     ExtStatusCode::Success
 }
 
+#[no_mangle]
+pub extern "C" fn oasis_upgrade(
+    addr: *const Address,
+    code: *const u8,
+    code_len: u32,
+    migration_input: *const u8,
+    migration_input_len: u32,
+) -> ExtStatusCode {
+    ExtStatusCode::Success
+}
+
+#[no_mangle]
+pub extern "C" fn oasis_create(
+    value: *const u128,
+    code: *const u8,
+    code_len: u32,
+    ctor_input: *const u8,
+    ctor_input_len: u32,
+    addr: *mut Address,
+) -> ExtStatusCode {
+    // The constructor is not run, but the client is bound to a new account holding `value`.
+    unsafe { *addr = crate::create_account(*value) };
+    ExtStatusCode::Success
+}
+
 #[no_mangle]
 pub extern "C" fn oasis_address(addr: *mut Address) -> ExtStatusCode {
     ExtStatusCode::Success
@@ -91,7 +116,7 @@ pub extern "C" fn oasis_sender(addr: *mut Address) -> ExtStatusCode {
 }
 
 #[no_mangle]
-pub extern "C" fn oasis_value(value: *mut u64) -> ExtStatusCode {
+pub extern "C" fn oasis_value(value: *mut u128) -> ExtStatusCode {
     ExtStatusCode::Success
 }
 
@@ -0,0 +1,121 @@
+macro_rules! impl_int128 {
+    ($name:ident, $int:ty, $doc:literal) => {
+        #[doc = $doc]
+        /// Serialized as a 16-byte big-endian byte string, since CBOR integers hold
+        /// at most 64 bits of magnitude.
+        #[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $name(pub $int);
+
+        impl $name {
+            pub fn from_be_bytes(bytes: [u8; 16]) -> Self {
+                $name(<$int>::from_be_bytes(bytes))
+            }
+
+            pub fn to_be_bytes(self) -> [u8; 16] {
+                self.0.to_be_bytes()
+            }
+        }
+
+        impl From<$int> for $name {
+            fn from(n: $int) -> Self {
+                $name(n)
+            }
+        }
+
+        impl From<$name> for $int {
+            fn from(n: $name) -> Self {
+                n.0
+            }
+        }
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                std::fmt::Display::fmt(&self.0, f)
+            }
+        }
+
+        impl std::fmt::Debug for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                std::fmt::Display::fmt(&self.0, f)
+            }
+        }
+
+        impl std::str::FromStr for $name {
+            type Err = std::num::ParseIntError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                s.parse().map($name)
+            }
+        }
+
+        impl serde::Serialize for $name {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_bytes(&self.to_be_bytes())
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for $name {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                struct Visitor;
+
+                impl<'de> serde::de::Visitor<'de> for Visitor {
+                    type Value = $name;
+
+                    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                        write!(f, "16 big-endian bytes")
+                    }
+
+                    fn visit_bytes<E: serde::de::Error>(self, bytes: &[u8]) -> Result<$name, E> {
+                        if bytes.len() != 16 {
+                            return Err(E::invalid_length(bytes.len(), &self));
+                        }
+                        let mut be_bytes = [0u8; 16];
+                        be_bytes.copy_from_slice(bytes);
+                        Ok($name::from_be_bytes(be_bytes))
+                    }
+
+                    fn visit_seq<A: serde::de::SeqAccess<'de>>(
+                        self,
+                        mut seq: A,
+                    ) -> Result<$name, A::Error> {
+                        let mut bytes = Vec::with_capacity(16);
+                        while let Some(byte) = seq.next_element()? {
+                            bytes.push(byte);
+                        }
+                        self.visit_bytes(&bytes)
+                    }
+                }
+
+                deserializer.deserialize_bytes(Visitor)
+            }
+        }
+    };
+}
+
+impl_int128!(U128, u128, "A 128-bit unsigned integer, e.g., a balance.");
+impl_int128!(I128, i128, "A 128-bit signed integer.");
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bytes() {
+        let n = U128(u128::from(u64::max_value()) + 1);
+        let bytes = n.to_be_bytes();
+        assert_eq!(&bytes[..8], &[0, 0, 0, 0, 0, 0, 0, 1]);
+        assert_eq!(U128::from_be_bytes(bytes), n);
+        assert_eq!(I128(-1).to_be_bytes(), [0xff; 16]);
+        assert_eq!(I128::from_be_bytes([0xff; 16]), I128(-1));
+    }
+
+    #[test]
+    fn test_fmt_and_parse() {
+        assert_eq!(
+            U128(u128::max_value()).to_string(),
+            u128::max_value().to_string()
+        );
+        assert_eq!("-18446744073709551616".parse(), Ok(I128(-(1 << 64))));
+        assert!("-1".parse::<U128>().is_err());
+    }
+}
@@ -1,6 +1,12 @@
 #[macro_use]
 extern crate serde;
 
+mod int128;
+mod u256;
+
+pub use int128::{I128, U128};
+pub use u256::{ParseU256Error, U256};
+
 /// A 160-bit little-endian hash address type.
 #[derive(
     Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Debug, Hash, Serialize, Deserialize,
@@ -68,15 +74,16 @@ impl ExtStatusCode {
     pub const InsufficientFunds: ExtStatusCode = ExtStatusCode(1);
     pub const InvalidInput: ExtStatusCode = ExtStatusCode(2);
     pub const NoAccount: ExtStatusCode = ExtStatusCode(3);
+    pub const PermissionDenied: ExtStatusCode = ExtStatusCode(4);
 }
 
 pub struct AccountMeta {
-    pub balance: u64,
+    pub balance: u128,
     pub expiry: Option<std::time::Duration>,
 }
 
 impl blockchain_traits::AccountMeta for AccountMeta {
-    fn balance(&self) -> u64 {
+    fn balance(&self) -> u128 {
         self.balance
     }
 }
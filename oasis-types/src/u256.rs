@@ -0,0 +1,375 @@
+use std::{
+    cmp::Ordering,
+    ops::{Add, AddAssign, Div, Mul, MulAssign, Rem, Sub, SubAssign},
+};
+
+/// A 256-bit unsigned integer, e.g., a token amount.
+/// Serialized as a 32-byte big-endian byte string.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct U256([u64; 4]); // little-endian limbs
+
+impl U256 {
+    pub const fn zero() -> Self {
+        U256([0; 4])
+    }
+
+    pub const fn max_value() -> Self {
+        U256([u64::max_value(); 4])
+    }
+
+    pub fn from_be_bytes(bytes: [u8; 32]) -> Self {
+        let mut limbs = [0u64; 4];
+        for (i, limb) in limbs.iter_mut().enumerate() {
+            let mut limb_bytes = [0u8; 8];
+            limb_bytes.copy_from_slice(&bytes[(3 - i) * 8..(4 - i) * 8]);
+            *limb = u64::from_be_bytes(limb_bytes);
+        }
+        U256(limbs)
+    }
+
+    pub fn to_be_bytes(self) -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        for (i, limb) in self.0.iter().enumerate() {
+            bytes[(3 - i) * 8..(4 - i) * 8].copy_from_slice(&limb.to_be_bytes());
+        }
+        bytes
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.0 == [0; 4]
+    }
+
+    /// Returns the value as a `u128` if it fits.
+    pub fn to_u128(&self) -> Option<u128> {
+        if self.0[2] != 0 || self.0[3] != 0 {
+            return None;
+        }
+        Some(u128::from(self.0[1]) << 64 | u128::from(self.0[0]))
+    }
+
+    pub fn overflowing_add(self, other: Self) -> (Self, bool) {
+        let mut sum = [0u64; 4];
+        let mut carry = false;
+        for (i, limb) in sum.iter_mut().enumerate() {
+            let (s, c1) = self.0[i].overflowing_add(other.0[i]);
+            let (s, c2) = s.overflowing_add(carry as u64);
+            *limb = s;
+            carry = c1 || c2;
+        }
+        (U256(sum), carry)
+    }
+
+    pub fn overflowing_sub(self, other: Self) -> (Self, bool) {
+        let mut diff = [0u64; 4];
+        let mut borrow = false;
+        for (i, limb) in diff.iter_mut().enumerate() {
+            let (d, b1) = self.0[i].overflowing_sub(other.0[i]);
+            let (d, b2) = d.overflowing_sub(borrow as u64);
+            *limb = d;
+            borrow = b1 || b2;
+        }
+        (U256(diff), borrow)
+    }
+
+    pub fn checked_add(self, other: Self) -> Option<Self> {
+        match self.overflowing_add(other) {
+            (sum, false) => Some(sum),
+            _ => None,
+        }
+    }
+
+    pub fn checked_sub(self, other: Self) -> Option<Self> {
+        match self.overflowing_sub(other) {
+            (diff, false) => Some(diff),
+            _ => None,
+        }
+    }
+
+    pub fn checked_mul(self, other: Self) -> Option<Self> {
+        let mut product = [0u64; 8];
+        for i in 0..4 {
+            let mut carry = 0u128;
+            for j in 0..4 {
+                let p = u128::from(self.0[i]) * u128::from(other.0[j])
+                    + u128::from(product[i + j])
+                    + carry;
+                product[i + j] = p as u64;
+                carry = p >> 64;
+            }
+            product[i + 4] = carry as u64;
+        }
+        if product[4..].iter().any(|&limb| limb != 0) {
+            return None;
+        }
+        let mut limbs = [0u64; 4];
+        limbs.copy_from_slice(&product[..4]);
+        Some(U256(limbs))
+    }
+
+    /// Returns the quotient and remainder of `self / other`, or `None` if `other` is zero.
+    pub fn checked_div_rem(self, other: Self) -> Option<(Self, Self)> {
+        if other.is_zero() {
+            return None;
+        }
+        let mut quotient = Self::zero();
+        let mut remainder = Self::zero();
+        for bit in (0..256).rev() {
+            remainder = remainder.shl1();
+            remainder.0[0] |= self.bit(bit) as u64;
+            if remainder >= other {
+                remainder = remainder.overflowing_sub(other).0;
+                quotient.0[bit / 64] |= 1 << (bit % 64);
+            }
+        }
+        Some((quotient, remainder))
+    }
+
+    pub fn checked_div(self, other: Self) -> Option<Self> {
+        self.checked_div_rem(other).map(|(quotient, _)| quotient)
+    }
+
+    fn bit(&self, bit: usize) -> bool {
+        self.0[bit / 64] >> (bit % 64) & 1 == 1
+    }
+
+    fn shl1(self) -> Self {
+        let mut limbs = [0u64; 4];
+        for (i, limb) in limbs.iter_mut().enumerate() {
+            *limb = self.0[i] << 1 | if i > 0 { self.0[i - 1] >> 63 } else { 0 };
+        }
+        U256(limbs)
+    }
+}
+
+macro_rules! impl_from_uint {
+    ($($uint:ty),*) => {
+        $(
+            impl From<$uint> for U256 {
+                fn from(n: $uint) -> Self {
+                    U256::from(u128::from(n))
+                }
+            }
+        )*
+    };
+}
+
+impl_from_uint!(u8, u16, u32, u64);
+
+impl From<u128> for U256 {
+    fn from(n: u128) -> Self {
+        U256([n as u64, (n >> 64) as u64, 0, 0])
+    }
+}
+
+impl Ord for U256 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.iter().rev().cmp(other.0.iter().rev())
+    }
+}
+
+impl PartialOrd for U256 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+macro_rules! impl_op {
+    ($op:ident, $op_fn:ident, $op_assign:ident, $op_assign_fn:ident, $checked_fn:ident) => {
+        impl $op for U256 {
+            type Output = Self;
+
+            fn $op_fn(self, other: Self) -> Self {
+                self.$checked_fn(other).expect(concat!(
+                    "attempt to ",
+                    stringify!($op_fn),
+                    " with overflow"
+                ))
+            }
+        }
+
+        impl $op_assign for U256 {
+            fn $op_assign_fn(&mut self, other: Self) {
+                *self = $op::$op_fn(*self, other);
+            }
+        }
+    };
+}
+
+impl_op!(Add, add, AddAssign, add_assign, checked_add);
+impl_op!(Sub, sub, SubAssign, sub_assign, checked_sub);
+impl_op!(Mul, mul, MulAssign, mul_assign, checked_mul);
+
+impl Div for U256 {
+    type Output = Self;
+
+    fn div(self, other: Self) -> Self {
+        self.checked_div(other).expect("attempt to divide by zero")
+    }
+}
+
+impl Rem for U256 {
+    type Output = Self;
+
+    fn rem(self, other: Self) -> Self {
+        self.checked_div_rem(other)
+            .expect("attempt to calculate the remainder with a divisor of zero")
+            .1
+    }
+}
+
+impl std::fmt::Display for U256 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_zero() {
+            return f.pad_integral(true, "", "0");
+        }
+        let ten = U256::from(10u8);
+        let mut digits = Vec::new();
+        let mut n = *self;
+        while !n.is_zero() {
+            let (quotient, digit) = n.checked_div_rem(ten).unwrap();
+            digits.push(b'0' + digit.0[0] as u8);
+            n = quotient;
+        }
+        digits.reverse();
+        f.pad_integral(true, "", std::str::from_utf8(&digits).unwrap())
+    }
+}
+
+impl std::fmt::Debug for U256 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Display::fmt(self, f)
+    }
+}
+
+impl std::fmt::LowerHex for U256 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let hex = hex::encode(self.to_be_bytes());
+        let digits = hex.trim_start_matches('0');
+        f.pad_integral(true, "0x", if digits.is_empty() { "0" } else { digits })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParseU256Error {
+    Empty,
+    InvalidDigit,
+    Overflow,
+}
+
+impl std::fmt::Display for ParseU256Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            ParseU256Error::Empty => "cannot parse integer from empty string",
+            ParseU256Error::InvalidDigit => "invalid digit found in string",
+            ParseU256Error::Overflow => "number too large to fit in a U256",
+        })
+    }
+}
+
+impl std::error::Error for ParseU256Error {}
+
+impl std::str::FromStr for U256 {
+    type Err = ParseU256Error;
+
+    /// Parses a decimal number or, if prefixed with `0x`, a hexadecimal number.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (digits, radix) = if s.starts_with("0x") {
+            (&s[2..], 16u8)
+        } else {
+            (s, 10u8)
+        };
+        if digits.is_empty() {
+            return Err(ParseU256Error::Empty);
+        }
+        digits.chars().try_fold(U256::zero(), |n, ch| {
+            let digit = ch
+                .to_digit(u32::from(radix))
+                .ok_or(ParseU256Error::InvalidDigit)?;
+            n.checked_mul(U256::from(radix))
+                .and_then(|n| n.checked_add(U256::from(digit)))
+                .ok_or(ParseU256Error::Overflow)
+        })
+    }
+}
+
+impl serde::Serialize for U256 {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(&self.to_be_bytes())
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for U256 {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct U256Visitor;
+
+        impl<'de> serde::de::Visitor<'de> for U256Visitor {
+            type Value = U256;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                write!(f, "at most 32 big-endian bytes")
+            }
+
+            fn visit_bytes<E: serde::de::Error>(self, bytes: &[u8]) -> Result<U256, E> {
+                if bytes.len() > 32 {
+                    return Err(E::invalid_length(bytes.len(), &self));
+                }
+                let mut be_bytes = [0u8; 32];
+                be_bytes[32 - bytes.len()..].copy_from_slice(bytes);
+                Ok(U256::from_be_bytes(be_bytes))
+            }
+
+            fn visit_seq<A: serde::de::SeqAccess<'de>>(self, mut seq: A) -> Result<U256, A::Error> {
+                let mut bytes = Vec::with_capacity(32);
+                while let Some(byte) = seq.next_element()? {
+                    bytes.push(byte);
+                }
+                self.visit_bytes(&bytes)
+            }
+        }
+
+        deserializer.deserialize_bytes(U256Visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arithmetic() {
+        let max_u128 = U256::from(u128::max_value());
+        let sum = max_u128 + U256::from(1u8);
+        assert_eq!(sum.to_u128(), None);
+        assert_eq!(sum - U256::from(1u8), max_u128);
+        assert_eq!((max_u128 * max_u128) / max_u128, max_u128);
+        assert_eq!(sum % U256::from(10u8), U256::from(6u8));
+        assert_eq!(U256::max_value().checked_add(U256::from(1u8)), None);
+        assert_eq!(U256::zero().checked_sub(U256::from(1u8)), None);
+        assert_eq!(U256::max_value().checked_mul(U256::from(2u8)), None);
+        assert!(sum > max_u128);
+    }
+
+    #[test]
+    fn test_fmt_and_parse() {
+        let n: U256 = "340282366920938463463374607431768211456".parse().unwrap();
+        assert_eq!(n, U256::from(u128::max_value()) + U256::from(1u8));
+        assert_eq!(n.to_string(), "340282366920938463463374607431768211456");
+        assert_eq!(format!("{:x}", n), "100000000000000000000000000000000");
+        assert_eq!("0xff".parse::<U256>(), Ok(U256::from(255u8)));
+        assert_eq!(U256::zero().to_string(), "0");
+        assert_eq!("".parse::<U256>(), Err(ParseU256Error::Empty));
+        assert_eq!("1a".parse::<U256>(), Err(ParseU256Error::InvalidDigit));
+        assert_eq!(
+            format!("1{}", U256::max_value()).parse::<U256>(),
+            Err(ParseU256Error::Overflow)
+        );
+    }
+
+    #[test]
+    fn test_bytes() {
+        let n = U256::from(0x0102u16);
+        let bytes = n.to_be_bytes();
+        assert_eq!(&bytes[30..], &[1, 2]);
+        assert_eq!(U256::from_be_bytes(bytes), n);
+    }
+}
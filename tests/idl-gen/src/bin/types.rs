@@ -5,7 +5,7 @@ extern crate serde;
 
 use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 
-use oasis_std::{Address, Context, Event, Service};
+use oasis_std::{Address, Context, Event, Service, I128, U128, U256};
 use map_vec::{Map, Set};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -23,11 +23,22 @@ pub struct TestEvent {
     non_indexed: (u32, u32),
 }
 
+/// A service that exercises IDL generation.
 #[derive(Service)]
-pub struct TestService {}
+pub struct TestService {
+    access: oasis_std::access::AccessControl,
+}
+
+impl oasis_std::access::AccessControlled for TestService {
+    fn access_control(&self) -> &oasis_std::access::AccessControl {
+        &self.access
+    }
+}
 
+/// A user-defined type.
 #[derive(Serialize, Deserialize, Clone, Default)]
 pub struct DefTy {
+    /// An optional integer.
     f1: Option<i64>,
     f2: Vec<Option<DefTy>>,
     f3: HashMap<String, InnerTy>,
@@ -45,6 +56,18 @@ pub struct TestEvent2 {
     indexed2: u32,
 }
 
+/// The errors returned by `transfer`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum Error {
+    InsufficientFunds {
+        /// The account lacking funds.
+        address: Address,
+    },
+    Rejected(String),
+    Clamped(u64, Option<InnerTy>),
+    Unknown,
+}
+
 type Result<T> = std::result::Result<T, ()>;
 
 impl TestService {
@@ -78,9 +101,31 @@ impl TestService {
     }
 
     #[oasis_std::default]
+    #[oasis_std::payable]
     pub fn the_default_fn(&mut self, ctx: &Context) -> std::result::Result<Option<u64>, Set<u32>> {
         unimplemented!()
     }
+
+    /// Transfers tokens to another account.
+    ///
+    /// # Arguments
+    ///
+    /// * `to` - the recipient
+    /// * `amount` - the number of tokens
+    pub fn transfer(
+        &mut self,
+        ctx: &Context,
+        to: Address,
+        amount: u64,
+    ) -> std::result::Result<(), Error> {
+        unimplemented!()
+    }
+
+    #[oasis_std::only(owner, role = "minter")]
+    #[oasis_std::when_not_paused]
+    pub fn mint(&mut self, ctx: &Context, amount: U128, cap: U256) -> I128 {
+        unimplemented!()
+    }
 }
 
 fn main() {
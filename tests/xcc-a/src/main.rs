@@ -12,6 +12,12 @@ impl ServiceA {
         let b = xcc_b::ServiceBClient::at(b_addr);
         Ok(b.random(&Context::default(), xcc_b::Number(42)).unwrap())
     }
+
+    pub fn deploy_b(&mut self, _ctx: &Context, seed: u8) -> Result<Address, ()> {
+        let b = xcc_b::ServiceBClient::new(&Context::default(), xcc_b::Number(seed))
+            .map_err(|_| ())?;
+        Ok(b.address)
+    }
 }
 
 fn main() {